@@ -0,0 +1,23 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::io::Cursor;
+use wvb::{BundleDescriptor, BundleReader, ReadLimits, Reader};
+
+fuzz_target!(|data: &[u8]| {
+  let limits = ReadLimits::new();
+  let readers = [
+    BundleReader::new(Cursor::new(data)),
+    BundleReader::new_with_limits(Cursor::new(data), limits),
+  ];
+  for mut reader in readers {
+    let descriptor: BundleDescriptor = match reader.read() {
+      Ok(x) => x,
+      Err(_) => continue,
+    };
+    for path in descriptor.index().entries().keys() {
+      let _ = descriptor.get_data(Cursor::new(data), path);
+      let _ = descriptor.get_data_checksum(Cursor::new(data), path);
+    }
+  }
+});
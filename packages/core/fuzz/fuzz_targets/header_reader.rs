@@ -0,0 +1,10 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::io::Cursor;
+use wvb::{HeaderReader, HeaderReaderOptions, Reader};
+
+fuzz_target!(|data: &[u8]| {
+  let options = HeaderReaderOptions::new().verify_checksum(true);
+  let _ = HeaderReader::new_with_options(Cursor::new(data), options).read();
+});
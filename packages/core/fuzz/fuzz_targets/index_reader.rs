@@ -0,0 +1,18 @@
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use std::io::Cursor;
+use wvb::{Header, HeaderWriter, IndexReader, IndexReaderOptions, Reader, Version, Writer};
+
+// the input is read as the index section of a bundle with a valid header
+fuzz_target!(|data: &[u8]| {
+  let header = Header::new(Version::V1, data.len().saturating_sub(4) as u32);
+  let mut buf = vec![];
+  HeaderWriter::new(Cursor::new(&mut buf))
+    .write(&header)
+    .unwrap();
+  buf.extend_from_slice(data);
+  let options = IndexReaderOptions::new().verify_checksum(true);
+  let _ = IndexReader::new_with_options(Cursor::new(&buf), header, options).read();
+  let _ = IndexReader::new(Cursor::new(&buf), header).read_index();
+});
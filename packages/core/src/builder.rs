@@ -1,6 +1,7 @@
 use crate::checksum::{CHECKSUM_LEN, make_checksum};
 use crate::header::HeaderWriterOptions;
 use crate::index::{Index, IndexEntry, IndexWriterOptions};
+use crate::metadata::{BundleMetadata, metadata_size};
 use crate::version::Version;
 use crate::{Bundle, BundleDescriptor, Header, IndexWriter, Writer};
 use http::HeaderMap;
@@ -79,6 +80,7 @@ impl BundleBuilderOptions {
 pub struct BundleBuilder {
   entries: HashMap<String, BundleEntry>,
   version: Version,
+  metadata: BundleMetadata,
   options: BundleBuilderOptions,
 }
 
@@ -110,6 +112,19 @@ impl BundleBuilder {
     self
   }
 
+  pub fn metadata(&self) -> &BundleMetadata {
+    &self.metadata
+  }
+
+  pub fn metadata_mut(&mut self) -> &mut BundleMetadata {
+    &mut self.metadata
+  }
+
+  pub fn set_metadata(&mut self, metadata: BundleMetadata) -> &mut Self {
+    self.metadata = metadata;
+    self
+  }
+
   pub fn options(&self) -> &BundleBuilderOptions {
     &self.options
   }
@@ -152,7 +167,12 @@ impl BundleBuilder {
   pub fn build(&self) -> crate::Result<Bundle> {
     let index = self.build_index();
     let header = self.build_header(&index)?;
-    let manifest = BundleDescriptor { header, index };
+    let manifest = BundleDescriptor {
+      header,
+      index,
+      metadata: self.metadata.clone(),
+      verify_data: None,
+    };
     let data = self.build_data();
     Ok(Bundle {
       descriptor: manifest,
@@ -164,7 +184,8 @@ impl BundleBuilder {
     let index_bytes_size =
       IndexWriter::new_with_options(&mut vec![], self.options().index).write(index)?;
     let index_size = (index_bytes_size - CHECKSUM_LEN) as u32;
-    let header = Header::new(self.version(), index_size);
+    let metadata_size = metadata_size(self.version(), &self.metadata)?;
+    let header = Header::new(self.version(), index_size).with_metadata_size(metadata_size);
     Ok(header)
   }
 
@@ -1,26 +1,34 @@
 use crate::builder::BundleBuilder;
-use crate::checksum::{CHECKSUM_LEN, parse_checksum};
+use crate::checksum::{CHECKSUM_LEN, make_checksum, parse_checksum};
 use crate::header::{Header, HeaderReader, HeaderWriter};
-use crate::index::{Index, IndexEntry, IndexReader, IndexWriter};
-use crate::reader::Reader;
+use crate::index::{Index, IndexEntry, IndexReader, IndexReaderOptions, IndexWriter};
+use crate::metadata::{
+  BundleMetadata, MetadataReader, MetadataReaderOptions, MetadataWriter, metadata_size,
+};
+use crate::reader::{ReadLimits, Reader, read_len};
+use crate::verify::VerifyOptions;
+use crate::version::Version;
 use crate::writer::Writer;
-use lz4_flex::decompress_size_prepended;
+use lz4_flex::block::{decompress, uncompressed_size};
 use std::io::{Cursor, Read, Seek, SeekFrom, Write};
 
+#[cfg(feature = "async")]
+use crate::reader::async_read_len;
 #[cfg(feature = "async")]
 use crate::{
-  AsyncHeaderReader, AsyncHeaderWriter, AsyncIndexReader, AsyncIndexWriter, AsyncReader,
-  AsyncWriter,
+  AsyncHeaderReader, AsyncHeaderWriter, AsyncIndexReader, AsyncIndexWriter, AsyncMetadataReader,
+  AsyncMetadataWriter, AsyncReader, AsyncWriter,
 };
 #[cfg(feature = "async")]
 use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
 
-/// Bundle metadata including header and index information.
+/// Bundle metadata including header, index and metadata section information.
 ///
-/// A `BundleDescriptor` contains the header and index of a bundle without loading
+/// A `BundleDescriptor` contains the header, index and metadata of a bundle without loading
 /// the full file data. This is useful for:
 ///
 /// - Reading bundle metadata without loading all files
+/// - Identifying a bundle file by its name and version
 /// - Lazy-loading files on demand from a reader
 /// - Inspecting bundle contents efficiently
 ///
@@ -44,6 +52,8 @@ use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, As
 pub struct BundleDescriptor {
   pub(crate) header: Header,
   pub(crate) index: Index,
+  pub(crate) metadata: BundleMetadata,
+  pub(crate) verify_data: Option<VerifyOptions>,
 }
 
 impl BundleDescriptor {
@@ -57,6 +67,23 @@ impl BundleDescriptor {
     &self.index
   }
 
+  /// Returns a reference to the bundle metadata.
+  ///
+  /// Bundles written without metadata return empty metadata.
+  pub fn metadata(&self) -> &BundleMetadata {
+    &self.metadata
+  }
+
+  /// Sets whether file data is checked against its checksum when read.
+  ///
+  /// When set, reading file data whose checksum doesn't match fails with
+  /// [`crate::Error::ChecksumMismatch`]. Use [`BundleDescriptor::verify`] to check every
+  /// entry at once.
+  pub fn set_verify_data(&mut self, options: Option<VerifyOptions>) -> &mut Self {
+    self.verify_data = options;
+    self
+  }
+
   /// Reads file data from the bundle using the provided reader.
   ///
   /// Returns `None` if the path doesn't exist in the bundle.
@@ -70,7 +97,8 @@ impl BundleDescriptor {
       return Ok(None);
     }
     let entry = self.index.get_entry(path).unwrap();
-    let mut reader = BundleDataReader::new(reader, self.header.index_end_offset());
+    let mut reader =
+      BundleDataReader::new(reader, self.header.index_end_offset()).verify(self.verify_data);
     let data = reader.read_entry_data(entry)?;
     Ok(Some(data))
   }
@@ -105,7 +133,8 @@ impl BundleDescriptor {
       return Ok(None);
     }
     let entry = self.index.get_entry(path).unwrap();
-    let mut reader = AsyncBundleDataReader::new(reader, self.header.index_end_offset());
+    let mut reader =
+      AsyncBundleDataReader::new(reader, self.header.index_end_offset()).verify(self.verify_data);
     let data = reader.read_entry_data(entry).await?;
     Ok(Some(data))
   }
@@ -184,11 +213,23 @@ impl Bundle {
     BundleBuilder::new_with_capacity(capacity)
   }
 
-  /// Returns a reference to the bundle descriptor (header and index).
+  /// Returns a reference to the bundle descriptor (header, index and metadata).
   pub fn descriptor(&self) -> &BundleDescriptor {
     &self.descriptor
   }
 
+  /// Returns a reference to the bundle metadata.
+  pub fn metadata(&self) -> &BundleMetadata {
+    &self.descriptor.metadata
+  }
+
+  /// Sets whether file data is checked against its checksum when read, see
+  /// [`BundleDescriptor::set_verify_data`].
+  pub fn set_verify_data(&mut self, options: Option<VerifyOptions>) -> &mut Self {
+    self.descriptor.set_verify_data(options);
+    self
+  }
+
   /// Retrieves file data by path.
   ///
   /// Returns `None` if the path doesn't exist in the bundle.
@@ -209,7 +250,8 @@ impl Bundle {
       return Ok(None);
     }
     let entry = self.descriptor.index.get_entry(path).unwrap();
-    let mut reader = BundleDataReader::new(Cursor::new(&self.data), 0);
+    let mut reader =
+      BundleDataReader::new(Cursor::new(&self.data), 0).verify(self.descriptor.verify_data);
     let data = reader.read_entry_data(entry)?;
     Ok(Some(data))
   }
@@ -228,39 +270,94 @@ impl Bundle {
   }
 }
 
-fn read_entry(entry: &IndexEntry) -> (u64, Vec<u8>) {
-  (entry.offset(), vec![0u8; entry.len() as usize])
+/// LZ4 output is never more than 255 times larger than its input, since every 255 bytes of a
+/// match take at least one byte.
+const MAX_COMPRESSION_RATIO: u64 = 255;
+
+fn read_entry(base_offset: u64, entry: &IndexEntry) -> crate::Result<(u64, u64)> {
+  let offset = base_offset
+    .checked_add(entry.offset())
+    .ok_or_else(|| crate::Error::invalid_entry("offset is out of range"))?;
+  Ok((offset, entry.len()))
 }
 
-fn parse_entry(buf: &[u8]) -> crate::Result<Vec<u8>> {
-  let decompressed = decompress_size_prepended(buf)?;
+fn parse_entry(buf: &[u8], entry: &IndexEntry) -> crate::Result<Vec<u8>> {
+  let (size, block) = uncompressed_size(buf)?;
+  // the prepended size is allocated before decompressing, so it's only trusted when it
+  // matches the index and can be decompressed from the block
+  if size as u64 != entry.content_length()
+    || size as u64 > (block.len() as u64).saturating_mul(MAX_COMPRESSION_RATIO)
+  {
+    return Err(crate::Error::invalid_entry(
+      "decompressed size does not match the index",
+    ));
+  }
+  let decompressed = decompress(block, size)?;
   Ok(decompressed)
 }
 
-fn read_entry_checksum(entry: &IndexEntry) -> (u64, [u8; CHECKSUM_LEN]) {
-  (entry.offset() + entry.len(), [0u8; CHECKSUM_LEN])
+fn read_entry_checksum(
+  base_offset: u64,
+  entry: &IndexEntry,
+) -> crate::Result<(u64, [u8; CHECKSUM_LEN])> {
+  let offset = base_offset
+    .checked_add(entry.offset())
+    .and_then(|x| x.checked_add(entry.len()))
+    .ok_or_else(|| crate::Error::invalid_entry("offset is out of range"))?;
+  Ok((offset, [0u8; CHECKSUM_LEN]))
+}
+
+fn verify_entry(buf: &[u8], checksum: u32, options: &VerifyOptions) -> crate::Result<()> {
+  if make_checksum(options.data_checksum_seed, buf) != checksum {
+    return Err(crate::Error::ChecksumMismatch);
+  }
+  Ok(())
 }
 
 pub(crate) struct BundleDataReader<R: Read + Seek> {
   r: R,
   base_offset: u64,
+  verify: Option<VerifyOptions>,
 }
 
 impl<R: Read + Seek> BundleDataReader<R> {
   pub fn new(r: R, base_offset: u64) -> Self {
-    Self { r, base_offset }
+    Self {
+      r,
+      base_offset,
+      verify: None,
+    }
+  }
+
+  /// Checks entry data against its checksum before decompressing it.
+  pub fn verify(mut self, options: Option<VerifyOptions>) -> Self {
+    self.verify = options;
+    self
   }
 
   pub fn read_entry_data(&mut self, entry: &IndexEntry) -> crate::Result<Vec<u8>> {
-    let (offset, mut buf) = read_entry(entry);
-    self.r.seek(SeekFrom::Start(self.base_offset + offset))?;
-    self.r.read_exact(&mut buf)?;
-    parse_entry(&buf)
+    let buf = self.read_entry_raw(entry)?;
+    if let Some(options) = self.verify {
+      verify_entry(&buf, self.read_entry_checksum(entry)?, &options)?;
+    }
+    parse_entry(&buf, entry)
+  }
+
+  /// Checks the compressed data of an entry against its checksum.
+  pub fn verify_entry(&mut self, entry: &IndexEntry, options: &VerifyOptions) -> crate::Result<()> {
+    let buf = self.read_entry_raw(entry)?;
+    verify_entry(&buf, self.read_entry_checksum(entry)?, options)
+  }
+
+  fn read_entry_raw(&mut self, entry: &IndexEntry) -> crate::Result<Vec<u8>> {
+    let (offset, len) = read_entry(self.base_offset, entry)?;
+    self.r.seek(SeekFrom::Start(offset))?;
+    read_len(&mut self.r, len)
   }
 
   pub fn read_entry_checksum(&mut self, entry: &IndexEntry) -> crate::Result<u32> {
-    let (offset, mut buf) = read_entry_checksum(entry);
-    self.r.seek(SeekFrom::Start(self.base_offset + offset))?;
+    let (offset, mut buf) = read_entry_checksum(self.base_offset, entry)?;
+    self.r.seek(SeekFrom::Start(offset))?;
     self.r.read_exact(&mut buf)?;
     Ok(parse_checksum(&buf))
   }
@@ -270,42 +367,91 @@ impl<R: Read + Seek> BundleDataReader<R> {
 pub(crate) struct AsyncBundleDataReader<R: AsyncRead + AsyncSeek + Unpin> {
   r: R,
   base_offset: u64,
+  verify: Option<VerifyOptions>,
 }
 
 #[cfg(feature = "async")]
 impl<R: AsyncRead + AsyncSeek + Unpin> AsyncBundleDataReader<R> {
   pub fn new(r: R, base_offset: u64) -> Self {
-    Self { r, base_offset }
+    Self {
+      r,
+      base_offset,
+      verify: None,
+    }
   }
 
-  pub async fn read_entry_data(&mut self, entry: &IndexEntry) -> crate::Result<Vec<u8>> {
-    let (offset, mut buf) = read_entry(entry);
+  /// Checks entry data against its checksum before decompressing it.
+  pub fn verify(mut self, options: Option<VerifyOptions>) -> Self {
+    self.verify = options;
     self
-      .r
-      .seek(SeekFrom::Start(self.base_offset + offset))
-      .await?;
-    self.r.read_exact(&mut buf).await?;
-    parse_entry(&buf)
+  }
+
+  pub async fn read_entry_data(&mut self, entry: &IndexEntry) -> crate::Result<Vec<u8>> {
+    let buf = self.read_entry_raw(entry).await?;
+    if let Some(options) = self.verify {
+      verify_entry(&buf, self.read_entry_checksum(entry).await?, &options)?;
+    }
+    parse_entry(&buf, entry)
+  }
+
+  /// Checks the compressed data of an entry against its checksum.
+  pub async fn verify_entry(
+    &mut self,
+    entry: &IndexEntry,
+    options: &VerifyOptions,
+  ) -> crate::Result<()> {
+    let buf = self.read_entry_raw(entry).await?;
+    verify_entry(&buf, self.read_entry_checksum(entry).await?, options)
+  }
+
+  async fn read_entry_raw(&mut self, entry: &IndexEntry) -> crate::Result<Vec<u8>> {
+    let (offset, len) = read_entry(self.base_offset, entry)?;
+    self.r.seek(SeekFrom::Start(offset)).await?;
+    async_read_len(&mut self.r, len).await
   }
 
   pub async fn read_entry_checksum(&mut self, entry: &IndexEntry) -> crate::Result<u32> {
-    let (offset, mut buf) = read_entry_checksum(entry);
-    self
-      .r
-      .seek(SeekFrom::Start(self.base_offset + offset))
-      .await?;
+    let (offset, mut buf) = read_entry_checksum(self.base_offset, entry)?;
+    self.r.seek(SeekFrom::Start(offset)).await?;
     self.r.read_exact(&mut buf).await?;
     Ok(parse_checksum(&buf))
   }
 }
 
+fn index_options(limits: Option<ReadLimits>) -> IndexReaderOptions {
+  match limits {
+    Some(limits) => IndexReaderOptions::new().max_size(limits.max_index_size),
+    None => IndexReaderOptions::new(),
+  }
+}
+
+fn metadata_options(limits: Option<ReadLimits>) -> MetadataReaderOptions {
+  match limits {
+    Some(limits) => MetadataReaderOptions::new().max_size(limits.max_metadata_size),
+    None => MetadataReaderOptions::new(),
+  }
+}
+
+/// Reads a bundle.
+///
+/// Bundles from untrusted sources should be read with [`BundleReader::new_with_limits`],
+/// which validates the index before any entry is read.
 pub struct BundleReader<R: Read + Seek> {
   r: R,
+  limits: Option<ReadLimits>,
 }
 
 impl<R: Read + Seek> BundleReader<R> {
   pub fn new(r: R) -> Self {
-    Self { r }
+    Self { r, limits: None }
+  }
+
+  /// Creates a reader rejecting bundles which exceed the limits or have invalid entries.
+  pub fn new_with_limits(r: R, limits: ReadLimits) -> Self {
+    Self {
+      r,
+      limits: Some(limits),
+    }
   }
 
   pub fn read_header(&mut self) -> crate::Result<Header> {
@@ -315,24 +461,49 @@ impl<R: Read + Seek> BundleReader<R> {
   }
 
   pub fn read_index(&mut self, header: Header) -> crate::Result<Index> {
-    let mut reader = IndexReader::new(&mut self.r, header);
+    let mut reader = IndexReader::new_with_options(&mut self.r, header, index_options(self.limits));
     let index = reader.read()?;
+    if let Some(limits) = self.limits {
+      let (start, end) = self.data_range(header)?;
+      limits.validate_index(&index, end.saturating_sub(start))?;
+    }
     Ok(index)
   }
 
+  pub fn read_metadata(&mut self, header: Header) -> crate::Result<BundleMetadata> {
+    let mut reader =
+      MetadataReader::new_with_options(&mut self.r, header, metadata_options(self.limits));
+    let metadata = reader.read()?;
+    Ok(metadata)
+  }
+
   pub fn read_data(&mut self, header: Header) -> crate::Result<Vec<u8>> {
-    self.r.seek(SeekFrom::Start(header.index_end_offset()))?;
-    let mut data = vec![];
-    self.r.read_to_end(&mut data)?;
+    let (start, end) = self.data_range(header)?;
+    self.r.seek(SeekFrom::Start(start))?;
+    let mut data = vec![0u8; end.saturating_sub(start) as usize];
+    self.r.read_exact(&mut data)?;
     Ok(data)
   }
+
+  /// Returns the start and end offsets of the data section.
+  fn data_range(&mut self, header: Header) -> crate::Result<(u64, u64)> {
+    let metadata_len = MetadataReader::new(&mut self.r, header).read_trailer_len()?;
+    let end = self.r.seek(SeekFrom::End(0))? - metadata_len;
+    Ok((header.index_end_offset(), end))
+  }
 }
 
 impl<R: Read + Seek> Reader<BundleDescriptor> for BundleReader<R> {
   fn read(&mut self) -> crate::Result<BundleDescriptor> {
     let header = self.read_header()?;
     let index = self.read_index(header)?;
-    Ok(BundleDescriptor { header, index })
+    let metadata = self.read_metadata(header)?;
+    Ok(BundleDescriptor {
+      header,
+      index,
+      metadata,
+      verify_data: None,
+    })
   }
 }
 
@@ -340,9 +511,15 @@ impl<R: Read + Seek> Reader<Bundle> for BundleReader<R> {
   fn read(&mut self) -> crate::Result<Bundle> {
     let header = self.read_header()?;
     let index = self.read_index(header)?;
+    let metadata = self.read_metadata(header)?;
     let data = self.read_data(header)?;
     Ok(Bundle {
-      descriptor: BundleDescriptor { header, index },
+      descriptor: BundleDescriptor {
+        header,
+        index,
+        metadata,
+        verify_data: None,
+      },
       data,
     })
   }
@@ -351,12 +528,21 @@ impl<R: Read + Seek> Reader<Bundle> for BundleReader<R> {
 #[cfg(feature = "async")]
 pub struct AsyncBundleReader<R: AsyncRead + AsyncSeek + Unpin> {
   r: R,
+  limits: Option<ReadLimits>,
 }
 
 #[cfg(feature = "async")]
 impl<R: AsyncRead + AsyncSeek + Unpin> AsyncBundleReader<R> {
   pub fn new(r: R) -> Self {
-    Self { r }
+    Self { r, limits: None }
+  }
+
+  /// Creates a reader rejecting bundles which exceed the limits or have invalid entries.
+  pub fn new_with_limits(r: R, limits: ReadLimits) -> Self {
+    Self {
+      r,
+      limits: Some(limits),
+    }
   }
 
   pub async fn read_header(&mut self) -> crate::Result<Header> {
@@ -366,20 +552,39 @@ impl<R: AsyncRead + AsyncSeek + Unpin> AsyncBundleReader<R> {
   }
 
   pub async fn read_index(&mut self, header: Header) -> crate::Result<Index> {
-    let mut reader = AsyncIndexReader::new(&mut self.r, header);
+    let mut reader =
+      AsyncIndexReader::new_with_options(&mut self.r, header, index_options(self.limits));
     let index = reader.read().await?;
+    if let Some(limits) = self.limits {
+      let (start, end) = self.data_range(header).await?;
+      limits.validate_index(&index, end.saturating_sub(start))?;
+    }
     Ok(index)
   }
 
+  pub async fn read_metadata(&mut self, header: Header) -> crate::Result<BundleMetadata> {
+    let mut reader =
+      AsyncMetadataReader::new_with_options(&mut self.r, header, metadata_options(self.limits));
+    let metadata = reader.read().await?;
+    Ok(metadata)
+  }
+
   pub async fn read_data(&mut self, header: Header) -> crate::Result<Vec<u8>> {
-    self
-      .r
-      .seek(SeekFrom::Start(header.index_end_offset()))
-      .await?;
-    let mut data = vec![];
-    self.r.read_to_end(&mut data).await?;
+    let (start, end) = self.data_range(header).await?;
+    self.r.seek(SeekFrom::Start(start)).await?;
+    let mut data = vec![0u8; end.saturating_sub(start) as usize];
+    self.r.read_exact(&mut data).await?;
     Ok(data)
   }
+
+  /// Returns the start and end offsets of the data section.
+  async fn data_range(&mut self, header: Header) -> crate::Result<(u64, u64)> {
+    let metadata_len = AsyncMetadataReader::new(&mut self.r, header)
+      .read_trailer_len()
+      .await?;
+    let end = self.r.seek(SeekFrom::End(0)).await? - metadata_len;
+    Ok((header.index_end_offset(), end))
+  }
 }
 
 #[cfg(feature = "async")]
@@ -387,7 +592,13 @@ impl<R: AsyncRead + AsyncSeek + Unpin> AsyncReader<BundleDescriptor> for AsyncBu
   async fn read(&mut self) -> crate::Result<BundleDescriptor> {
     let header = self.read_header().await?;
     let index = self.read_index(header).await?;
-    Ok(BundleDescriptor { header, index })
+    let metadata = self.read_metadata(header).await?;
+    Ok(BundleDescriptor {
+      header,
+      index,
+      metadata,
+      verify_data: None,
+    })
   }
 }
 
@@ -396,60 +607,127 @@ impl<R: AsyncRead + AsyncSeek + Unpin> AsyncReader<Bundle> for AsyncBundleReader
   async fn read(&mut self) -> crate::Result<Bundle> {
     let header = self.read_header().await?;
     let index = self.read_index(header).await?;
+    let metadata = self.read_metadata(header).await?;
     let data = self.read_data(header).await?;
     Ok(Bundle {
-      descriptor: BundleDescriptor { header, index },
+      descriptor: BundleDescriptor {
+        header,
+        index,
+        metadata,
+        verify_data: None,
+      },
       data,
     })
   }
 }
 
+/// Returns the header of a bundle written in the given format version.
+fn header_for_version(descriptor: &BundleDescriptor, version: Version) -> crate::Result<Header> {
+  let metadata_size = metadata_size(version, &descriptor.metadata)?;
+  let header =
+    Header::new(version, descriptor.header.index_size()).with_metadata_size(metadata_size);
+  Ok(header)
+}
+
+/// Writes a bundle.
+///
+/// Bundles are written in the format version of their header, unless a target version is set
+/// with [`BundleWriter::new_with_version`] (e.g. to ship a bundle to clients which only read
+/// an older format).
 pub struct BundleWriter<W: Write> {
   w: W,
+  version: Option<Version>,
 }
 
 impl<W: Write> BundleWriter<W> {
   pub fn new(w: W) -> Self {
-    Self { w }
+    Self { w, version: None }
+  }
+
+  pub fn new_with_version(w: W, version: Version) -> Self {
+    Self {
+      w,
+      version: Some(version),
+    }
   }
 }
 
 impl<W: Write> Writer<Bundle> for BundleWriter<W> {
   fn write(&mut self, data: &Bundle) -> crate::Result<usize> {
-    let header_len = HeaderWriter::new(&mut self.w).write(&data.descriptor.header)?;
-    let index_len = IndexWriter::new(&mut self.w).write(&data.descriptor.index)?;
-    let data_len = data.data.len();
-    self.w.write_all(&data.data)?;
+    let version = self.version.unwrap_or(data.descriptor.header.version());
+    let header = header_for_version(&data.descriptor, version)?;
+    let metadata = &data.descriptor.metadata;
+    let mut len = HeaderWriter::new(&mut self.w).write(&header)?;
+    match version {
+      Version::V1 => {
+        len += IndexWriter::new(&mut self.w).write(&data.descriptor.index)?;
+        self.w.write_all(&data.data)?;
+        len += data.data.len();
+        len += MetadataWriter::new(&mut self.w, version).write(metadata)?;
+      }
+      Version::V2 => {
+        len += MetadataWriter::new(&mut self.w, version).write(metadata)?;
+        len += IndexWriter::new(&mut self.w).write(&data.descriptor.index)?;
+        self.w.write_all(&data.data)?;
+        len += data.data.len();
+      }
+    }
     self.w.flush()?;
-    Ok(header_len + index_len + data_len)
+    Ok(len)
   }
 }
 
 #[cfg(feature = "async")]
 pub struct AsyncBundleWriter<W: AsyncWrite + Unpin> {
   w: W,
+  version: Option<Version>,
 }
 
 #[cfg(feature = "async")]
 impl<W: AsyncWrite + Unpin> AsyncBundleWriter<W> {
   pub fn new(w: W) -> Self {
-    Self { w }
+    Self { w, version: None }
+  }
+
+  pub fn new_with_version(w: W, version: Version) -> Self {
+    Self {
+      w,
+      version: Some(version),
+    }
   }
 }
 
 #[cfg(feature = "async")]
 impl<W: AsyncWrite + Unpin> AsyncWriter<Bundle> for AsyncBundleWriter<W> {
   async fn write(&mut self, data: &Bundle) -> crate::Result<usize> {
-    let header_len = AsyncHeaderWriter::new(&mut self.w)
-      .write(&data.descriptor.header)
-      .await?;
-    let index_len = AsyncIndexWriter::new(&mut self.w)
-      .write(&data.descriptor.index)
-      .await?;
-    let data_len = data.data.len();
-    self.w.write_all(&data.data).await?;
+    let version = self.version.unwrap_or(data.descriptor.header.version());
+    let header = header_for_version(&data.descriptor, version)?;
+    let metadata = &data.descriptor.metadata;
+    let mut len = AsyncHeaderWriter::new(&mut self.w).write(&header).await?;
+    match version {
+      Version::V1 => {
+        len += AsyncIndexWriter::new(&mut self.w)
+          .write(&data.descriptor.index)
+          .await?;
+        self.w.write_all(&data.data).await?;
+        len += data.data.len();
+        len += AsyncMetadataWriter::new(&mut self.w, version)
+          .write(metadata)
+          .await?;
+      }
+      Version::V2 => {
+        len += AsyncMetadataWriter::new(&mut self.w, version)
+          .write(metadata)
+          .await?;
+        len += AsyncIndexWriter::new(&mut self.w)
+          .write(&data.descriptor.index)
+          .await?;
+        self.w.write_all(&data.data).await?;
+        len += data.data.len();
+      }
+    }
     self.w.flush().await?;
-    Ok(header_len + index_len + data_len)
+    Ok(len)
   }
 }
 
@@ -554,4 +832,224 @@ mod tests {
       .unwrap();
     assert_eq!(html.unwrap(), INDEX_HTML.as_bytes());
   }
+
+  fn two_entries() -> Bundle {
+    let mut builder = Bundle::builder();
+    builder.insert_entry(
+      "/index.html",
+      BundleEntry::new(INDEX_HTML.as_bytes(), "text/html", None),
+    );
+    builder.insert_entry(
+      "/index.js",
+      BundleEntry::new(INDEX_JS.as_bytes(), "text/javascript", None),
+    );
+    builder.build().unwrap()
+  }
+
+  /// Writes a bundle with its index replaced.
+  fn write_with_index(bundle: &Bundle, index: Index) -> Vec<u8> {
+    let index_size = IndexWriter::new(vec![]).write(&index).unwrap() - CHECKSUM_LEN;
+    let bundle = Bundle {
+      descriptor: BundleDescriptor {
+        header: Header::new(Version::V1, index_size as u32),
+        index,
+        metadata: bundle.descriptor.metadata.clone(),
+        verify_data: None,
+      },
+      data: bundle.data.clone(),
+    };
+    let mut data = vec![];
+    BundleWriter::new(Cursor::new(&mut data))
+      .write(&bundle)
+      .unwrap();
+    data
+  }
+
+  #[test]
+  fn read_limits() {
+    let bundle = two_entries();
+    let mut data = vec![];
+    BundleWriter::new(Cursor::new(&mut data))
+      .write(&bundle)
+      .unwrap();
+    let read: Bundle = BundleReader::new_with_limits(Cursor::new(&data), ReadLimits::new())
+      .read()
+      .unwrap();
+    assert_eq!(read, bundle);
+
+    let limits = ReadLimits::new().max_index_size(10);
+    let err = Reader::<BundleDescriptor>::read(&mut BundleReader::new_with_limits(
+      Cursor::new(&data),
+      limits,
+    ))
+    .unwrap_err();
+    assert!(matches!(err, crate::Error::ReadLimitExceeded { .. }));
+
+    let limits = ReadLimits::new().max_decompressed_size(100);
+    let err = Reader::<BundleDescriptor>::read(&mut BundleReader::new_with_limits(
+      Cursor::new(&data),
+      limits,
+    ))
+    .unwrap_err();
+    assert!(matches!(err, crate::Error::ReadLimitExceeded { .. }));
+  }
+
+  #[test]
+  fn invalid_entries() {
+    let bundle = two_entries();
+    let html = bundle.descriptor.index.get_entry("/index.html").unwrap();
+    let js = bundle.descriptor.index.get_entry("/index.js").unwrap();
+    let limits = ReadLimits::new().max_entry_size(u64::MAX);
+    let read = |data: &[u8]| {
+      Reader::<BundleDescriptor>::read(&mut BundleReader::new_with_limits(
+        Cursor::new(data),
+        limits,
+      ))
+    };
+
+    // past the end of the data section
+    let mut index = bundle.descriptor.index.clone();
+    let entry = IndexEntry::new(js.offset(), 1 << 40, js.content_type(), js.content_length());
+    index.insert_entry("/index.js", entry);
+    let data = write_with_index(&bundle, index);
+    assert!(matches!(read(&data), Err(crate::Error::InvalidEntry(_))));
+    // without limits, reading the entry fails instead of allocating it
+    let descriptor: BundleDescriptor = BundleReader::new(Cursor::new(&data)).read().unwrap();
+    assert!(
+      descriptor
+        .get_data(Cursor::new(&data), "/index.js")
+        .is_err()
+    );
+
+    // overlapping another entry
+    let mut index = bundle.descriptor.index.clone();
+    let entry = IndexEntry::new(
+      html.offset() + 1,
+      js.len(),
+      js.content_type(),
+      js.content_length(),
+    );
+    index.insert_entry("/index.js", entry);
+    let data = write_with_index(&bundle, index);
+    assert!(matches!(read(&data), Err(crate::Error::InvalidEntry(_))));
+
+    // decompressed size not matching the index
+    let mut index = bundle.descriptor.index.clone();
+    let entry = IndexEntry::new(js.offset(), js.len(), js.content_type(), 1 << 40);
+    index.insert_entry("/index.js", entry);
+    let data = write_with_index(&bundle, index);
+    let descriptor: BundleDescriptor = BundleReader::new(Cursor::new(&data)).read().unwrap();
+    assert!(matches!(
+      descriptor.get_data(Cursor::new(&data), "/index.js"),
+      Err(crate::Error::InvalidEntry(_))
+    ));
+  }
+
+  #[test]
+  fn truncated() {
+    let mut data = vec![];
+    BundleWriter::new(Cursor::new(&mut data))
+      .write(&two_entries())
+      .unwrap();
+    for len in 0..data.len() {
+      let data = &data[..len];
+      if let Ok(descriptor) =
+        Reader::<BundleDescriptor>::read(&mut BundleReader::new(Cursor::new(data)))
+      {
+        let _ = descriptor.get_data(Cursor::new(data), "/index.js");
+      }
+    }
+  }
+
+  #[test]
+  fn metadata() {
+    let mut builder = Bundle::builder();
+    builder.insert_entry(
+      "/index.html",
+      BundleEntry::new(INDEX_HTML.as_bytes(), "text/html", None),
+    );
+    builder.metadata_mut().set_name("app").set_version("1.0.0");
+    let bundle = builder.build().unwrap();
+    let mut data = vec![];
+    let size = BundleWriter::new(Cursor::new(&mut data))
+      .write(&bundle)
+      .unwrap();
+    assert!(size > 150);
+    assert_eq!(size, data.len());
+
+    let mut reader = BundleReader::new(Cursor::new(&data));
+    let descriptor: BundleDescriptor = reader.read().unwrap();
+    assert_eq!(descriptor.metadata().name(), Some("app"));
+    assert_eq!(descriptor.metadata().version(), Some("1.0.0"));
+
+    let mut reader = BundleReader::new(Cursor::new(&data));
+    let read: Bundle = reader.read().unwrap();
+    assert_eq!(read, bundle);
+    let html = read.get_data("/index.html").unwrap().unwrap();
+    assert_eq!(html, INDEX_HTML.as_bytes());
+  }
+
+  #[test]
+  fn no_metadata() {
+    let mut builder = Bundle::builder();
+    builder.insert_entry(
+      "/index.html",
+      BundleEntry::new(INDEX_HTML.as_bytes(), "text/html", None),
+    );
+    let bundle = builder.build().unwrap();
+    let mut data = vec![];
+    BundleWriter::new(Cursor::new(&mut data))
+      .write(&bundle)
+      .unwrap();
+    let mut reader = BundleReader::new(Cursor::new(&data));
+    let descriptor: BundleDescriptor = reader.read().unwrap();
+    assert!(descriptor.metadata().is_empty());
+  }
+
+  #[test]
+  fn write_v2() {
+    let mut builder = Bundle::builder();
+    builder.set_version(Version::V2);
+    builder.insert_entry(
+      "/index.html",
+      BundleEntry::new(INDEX_HTML.as_bytes(), "text/html", None),
+    );
+    builder.metadata_mut().set_name("app");
+    let bundle = builder.build().unwrap();
+    let mut data = vec![];
+    let size = BundleWriter::new(Cursor::new(&mut data))
+      .write(&bundle)
+      .unwrap();
+    assert_eq!(size, data.len());
+    assert_eq!(data[Header::VERSION_OFFSET as usize], 0x02);
+
+    let descriptor: BundleDescriptor = BundleReader::new(Cursor::new(&data)).read().unwrap();
+    assert_eq!(descriptor.header().version(), Version::V2);
+    assert_eq!(descriptor.metadata().name(), Some("app"));
+    let html = descriptor
+      .get_data(Cursor::new(&data), "/index.html")
+      .unwrap()
+      .unwrap();
+    assert_eq!(html, INDEX_HTML.as_bytes());
+    let read: Bundle = BundleReader::new(Cursor::new(&data)).read().unwrap();
+    assert_eq!(read, bundle);
+  }
+
+  #[test]
+  fn write_older_version() {
+    let mut builder = Bundle::builder();
+    builder.set_version(Version::V2);
+    builder.insert_entry(
+      "/index.html",
+      BundleEntry::new(INDEX_HTML.as_bytes(), "text/html", None),
+    );
+    let bundle = builder.build().unwrap();
+    let mut data = vec![];
+    let size = BundleWriter::new_with_version(Cursor::new(&mut data), Version::V1)
+      .write(&bundle)
+      .unwrap();
+    assert_eq!(size, 150);
+    let descriptor: BundleDescriptor = BundleReader::new(Cursor::new(&data)).read().unwrap();
+    assert_eq!(descriptor.header().version(), Version::V1);
+  }
 }
@@ -6,6 +6,12 @@ pub(crate) fn make_checksum(seed: u32, data: &[u8]) -> u32 {
   XxHash32::oneshot(seed, data)
 }
 
+/// Hasher making the same checksum as [`make_checksum`] from data written in parts.
+#[cfg(feature = "protocol")]
+pub(crate) fn checksum_hasher(seed: u32) -> XxHash32 {
+  XxHash32::with_seed(seed)
+}
+
 pub(crate) fn write_checksum(checksum: u32) -> Vec<u8> {
   checksum.to_be_bytes().to_vec()
 }
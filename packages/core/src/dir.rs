@@ -0,0 +1,397 @@
+use crate::{BundleBuilder, BundleEntry, glob, mime};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// How symbolic links are handled while walking a directory.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum SymlinkPolicy {
+  /// Symbolic links are not added.
+  #[default]
+  Skip,
+  /// Symbolic links are added as their targets. Links pointing back to a directory being
+  /// walked are skipped, so cycles don't recurse forever.
+  Follow,
+}
+
+/// Options for adding a directory to a bundle, see [`BundleBuilder::add_dir`].
+///
+/// Globs are matched against paths relative to the directory, without a leading `/` (e.g.
+/// `assets/app.js`), and support `*`, `**`, `?` and `{a,b}`.
+///
+/// # Example
+///
+/// ```
+/// use wvb::{DirOptions, SymlinkPolicy};
+///
+/// let options = DirOptions::new()
+///   .exclude("**/*.map")
+///   .ignore_rule("node_modules/")
+///   .ignore_file(".gitignore")
+///   .symlinks(SymlinkPolicy::Follow);
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct DirOptions {
+  /// Globs of files to add. Every file is added when empty.
+  pub include: Vec<String>,
+  /// Globs of files not to add, even if they are included.
+  pub exclude: Vec<String>,
+  /// `.gitignore`-style rules, relative to the directory.
+  pub ignore_rules: Vec<String>,
+  /// Names of files holding `.gitignore`-style rules, read in every walked directory.
+  ///
+  /// The files themselves are not added, unless `keep_ignore_files` is set.
+  pub ignore_files: Vec<String>,
+  pub keep_ignore_files: bool,
+  pub symlinks: SymlinkPolicy,
+}
+
+impl DirOptions {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn include(mut self, pattern: impl Into<String>) -> Self {
+    self.include.push(pattern.into());
+    self
+  }
+
+  pub fn exclude(mut self, pattern: impl Into<String>) -> Self {
+    self.exclude.push(pattern.into());
+    self
+  }
+
+  pub fn ignore_rule(mut self, rule: impl Into<String>) -> Self {
+    self.ignore_rules.push(rule.into());
+    self
+  }
+
+  pub fn ignore_file(mut self, name: impl Into<String>) -> Self {
+    self.ignore_files.push(name.into());
+    self
+  }
+
+  /// Add the files named in `ignore_files` as entries, which are skipped by default.
+  pub fn keep_ignore_files(mut self, keep: bool) -> Self {
+    self.keep_ignore_files = keep;
+    self
+  }
+
+  pub fn symlinks(mut self, policy: SymlinkPolicy) -> Self {
+    self.symlinks = policy;
+    self
+  }
+
+  fn is_included(&self, path: &str) -> bool {
+    (self.include.is_empty() || self.include.iter().any(|x| glob::matches(x, path)))
+      && !self.exclude.iter().any(|x| glob::matches(x, path))
+  }
+}
+
+/// A `.gitignore`-style rule.
+#[derive(Debug, Clone)]
+struct IgnoreRule {
+  /// Directory the rule is relative to, empty for the walked directory itself.
+  base: String,
+  pattern: String,
+  negated: bool,
+  dir_only: bool,
+}
+
+impl IgnoreRule {
+  fn parse(line: &str, base: &str) -> Option<Self> {
+    let line = line.trim_end();
+    if line.is_empty() || line.starts_with('#') {
+      return None;
+    }
+    let (negated, line) = match line.strip_prefix('!') {
+      Some(x) => (true, x),
+      None => (false, line.strip_prefix('\\').unwrap_or(line)),
+    };
+    let (dir_only, line) = match line.strip_suffix('/') {
+      Some(x) => (true, x),
+      None => (false, line),
+    };
+    if line.is_empty() {
+      return None;
+    }
+    // patterns with a slash are anchored to the base, others match at any depth
+    let pattern = match line.strip_prefix('/') {
+      Some(x) => x.to_string(),
+      None if line.contains('/') => line.to_string(),
+      None => format!("**/{line}"),
+    };
+    Some(Self {
+      base: base.to_string(),
+      pattern,
+      negated,
+      dir_only,
+    })
+  }
+
+  fn matches(&self, path: &str, is_dir: bool) -> bool {
+    if self.dir_only && !is_dir {
+      return false;
+    }
+    let path = if self.base.is_empty() {
+      path
+    } else {
+      match path
+        .strip_prefix(self.base.as_str())
+        .and_then(|x| x.strip_prefix('/'))
+      {
+        Some(x) => x,
+        None => return false,
+      }
+    };
+    glob::matches(&self.pattern, path)
+  }
+}
+
+/// Returns whether a path is ignored, the last matching rule wins.
+fn is_ignored(rules: &[IgnoreRule], path: &str, is_dir: bool) -> bool {
+  rules
+    .iter()
+    .rev()
+    .find(|x| x.matches(path, is_dir))
+    .is_some_and(|x| !x.negated)
+}
+
+struct DirWalker<'a> {
+  builder: &'a mut BundleBuilder,
+  options: &'a DirOptions,
+  rules: Vec<IgnoreRule>,
+  /// Canonical paths of the directories being walked, to detect symbolic link cycles.
+  ancestors: Vec<PathBuf>,
+  count: usize,
+}
+
+impl DirWalker<'_> {
+  fn walk(&mut self, dir: &Path, rel: &str) -> crate::Result<()> {
+    let rules_len = self.rules.len();
+    for name in &self.options.ignore_files {
+      match fs::read_to_string(dir.join(name)) {
+        Ok(content) => self
+          .rules
+          .extend(content.lines().filter_map(|x| IgnoreRule::parse(x, rel))),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+      }
+    }
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|x| x.file_name());
+    for entry in entries {
+      let file_name = entry.file_name();
+      let name = file_name.to_str().ok_or_else(|| {
+        io::Error::new(
+          io::ErrorKind::InvalidData,
+          format!("file name is not valid UTF-8: {}", entry.path().display()),
+        )
+      })?;
+      let path = if rel.is_empty() {
+        name.to_string()
+      } else {
+        format!("{rel}/{name}")
+      };
+      let mut file_type = entry.file_type()?;
+      if file_type.is_symlink() {
+        match self.options.symlinks {
+          SymlinkPolicy::Skip => continue,
+          SymlinkPolicy::Follow => file_type = fs::metadata(entry.path())?.file_type(),
+        }
+      }
+      if file_type.is_dir() {
+        if is_ignored(&self.rules, &path, true) {
+          continue;
+        }
+        let canonical = entry.path().canonicalize()?;
+        if self.ancestors.contains(&canonical) {
+          continue;
+        }
+        self.ancestors.push(canonical);
+        self.walk(&entry.path(), &path)?;
+        self.ancestors.pop();
+      } else if file_type.is_file() {
+        if is_ignored(&self.rules, &path, false)
+          || !self.options.is_included(&path)
+          || (!self.options.keep_ignore_files
+            && self.options.ignore_files.iter().any(|x| x == name))
+        {
+          continue;
+        }
+        let data = fs::read(entry.path())?;
+        let content_type = mime::content_type(name, &data);
+        self.builder.insert_entry(
+          format!("/{path}"),
+          BundleEntry::new(&data, content_type, None),
+        );
+        self.count += 1;
+      }
+    }
+    self.rules.truncate(rules_len);
+    Ok(())
+  }
+}
+
+impl BundleBuilder {
+  /// Adds every file in a directory, such as a `dist/` folder, as entries at paths relative
+  /// to the directory (e.g. `dist/assets/app.js` is added as `/assets/app.js`).
+  ///
+  /// Ignored directories are not walked, so ignored files inside them can't be re-included
+  /// by negated rules. Content types are detected by sniffing the file data, falling back to
+  /// file extensions, see [`crate::mime::content_type`].
+  ///
+  /// Returns the number of added files.
+  ///
+  /// # Example
+  ///
+  /// ```no_run
+  /// use wvb::{BundleBuilder, DirOptions};
+  ///
+  /// let mut builder = BundleBuilder::new();
+  /// builder
+  ///   .add_dir("dist", DirOptions::new().exclude("**/*.map"))
+  ///   .unwrap();
+  /// let bundle = builder.build().unwrap();
+  /// ```
+  pub fn add_dir(&mut self, path: impl AsRef<Path>, options: DirOptions) -> crate::Result<usize> {
+    let path = path.as_ref();
+    let rules = options
+      .ignore_rules
+      .iter()
+      .filter_map(|x| IgnoreRule::parse(x, ""))
+      .collect();
+    let mut walker = DirWalker {
+      builder: self,
+      options: &options,
+      rules,
+      ancestors: vec![path.canonicalize()?],
+      count: 0,
+    };
+    walker.walk(path, "")?;
+    Ok(walker.count)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::testing::TempDir;
+
+  fn write(dir: &Path, path: &str, data: &[u8]) {
+    let path = dir.join(path);
+    fs::create_dir_all(path.parent().unwrap()).unwrap();
+    fs::write(path, data).unwrap();
+  }
+
+  fn setup() -> TempDir {
+    let temp_dir = TempDir::new();
+    let dir = temp_dir.dir();
+    write(dir, "index.html", b"<h1>Hello</h1>");
+    write(dir, "assets/app.js", b"console.log('hello');");
+    write(dir, "assets/app.js.map", b"{}");
+    write(dir, "assets/logo", b"\x89PNG\r\n\x1a\n");
+    write(dir, "assets/.gitignore", b"# generated\n*.log\n/tmp/\n");
+    write(dir, "assets/debug.log", b"debug");
+    write(dir, "assets/tmp/cache", b"cache");
+    write(dir, "assets/vendor/tmp/lib.js", b"lib");
+    write(dir, "node_modules/dep/index.js", b"dep");
+    temp_dir
+  }
+
+  fn paths(builder: &BundleBuilder) -> Vec<&str> {
+    let mut paths = builder
+      .entries()
+      .keys()
+      .map(|x| x.as_str())
+      .collect::<Vec<_>>();
+    paths.sort();
+    paths
+  }
+
+  #[test]
+  fn add_dir() {
+    let temp_dir = setup();
+    let mut builder = BundleBuilder::new();
+    let count = builder.add_dir(temp_dir.dir(), DirOptions::new()).unwrap();
+    assert_eq!(count, 9);
+    assert_eq!(
+      builder.get_entry("/index.html").unwrap().content_type(),
+      "text/html"
+    );
+    assert_eq!(
+      builder.get_entry("/assets/app.js").unwrap().content_type(),
+      "text/javascript"
+    );
+    assert_eq!(
+      builder.get_entry("/assets/logo").unwrap().content_type(),
+      "image/png"
+    );
+    let bundle = builder.build().unwrap();
+    assert_eq!(
+      bundle.get_data("/assets/app.js").unwrap().unwrap(),
+      b"console.log('hello');"
+    );
+  }
+
+  #[test]
+  fn ignore() {
+    let temp_dir = setup();
+    let mut builder = BundleBuilder::new();
+    let options = DirOptions::new()
+      .ignore_rule("node_modules/")
+      .ignore_rule("*.map")
+      .ignore_rule("!app.js.map")
+      .ignore_file(".gitignore");
+    builder.add_dir(temp_dir.dir(), options.clone()).unwrap();
+    assert_eq!(
+      paths(&builder),
+      vec![
+        "/assets/app.js",
+        "/assets/app.js.map",
+        "/assets/logo",
+        "/assets/vendor/tmp/lib.js",
+        "/index.html",
+      ]
+    );
+
+    let mut builder = BundleBuilder::new();
+    builder
+      .add_dir(temp_dir.dir(), options.keep_ignore_files(true))
+      .unwrap();
+    assert!(builder.contains_path("/assets/.gitignore"));
+  }
+
+  #[test]
+  fn include_exclude() {
+    let temp_dir = setup();
+    let mut builder = BundleBuilder::new();
+    let options = DirOptions::new()
+      .include("**/*.{html,js}")
+      .exclude("node_modules/**")
+      .exclude("assets/vendor/**");
+    let count = builder.add_dir(temp_dir.dir(), options).unwrap();
+    assert_eq!(count, 2);
+    assert_eq!(paths(&builder), vec!["/assets/app.js", "/index.html"]);
+  }
+
+  #[cfg(unix)]
+  #[test]
+  fn symlinks() {
+    let temp_dir = TempDir::new();
+    let dir = temp_dir.dir();
+    write(dir, "assets/app.js", b"console.log('hello');");
+    std::os::unix::fs::symlink(dir.join("assets/app.js"), dir.join("app.js")).unwrap();
+    // points back to the walked directory
+    std::os::unix::fs::symlink(dir, dir.join("assets/root")).unwrap();
+
+    let mut builder = BundleBuilder::new();
+    builder.add_dir(dir, DirOptions::new()).unwrap();
+    assert_eq!(paths(&builder), vec!["/assets/app.js"]);
+
+    let mut builder = BundleBuilder::new();
+    let options = DirOptions::new().symlinks(SymlinkPolicy::Follow);
+    builder.add_dir(dir, options).unwrap();
+    assert_eq!(paths(&builder), vec!["/app.js", "/assets/app.js"]);
+  }
+}
@@ -28,8 +28,18 @@ pub enum Error {
   InvalidHeaderChecksum,
   #[error("invalid index checksum")]
   InvalidIndexChecksum,
+  #[error("invalid metadata section")]
+  InvalidMetadata,
+  #[error("invalid metadata checksum")]
+  InvalidMetadataChecksum,
   #[error("checksum mismatch")]
   ChecksumMismatch,
+  #[error("bundle verify failed: {}", .0.iter().map(|x| x.to_string()).collect::<Vec<_>>().join(", "))]
+  BundleVerifyFailed(Vec<crate::VerifyFailure>),
+  #[error("invalid entry: {0}")]
+  InvalidEntry(String),
+  #[error("{name} size exceeds the read limit ({size} > {limit} bytes)")]
+  ReadLimitExceeded { name: String, size: u64, limit: u64 },
   #[error("bundle not found")]
   BundleNotFound,
   #[cfg(feature = "source")]
@@ -45,15 +55,45 @@ pub enum Error {
     version: String,
     reason: String,
   },
+  #[cfg(feature = "source")]
+  #[error(
+    "bundle is incompatible with the host app (bundle_name: {bundle_name}, version: {version}): {reason}"
+  )]
+  BundleIncompatible {
+    bundle_name: String,
+    version: String,
+    reason: String,
+  },
+  #[cfg(feature = "source")]
+  #[error(
+    "bundle metadata does not match the manifest (bundle_name: {bundle_name}, version: {version}): {reason}"
+  )]
+  BundleMetadataMismatch {
+    bundle_name: String,
+    version: String,
+    reason: String,
+  },
   #[cfg(feature = "_serde")]
   #[error("serde json error: {0}")]
   SerdeJson(#[from] serde_json::Error),
+  #[cfg(feature = "protocol")]
+  #[error("invalid header rule `{rule}`: {reason}")]
+  InvalidHeaderRule { rule: String, reason: String },
   #[cfg(feature = "protocol-local")]
   #[error("cannot resolve local host")]
   CannotResolveLocalHost,
   #[cfg(feature = "_reqwest")]
   #[error("reqwest error: {0}")]
   Reqwest(#[from] reqwest::Error),
+  #[cfg(feature = "updater")]
+  #[error(
+    "bundle update is not eligible for this install (bundle_name: {bundle_name}, version: {version}): {reason}"
+  )]
+  UpdateNotEligible {
+    bundle_name: String,
+    version: String,
+    reason: String,
+  },
   #[cfg(feature = "remote")]
   #[error("invalid remote url: {0}")]
   InvalidRemoteUrl(#[from] http::uri::InvalidUri),
@@ -107,6 +147,10 @@ pub enum Error {
 }
 
 impl Error {
+  pub(crate) fn invalid_entry(message: impl Into<String>) -> Self {
+    Self::InvalidEntry(message.into())
+  }
+
   #[cfg(feature = "source")]
   pub(crate) fn bundle_entry_not_exists(
     bundle_name: impl Into<String>,
@@ -131,6 +175,45 @@ impl Error {
     }
   }
 
+  #[cfg(feature = "source")]
+  pub(crate) fn bundle_incompatible(
+    bundle_name: impl Into<String>,
+    version: impl Into<String>,
+    reason: impl ToString,
+  ) -> Self {
+    Self::BundleIncompatible {
+      bundle_name: bundle_name.into(),
+      version: version.into(),
+      reason: reason.to_string(),
+    }
+  }
+
+  #[cfg(feature = "source")]
+  pub(crate) fn bundle_metadata_mismatch(
+    bundle_name: impl Into<String>,
+    version: impl Into<String>,
+    reason: impl ToString,
+  ) -> Self {
+    Self::BundleMetadataMismatch {
+      bundle_name: bundle_name.into(),
+      version: version.into(),
+      reason: reason.to_string(),
+    }
+  }
+
+  #[cfg(feature = "updater")]
+  pub(crate) fn update_not_eligible(
+    bundle_name: impl Into<String>,
+    version: impl Into<String>,
+    reason: impl Into<String>,
+  ) -> Self {
+    Self::UpdateNotEligible {
+      bundle_name: bundle_name.into(),
+      version: version.into(),
+      reason: reason.into(),
+    }
+  }
+
   #[cfg(feature = "remote")]
   pub(crate) fn invalid_remote_config(message: impl Into<String>) -> Self {
     Self::InvalidRemoteConfig(message.into())
@@ -0,0 +1,153 @@
+//! Glob patterns matching entry paths.
+//!
+//! Patterns support `*` (any characters except `/`), `**` (any number of path segments), `?`
+//! (a single character) and `{a,b}` (alternatives).
+//!
+//! Patterns are compiled once and matched without backtracking, in `O(pattern * path)` time,
+//! so patterns from untrusted bundle metadata can't stall a request.
+
+/// Maximum number of patterns `{a,b}` alternatives expand into.
+pub(crate) const MAX_ALTERNATIVES: usize = 32;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Token {
+  Literal(u8),
+  /// `?`, a single character except `/`.
+  Any,
+  /// `*`, any characters except `/`.
+  Star,
+  /// `**`, any characters.
+  GlobStar,
+  /// `**/`, nothing or any characters ending with `/`.
+  GlobStarSlash,
+}
+
+/// A compiled glob pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Glob {
+  alternatives: Vec<Vec<Token>>,
+}
+
+impl Glob {
+  /// Compiles a pattern, failing when it expands into more than [`MAX_ALTERNATIVES`]
+  /// patterns.
+  pub(crate) fn new(pattern: &str) -> Result<Self, String> {
+    let alternatives = expand_braces(pattern)?
+      .iter()
+      .map(|x| tokenize(x.as_bytes()))
+      .collect();
+    Ok(Self { alternatives })
+  }
+
+  pub(crate) fn is_match(&self, path: &str) -> bool {
+    self
+      .alternatives
+      .iter()
+      .any(|x| match_tokens(x, path.as_bytes()))
+  }
+}
+
+/// Returns whether a path matches a glob pattern.
+pub(crate) fn matches(pattern: &str, path: &str) -> bool {
+  Glob::new(pattern).is_ok_and(|x| x.is_match(path))
+}
+
+/// Expands `{a,b}` alternatives into separate patterns.
+fn expand_braces(pattern: &str) -> Result<Vec<String>, String> {
+  let mut expanded = vec![String::new()];
+  let mut rest = pattern;
+  while let Some((start, end)) = rest
+    .find('{')
+    .and_then(|start| rest[start..].find('}').map(|end| (start, start + end)))
+  {
+    let prefix = &rest[..start];
+    let choices = rest[start + 1..end].split(',').collect::<Vec<_>>();
+    if expanded.len() * choices.len() > MAX_ALTERNATIVES {
+      return Err(format!(
+        "expands into more than {MAX_ALTERNATIVES} alternatives"
+      ));
+    }
+    expanded = expanded
+      .iter()
+      .flat_map(|x| {
+        choices
+          .iter()
+          .map(move |choice| format!("{x}{prefix}{choice}"))
+      })
+      .collect();
+    rest = &rest[end + 1..];
+  }
+  for x in &mut expanded {
+    x.push_str(rest);
+  }
+  Ok(expanded)
+}
+
+fn tokenize(pattern: &[u8]) -> Vec<Token> {
+  let mut tokens = Vec::with_capacity(pattern.len());
+  let mut i = 0;
+  while i < pattern.len() {
+    let token = match pattern[i] {
+      b'*' if pattern.get(i + 1) == Some(&b'*') => {
+        i += 1;
+        if pattern.get(i + 1) == Some(&b'/') {
+          i += 1;
+          Token::GlobStarSlash
+        } else {
+          Token::GlobStar
+        }
+      }
+      b'*' => Token::Star,
+      b'?' => Token::Any,
+      x => Token::Literal(x),
+    };
+    tokens.push(token);
+    i += 1;
+  }
+  tokens
+}
+
+/// Simulates the pattern as an automaton whose states are token positions.
+fn match_tokens(tokens: &[Token], path: &[u8]) -> bool {
+  let mut states = vec![false; tokens.len() + 1];
+  states[0] = true;
+  close(tokens, &mut states);
+  for &c in path {
+    let mut next = vec![false; tokens.len() + 1];
+    for (i, token) in tokens.iter().enumerate() {
+      if !states[i] {
+        continue;
+      }
+      match token {
+        Token::Literal(x) if *x == c => next[i + 1] = true,
+        Token::Literal(_) => {}
+        Token::Any if c != b'/' => next[i + 1] = true,
+        Token::Any => {}
+        Token::Star if c != b'/' => next[i] = true,
+        Token::Star => {}
+        Token::GlobStar => next[i] = true,
+        Token::GlobStarSlash => {
+          next[i] = true;
+          if c == b'/' {
+            next[i + 1] = true;
+          }
+        }
+      }
+    }
+    close(tokens, &mut next);
+    if !next.iter().any(|x| *x) {
+      return false;
+    }
+    states = next;
+  }
+  states[tokens.len()]
+}
+
+/// Adds the states reachable without consuming a character, stars matching nothing.
+fn close(tokens: &[Token], states: &mut [bool]) {
+  for (i, token) in tokens.iter().enumerate() {
+    if states[i] && matches!(token, Token::Star | Token::GlobStar | Token::GlobStarSlash) {
+      states[i + 1] = true;
+    }
+  }
+}
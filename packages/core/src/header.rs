@@ -13,14 +13,20 @@ use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, As
 
 /// Bundle header containing format metadata.
 ///
-/// The header is the first 17 bytes of a `.wvb` file:
+/// The header is the first 17 bytes of a `.wvb` file in version 1:
 ///
 /// | Magic (8) | Version (1) | Index Size (4) | Checksum (4) |
 /// |-----------|-------------|----------------|--------------|
 ///
+/// Version 2 adds the metadata size, making the header 21 bytes long:
+///
+/// | Magic (8) | Version (1) | Index Size (4) | Metadata Size (4) | Checksum (4) |
+/// |-----------|-------------|----------------|-------------------|--------------|
+///
 /// - **Magic Number**: `0xf09f8c90f09f8e81` (🌐🎁 in UTF-8)
-/// - **Version**: Bundle format version (currently 0x01)
+/// - **Version**: Bundle format version (0x01 or 0x02)
 /// - **Index Size**: Size of the index section in bytes (u32, big-endian)
+/// - **Metadata Size**: Size of the metadata section in bytes (u32, big-endian, version 2 only)
 /// - **Checksum**: xxHash-32 checksum of the header data
 ///
 /// # Example
@@ -36,6 +42,7 @@ use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, As
 pub struct Header {
   version: Version,
   index_size: u32,
+  metadata_size: u32,
 }
 
 impl Header {
@@ -57,17 +64,51 @@ impl Header {
   /// Length of the index size field in bytes
   pub const INDEX_SIZE_BYTES_LEN: usize = 4;
 
-  /// Offset of the header checksum
+  /// Offset of the header checksum in version 1
   pub const CHECKSUM_OFFSET: u64 = Self::INDEX_SIZE_OFFSET + Self::INDEX_SIZE_BYTES_LEN as u64;
 
-  /// Total size of the header in bytes (17 bytes)
+  /// Total size of the header in bytes in version 1 (17 bytes)
   pub const END_OFFSET: u64 = Self::CHECKSUM_OFFSET + CHECKSUM_LEN as u64;
 
+  /// Offset of the metadata size field (version 2)
+  pub const METADATA_SIZE_OFFSET: u64 = Self::INDEX_SIZE_OFFSET + Self::INDEX_SIZE_BYTES_LEN as u64;
+
+  /// Length of the metadata size field in bytes
+  pub const METADATA_SIZE_BYTES_LEN: usize = 4;
+
+  /// Returns the total size of the header in bytes for the given format version.
+  pub const fn len_of(version: Version) -> u64 {
+    match version {
+      Version::V1 => Self::END_OFFSET,
+      Version::V2 => Self::END_OFFSET + Self::METADATA_SIZE_BYTES_LEN as u64,
+    }
+  }
+
+  /// Returns the byte offset of the header checksum.
+  pub fn checksum_offset(&self) -> u64 {
+    self.end_offset() - CHECKSUM_LEN as u64
+  }
+
+  /// Returns the byte offset where the header ends.
+  pub fn end_offset(&self) -> u64 {
+    Self::len_of(self.version)
+  }
+
+  /// Returns the byte offset where the index section starts.
+  ///
+  /// In version 2, the metadata section sits between the header and the index.
+  pub fn index_offset(&self) -> u64 {
+    match self.version {
+      Version::V1 => self.end_offset(),
+      Version::V2 => self.end_offset() + self.metadata_size as u64 + CHECKSUM_LEN as u64,
+    }
+  }
+
   /// Calculates the byte offset where the index section ends.
   ///
   /// This is the starting point of the data section.
   pub fn index_end_offset(&self) -> u64 {
-    Self::END_OFFSET + self.index_size as u64 + CHECKSUM_LEN as u64
+    self.index_offset() + self.index_size as u64 + CHECKSUM_LEN as u64
   }
 
   /// Creates a new header.
@@ -76,13 +117,23 @@ impl Header {
   ///
   /// * `version` - Bundle format version
   /// * `index_size` - Size of the index section in bytes
-  pub fn new(version: Version, index_size: u32) -> Self {
+  pub const fn new(version: Version, index_size: u32) -> Self {
     Self {
       version,
       index_size,
+      metadata_size: 0,
     }
   }
 
+  /// Sets the size of the metadata section in bytes.
+  ///
+  /// Only stored in the header from version 2.
+  #[must_use]
+  pub fn with_metadata_size(mut self, metadata_size: u32) -> Self {
+    self.metadata_size = metadata_size;
+    self
+  }
+
   /// Returns the bundle format version.
   pub fn version(&self) -> Version {
     self.version
@@ -92,6 +143,11 @@ impl Header {
   pub fn index_size(&self) -> u32 {
     self.index_size
   }
+
+  /// Returns the size of the metadata section in bytes (always 0 in version 1).
+  pub fn metadata_size(&self) -> u32 {
+    self.metadata_size
+  }
 }
 
 fn write_magic() -> Vec<u8> {
@@ -106,6 +162,10 @@ fn write_index_size(index_size: u32) -> Vec<u8> {
   index_size.to_be_bytes().to_vec()
 }
 
+fn write_metadata_size(metadata_size: u32) -> Vec<u8> {
+  metadata_size.to_be_bytes().to_vec()
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct HeaderWriterOptions {
   pub(crate) checksum_seed: u32,
@@ -162,6 +222,12 @@ impl<W: Write> HeaderWriter<W> {
     Ok(bytes)
   }
 
+  pub fn write_metadata_size(&mut self, metadata_size: u32) -> crate::Result<Vec<u8>> {
+    let bytes = write_metadata_size(metadata_size);
+    self.w.write_all(&bytes)?;
+    Ok(bytes)
+  }
+
   pub fn write_checksum(&mut self, checksum: u32) -> crate::Result<Vec<u8>> {
     let bytes = write_checksum(checksum);
     self.w.write_all(&bytes)?;
@@ -175,6 +241,10 @@ impl<W: Write> Writer<Header> for HeaderWriter<W> {
     bytes.extend(self.write_magic()?);
     bytes.extend(self.write_version(header.version)?);
     bytes.extend(self.write_index_size(header.index_size)?);
+    match header.version {
+      Version::V1 => {}
+      Version::V2 => bytes.extend(self.write_metadata_size(header.metadata_size)?),
+    }
 
     let checksum = make_checksum(self.options.checksum_seed, &bytes);
     bytes.extend(self.write_checksum(checksum)?);
@@ -224,6 +294,12 @@ impl<W: AsyncWrite + Unpin> AsyncHeaderWriter<W> {
     Ok(bytes)
   }
 
+  pub async fn write_metadata_size(&mut self, metadata_size: u32) -> crate::Result<Vec<u8>> {
+    let bytes = write_metadata_size(metadata_size);
+    self.w.write_all(&bytes).await?;
+    Ok(bytes)
+  }
+
   pub async fn write_checksum(&mut self, checksum: u32) -> crate::Result<Vec<u8>> {
     let bytes = write_checksum(checksum);
     self.w.write_all(&bytes).await?;
@@ -238,6 +314,10 @@ impl<W: AsyncWrite + Unpin> AsyncWriter<Header> for AsyncHeaderWriter<W> {
     bytes.extend(self.write_magic().await?);
     bytes.extend(self.write_version(header.version).await?);
     bytes.extend(self.write_index_size(header.index_size).await?);
+    match header.version {
+      Version::V1 => {}
+      Version::V2 => bytes.extend(self.write_metadata_size(header.metadata_size).await?),
+    }
 
     let checksum = make_checksum(self.options.checksum_seed, &bytes);
     bytes.extend(self.write_checksum(checksum).await?);
@@ -261,10 +341,7 @@ fn read_version() -> (u64, [u8; VERSION_LEN]) {
 }
 
 fn parse_version(buf: &[u8; VERSION_LEN]) -> crate::Result<Version> {
-  if buf == Version::V1.bytes().as_ref() {
-    return Ok(Version::V1);
-  }
-  Err(crate::Error::InvalidVersion)
+  Version::from_bytes(*buf).ok_or(crate::Error::InvalidVersion)
 }
 
 fn read_index_size() -> (u64, [u8; Header::INDEX_SIZE_BYTES_LEN]) {
@@ -278,17 +355,29 @@ fn parse_index_size(buf: &[u8; Header::INDEX_SIZE_BYTES_LEN]) -> u32 {
   u32::from_be_bytes(AsRef::<[u8]>::as_ref(&buf).try_into().unwrap())
 }
 
-fn read_checksum() -> (u64, [u8; CHECKSUM_LEN]) {
-  (Header::CHECKSUM_OFFSET, [0u8; CHECKSUM_LEN])
+fn read_metadata_size() -> (u64, [u8; Header::METADATA_SIZE_BYTES_LEN]) {
+  (
+    Header::METADATA_SIZE_OFFSET,
+    [0u8; Header::METADATA_SIZE_BYTES_LEN],
+  )
+}
+
+fn parse_metadata_size(buf: &[u8; Header::METADATA_SIZE_BYTES_LEN]) -> u32 {
+  u32::from_be_bytes(*buf)
 }
 
-fn read_total() -> (u64, [u8; Header::CHECKSUM_OFFSET as usize]) {
+fn read_checksum(version: Version) -> (u64, [u8; CHECKSUM_LEN]) {
   (
-    Header::MAGIC_OFFSET,
-    [0u8; Header::CHECKSUM_OFFSET as usize],
+    Header::len_of(version) - CHECKSUM_LEN as u64,
+    [0u8; CHECKSUM_LEN],
   )
 }
 
+fn read_total(version: Version) -> (u64, Vec<u8>) {
+  let len = Header::len_of(version) as usize - CHECKSUM_LEN;
+  (Header::MAGIC_OFFSET, vec![0u8; len])
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct HeaderReaderOptions {
   pub checksum_seed: u32,
@@ -352,16 +441,24 @@ impl<R: Read + Seek> HeaderReader<R> {
     Ok(parse_index_size(&buf))
   }
 
+  pub fn read_metadata_size(&mut self) -> crate::Result<u32> {
+    let (offset, mut buf) = read_metadata_size();
+    self.r.seek(SeekFrom::Start(offset))?;
+    self.r.read_exact(&mut buf)?;
+    Ok(parse_metadata_size(&buf))
+  }
+
   pub fn read_checksum(&mut self) -> crate::Result<u32> {
-    let (offset, mut buf) = read_checksum();
+    let version = self.read_version()?;
+    let (offset, mut buf) = read_checksum(version);
     self.r.seek(SeekFrom::Start(offset))?;
     self.r.read_exact(&mut buf)?;
     let checksum = parse_checksum(&buf);
     Ok(checksum)
   }
 
-  fn verify_checksum(&mut self, checksum: u32) -> crate::Result<()> {
-    let (offset, mut total) = read_total();
+  fn verify_checksum(&mut self, version: Version, checksum: u32) -> crate::Result<()> {
+    let (offset, mut total) = read_total(version);
     self.r.seek(SeekFrom::Start(offset))?;
     self.r.read_exact(&mut total)?;
 
@@ -378,11 +475,15 @@ impl<R: Read + Seek> Reader<Header> for HeaderReader<R> {
     self.read_magic()?;
     let version = self.read_version()?;
     let index_size = self.read_index_size()?;
+    let metadata_size = match version {
+      Version::V1 => 0,
+      Version::V2 => self.read_metadata_size()?,
+    };
     let checksum = self.read_checksum()?;
     if self.options.verify_checksum {
-      self.verify_checksum(checksum)?;
+      self.verify_checksum(version, checksum)?;
     }
-    Ok(Header::new(version, index_size))
+    Ok(Header::new(version, index_size).with_metadata_size(metadata_size))
   }
 }
 
@@ -429,16 +530,24 @@ impl<R: AsyncRead + AsyncSeek + Unpin> AsyncHeaderReader<R> {
     Ok(parse_index_size(&buf))
   }
 
+  pub async fn read_metadata_size(&mut self) -> crate::Result<u32> {
+    let (offset, mut buf) = read_metadata_size();
+    self.r.seek(SeekFrom::Start(offset)).await?;
+    self.r.read_exact(&mut buf).await?;
+    Ok(parse_metadata_size(&buf))
+  }
+
   pub async fn read_checksum(&mut self) -> crate::Result<u32> {
-    let (offset, mut buf) = read_checksum();
+    let version = self.read_version().await?;
+    let (offset, mut buf) = read_checksum(version);
     self.r.seek(SeekFrom::Start(offset)).await?;
     self.r.read_exact(&mut buf).await?;
     let checksum = parse_checksum(&buf);
     Ok(checksum)
   }
 
-  async fn verify_checksum(&mut self, checksum: u32) -> crate::Result<()> {
-    let (offset, mut total) = read_total();
+  async fn verify_checksum(&mut self, version: Version, checksum: u32) -> crate::Result<()> {
+    let (offset, mut total) = read_total(version);
     self.r.seek(SeekFrom::Start(offset)).await?;
     self.r.read_exact(&mut total).await?;
 
@@ -456,11 +565,15 @@ impl<R: AsyncRead + AsyncSeek + Unpin> AsyncReader<Header> for AsyncHeaderReader
     self.read_magic().await?;
     let version = self.read_version().await?;
     let index_size = self.read_index_size().await?;
+    let metadata_size = match version {
+      Version::V1 => 0,
+      Version::V2 => self.read_metadata_size().await?,
+    };
     let checksum = self.read_checksum().await?;
     if self.options.verify_checksum {
-      self.verify_checksum(checksum).await?;
+      self.verify_checksum(version, checksum).await?;
     }
-    Ok(Header::new(version, index_size))
+    Ok(Header::new(version, index_size).with_metadata_size(metadata_size))
   }
 }
 
@@ -507,4 +620,32 @@ mod tests {
     assert_eq!(read_header.version(), Version::V1);
     assert_eq!(read_header.index_size(), 1234);
   }
+
+  #[test]
+  fn read_and_write_v2() {
+    let header = Header::new(Version::V2, 1234).with_metadata_size(56);
+    let mut buf = vec![];
+    HeaderWriter::new(Cursor::new(&mut buf))
+      .write(&header)
+      .unwrap();
+    assert_eq!(buf.len() as u64, Header::len_of(Version::V2));
+    assert_eq!(buf[8], 2);
+    let options = HeaderReaderOptions::new().verify_checksum(true);
+    let mut reader = HeaderReader::new_with_options(Cursor::new(&buf), options);
+    let read_header = reader.read().unwrap();
+    assert_eq!(header, read_header);
+    assert_eq!(read_header.metadata_size(), 56);
+    assert_eq!(read_header.index_offset(), 21 + 56 + 4);
+  }
+
+  #[test]
+  fn invalid_version() {
+    let mut buf = vec![];
+    HeaderWriter::new(Cursor::new(&mut buf))
+      .write(&Header::new(Version::V1, 1234))
+      .unwrap();
+    buf[8] = 0xff;
+    let mut reader = HeaderReader::new(Cursor::new(&buf));
+    assert!(matches!(reader.read(), Err(crate::Error::InvalidVersion)));
+  }
 }
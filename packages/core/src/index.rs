@@ -1,6 +1,6 @@
 use crate::checksum::{CHECKSUM_LEN, make_checksum, parse_checksum, write_checksum};
 use crate::header::Header;
-use crate::reader::Reader;
+use crate::reader::{DECODE_LIMIT, Reader, check_limit, read_len};
 use crate::writer::Writer;
 use bincode::de::Decoder;
 use bincode::enc::Encoder;
@@ -12,7 +12,7 @@ use std::io::{Read, Seek, SeekFrom, Write};
 use std::ops::{Deref, DerefMut};
 
 #[cfg(feature = "async")]
-use crate::reader::AsyncReader;
+use crate::reader::{AsyncReader, async_read_len};
 #[cfg(feature = "async")]
 use crate::writer::AsyncWriter;
 #[cfg(feature = "async")]
@@ -112,15 +112,12 @@ impl Encode for IndexEntry {
   }
 }
 
+type RawIndexEntry = (u64, u64, Vec<u8>, u64, Vec<(String, Vec<u8>)>);
+
 impl<T> Decode<T> for IndexEntry {
   fn decode<D: Decoder>(decoder: &mut D) -> Result<Self, DecodeError> {
-    let (offset, len, content_type_raw, content_length, pairs): (
-      u64,
-      u64,
-      Vec<u8>,
-      u64,
-      Vec<(String, Vec<u8>)>,
-    ) = Decode::decode(decoder)?;
+    let (offset, len, content_type_raw, content_length, pairs): RawIndexEntry =
+      Decode::decode(decoder)?;
     let content_type = String::from_utf8(content_type_raw)
       .map_err(|_| DecodeError::OtherString("invalid content type".into()))?;
     let mut headers = HeaderMap::new();
@@ -352,12 +349,16 @@ impl<W: AsyncWrite + Unpin> AsyncWriter<Index> for AsyncIndexWriter<W> {
   }
 }
 
-fn read_index(header: &Header) -> (u64, Vec<u8>) {
-  (Header::END_OFFSET, vec![0u8; header.index_size() as usize])
+fn read_index(header: &Header, options: &IndexReaderOptions) -> crate::Result<(u64, u64)> {
+  let size = header.index_size() as u64;
+  check_limit("index", size, options.max_size.map(u64::from))?;
+  Ok((header.index_offset(), size))
 }
 
 fn parse_index(buf: &[u8]) -> crate::Result<Index> {
-  let config = config::standard().with_big_endian();
+  let config = config::standard()
+    .with_big_endian()
+    .with_limit::<DECODE_LIMIT>();
   let (entries, _): (IndexEntryMap, _) =
     decode_from_slice(buf, config).map_err(|e| crate::Error::Decode {
       error: e,
@@ -368,15 +369,11 @@ fn parse_index(buf: &[u8]) -> crate::Result<Index> {
 
 fn read_checksum(header: &Header) -> (u64, [u8; CHECKSUM_LEN]) {
   (
-    Header::END_OFFSET + header.index_size() as u64,
+    header.index_offset() + header.index_size() as u64,
     [0u8; CHECKSUM_LEN],
   )
 }
 
-fn read_total(header: &Header) -> (u64, Vec<u8>) {
-  (Header::END_OFFSET, vec![0u8; header.index_size() as usize])
-}
-
 pub struct IndexReader<R: Read + Seek> {
   r: R,
   header: Header,
@@ -387,6 +384,8 @@ pub struct IndexReader<R: Read + Seek> {
 pub struct IndexReaderOptions {
   pub checksum_seed: u32,
   pub verify_checksum: bool,
+  /// Maximum size of the index section in bytes.
+  pub max_size: Option<u32>,
 }
 
 impl IndexReaderOptions {
@@ -403,6 +402,11 @@ impl IndexReaderOptions {
     self.verify_checksum = verify;
     self
   }
+
+  pub fn max_size(mut self, size: u32) -> Self {
+    self.max_size = Some(size);
+    self
+  }
 }
 
 impl<R: Read + Seek> IndexReader<R> {
@@ -415,9 +419,9 @@ impl<R: Read + Seek> IndexReader<R> {
   }
 
   pub fn read_index(&mut self) -> crate::Result<Index> {
-    let (offset, mut buf) = read_index(&self.header);
+    let (offset, len) = read_index(&self.header, &self.options)?;
     self.r.seek(SeekFrom::Start(offset))?;
-    self.r.read_exact(&mut buf)?;
+    let buf = read_len(&mut self.r, len)?;
     parse_index(&buf)
   }
 
@@ -429,10 +433,16 @@ impl<R: Read + Seek> IndexReader<R> {
     Ok(checksum)
   }
 
+  /// Checks the index section against its checksum, without decoding it.
+  pub fn verify(&mut self) -> crate::Result<()> {
+    let checksum = self.read_checksum()?;
+    self.verify_checksum(checksum)
+  }
+
   fn verify_checksum(&mut self, checksum: u32) -> crate::Result<()> {
-    let (offset, mut buf) = read_total(&self.header);
+    let (offset, len) = read_index(&self.header, &self.options)?;
     self.r.seek(SeekFrom::Start(offset))?;
-    self.r.read_exact(&mut buf)?;
+    let buf = read_len(&mut self.r, len)?;
 
     let expected_checksum = make_checksum(self.options.checksum_seed, &buf);
     if checksum != expected_checksum {
@@ -471,9 +481,9 @@ impl<R: AsyncRead + AsyncSeek + Unpin> AsyncIndexReader<R> {
   }
 
   pub async fn read_index(&mut self) -> crate::Result<Index> {
-    let (offset, mut buf) = read_index(&self.header);
+    let (offset, len) = read_index(&self.header, &self.options)?;
     self.r.seek(SeekFrom::Start(offset)).await?;
-    self.r.read_exact(&mut buf).await?;
+    let buf = async_read_len(&mut self.r, len).await?;
     parse_index(&buf)
   }
 
@@ -485,10 +495,16 @@ impl<R: AsyncRead + AsyncSeek + Unpin> AsyncIndexReader<R> {
     Ok(checksum)
   }
 
+  /// Checks the index section against its checksum, without decoding it.
+  pub async fn verify(&mut self) -> crate::Result<()> {
+    let checksum = self.read_checksum().await?;
+    self.verify_checksum(checksum).await
+  }
+
   async fn verify_checksum(&mut self, checksum: u32) -> crate::Result<()> {
-    let (offset, mut buf) = read_total(&self.header);
+    let (offset, len) = read_index(&self.header, &self.options)?;
     self.r.seek(SeekFrom::Start(offset)).await?;
-    self.r.read_exact(&mut buf).await?;
+    let buf = async_read_len(&mut self.r, len).await?;
 
     let expected_checksum = make_checksum(self.options.checksum_seed, &buf);
     if checksum != expected_checksum {
@@ -537,6 +553,18 @@ mod tests {
     );
   }
 
+  #[test]
+  fn huge_length_prefix() {
+    // a map of 2^40 entries
+    let mut buf = vec![253];
+    buf.extend((1u64 << 40).to_be_bytes());
+    assert!(parse_index(&buf).is_err());
+    // an entry whose path is 2^40 bytes long
+    let mut buf = vec![1, 253];
+    buf.extend((1u64 << 40).to_be_bytes());
+    assert!(parse_index(&buf).is_err());
+  }
+
   #[cfg(feature = "async")]
   #[tokio::test]
   async fn async_read_and_write() {
@@ -1,4 +1,4 @@
-#[derive(PartialEq, Eq, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum IntegrityPolicy {
   Strict,
   #[default]
@@ -16,15 +16,26 @@
 //!
 //! ## Bundle Format
 //!
-//! The `.wvb` format consists of three main parts:
+//! The `.wvb` format consists of three main parts, followed by an optional metadata section:
 //!
-//! | Header (17 bytes) | Index (variable) | Data (variable) |
-//! |-------------------|------------------|-----------------|
-//! | Magic number, version, index size, checksum | File paths and metadata | Compressed file contents |
+//! | Header (17 bytes) | Index (variable) | Data (variable) | Metadata (optional) |
+//! |-------------------|------------------|-----------------|---------------------|
+//! | Magic number, version, index size, checksum | File paths and metadata | Compressed file contents | Bundle name, version and custom key/values |
 //!
 //! - **Header**: Magic number (🌐🎁), format version, index size, and checksum
 //! - **Index**: HashMap of file paths to offset/length/headers, with checksum
 //! - **Data**: LZ4-compressed file contents with xxHash-32 checksums
+//! - **Metadata**: Bundle-level key/values with checksum, see [`BundleMetadata`]
+//!
+//! ### Format Versions
+//!
+//! - **V1**: The layout above. Written by default so bundles stay readable by existing clients.
+//! - **V2**: The header also stores the metadata size, and the metadata section sits between
+//!   the header and the index.
+//!
+//! Readers dispatch on the version byte of the header. Writers can target an explicit version
+//! with [`BundleWriter::new_with_version`], and [`migrate`] rewrites a bundle into the newest
+//! format.
 //!
 //! ## Quick Start
 //!
@@ -57,8 +68,10 @@
 //! ## Features
 //!
 //! - `async`: Async I/O support with tokio
+//! - `dir`: Adding directories to a bundle builder, with ignore rules and MIME detection
 //! - `source`: Bundle source management (builtin/remote)
 //! - `remote`: HTTP client for downloading bundles
+//! - `remote-server`: HTTP server serving bundles to remote clients
 //! - `updater`: Automatic bundle updates
 //! - `protocol`: Custom protocol handlers for serving bundles
 //! - `protocol-local`: Local file protocol support
@@ -112,7 +125,10 @@ mod checksum;
 mod error;
 mod header;
 mod index;
+mod metadata;
+mod migrate;
 mod reader;
+mod verify;
 mod version;
 mod writer;
 
@@ -121,18 +137,33 @@ pub(crate) type Result<T> = std::result::Result<T, Error>;
 pub use builder::*;
 pub use bundle::*;
 pub use consts::*;
+#[cfg(feature = "dir")]
+pub use dir::*;
 pub use error::Error;
 pub use header::*;
 pub use index::*;
+pub use metadata::*;
+pub use migrate::*;
 pub use reader::*;
+pub use verify::*;
 pub use version::*;
 pub use writer::*;
 
 pub use http;
+#[cfg(feature = "source")]
+pub use semver;
 
 mod consts;
+#[cfg(feature = "dir")]
+mod dir;
+#[cfg(any(feature = "protocol", feature = "dir"))]
+mod glob;
+#[cfg(feature = "_reqwest")]
+mod http_config;
 #[cfg(feature = "integrity")]
 pub mod integrity;
+#[cfg(feature = "dir")]
+pub mod mime;
 #[cfg(feature = "protocol")]
 pub mod protocol;
 #[cfg(feature = "remote")]
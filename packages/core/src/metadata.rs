@@ -0,0 +1,747 @@
+use crate::checksum::{CHECKSUM_LEN, make_checksum, parse_checksum, write_checksum};
+use crate::header::Header;
+use crate::reader::{DECODE_LIMIT, Reader, check_limit, read_len};
+use crate::version::Version;
+use crate::writer::Writer;
+use bincode::{config, decode_from_slice, encode_to_vec};
+use std::collections::BTreeMap;
+use std::io::{Read, Seek, SeekFrom, Write};
+
+#[cfg(feature = "async")]
+use crate::reader::{AsyncReader, async_read_len};
+#[cfg(feature = "async")]
+use crate::writer::AsyncWriter;
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncSeek, AsyncSeekExt, AsyncWrite, AsyncWriteExt};
+
+/// Bundle-level metadata.
+///
+/// Metadata is a set of string key/values describing the bundle itself, such as its name,
+/// version or the commit it was built from. Well-known keys have typed accessors, and any
+/// other key can be used for custom values.
+///
+/// In version 1, the metadata section is stored at the end of a `.wvb` file, after the data
+/// section:
+///
+/// | Metadata (variable) | Checksum (4) | Metadata Size (4) | Magic (8) |
+/// |---------------------|--------------|-------------------|-----------|
+///
+/// - **Metadata**: Binary-encoded key/values
+/// - **Checksum**: xxHash-32 checksum of the metadata
+/// - **Metadata Size**: Size of the metadata in bytes (u32, big-endian)
+/// - **Magic**: `wvb-meta` in ASCII
+///
+/// The section is omitted when the metadata is empty, and readers which don't know about the
+/// section ignore it, so bundles with metadata remain readable by older readers.
+///
+/// In version 2, the metadata size is stored in the [`Header`] and the section sits right
+/// after the header:
+///
+/// | Metadata (variable) | Checksum (4) |
+/// |---------------------|--------------|
+///
+/// # Example
+///
+/// ```
+/// use wvb::BundleMetadata;
+///
+/// let mut metadata = BundleMetadata::new();
+/// metadata.set_name("app").set_version("1.0.0");
+/// metadata.insert("channel", "beta");
+/// assert_eq!(metadata.name(), Some("app"));
+/// assert_eq!(metadata.get("channel"), Some("beta"));
+/// ```
+#[derive(Debug, Default, PartialEq, Eq, Clone)]
+pub struct BundleMetadata {
+  entries: BTreeMap<String, String>,
+}
+
+impl BundleMetadata {
+  /// Key of the bundle name.
+  pub const NAME: &'static str = "name";
+
+  /// Key of the bundle version.
+  pub const VERSION: &'static str = "version";
+
+  /// Key of the build time.
+  pub const BUILT_AT: &'static str = "builtAt";
+
+  /// Key of the git commit the bundle is built from.
+  pub const GIT_COMMIT: &'static str = "gitCommit";
+
+  /// Key of the semver range of host app versions the bundle is compatible with.
+  pub const COMPATIBLE_APP_VERSION: &'static str = "compatibleAppVersion";
+
+  /// Key of the comma-separated native capabilities the bundle requires.
+  pub const REQUIRED_CAPABILITIES: &'static str = "requiredCapabilities";
+
+  /// Key of the routing fallback for paths not found in the bundle.
+  ///
+  /// Either `spa` (serve `/index.html`), a single entry path, or comma-separated
+  /// `prefix=entry` rules such as `/admin=/admin/index.html,/=/index.html`.
+  pub const FALLBACK: &'static str = "fallback";
+
+  /// Key of the entry path served with status 404 when nothing else matches.
+  pub const NOT_FOUND: &'static str = "notFound";
+
+  /// Key of the response header rules, applied to entries matching glob patterns.
+  ///
+  /// See `HeaderRules` in the `protocol` module for the format.
+  pub const HEADERS: &'static str = "headers";
+
+  /// Key of the comma-separated entry paths decompressed into the protocol cache when the
+  /// bundle is loaded. Entries referenced by HTML entries in the list are warmed up too.
+  pub const WARMUP: &'static str = "warmup";
+
+  /// Length of the magic number in bytes.
+  pub const MAGIC_LEN: usize = 8;
+
+  /// Magic number bytes: "wvb-meta".
+  pub const MAGIC: [u8; BundleMetadata::MAGIC_LEN] = *b"wvb-meta";
+
+  /// Length of the metadata size field in bytes.
+  pub const SIZE_BYTES_LEN: usize = 4;
+
+  /// Length of the fields following the metadata (checksum, size and magic number).
+  pub const TRAILER_LEN: usize = CHECKSUM_LEN + Self::SIZE_BYTES_LEN + Self::MAGIC_LEN;
+
+  /// Creates a new empty metadata.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Returns `true` if there are no key/values.
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Returns the number of key/values.
+  pub fn len(&self) -> usize {
+    self.entries.len()
+  }
+
+  /// Returns all key/values.
+  pub fn entries(&self) -> &BTreeMap<String, String> {
+    &self.entries
+  }
+
+  pub fn get(&self, key: &str) -> Option<&str> {
+    self.entries.get(key).map(|x| x.as_str())
+  }
+
+  pub fn insert(&mut self, key: impl Into<String>, value: impl Into<String>) -> Option<String> {
+    self.entries.insert(key.into(), value.into())
+  }
+
+  pub fn remove(&mut self, key: &str) -> Option<String> {
+    self.entries.remove(key)
+  }
+
+  pub fn contains_key(&self, key: &str) -> bool {
+    self.entries.contains_key(key)
+  }
+
+  /// Returns the bundle name.
+  pub fn name(&self) -> Option<&str> {
+    self.get(Self::NAME)
+  }
+
+  pub fn set_name(&mut self, name: impl Into<String>) -> &mut Self {
+    self.insert(Self::NAME, name);
+    self
+  }
+
+  /// Returns the bundle version.
+  pub fn version(&self) -> Option<&str> {
+    self.get(Self::VERSION)
+  }
+
+  pub fn set_version(&mut self, version: impl Into<String>) -> &mut Self {
+    self.insert(Self::VERSION, version);
+    self
+  }
+
+  /// Returns the build time (e.g. an RFC 3339 timestamp).
+  pub fn built_at(&self) -> Option<&str> {
+    self.get(Self::BUILT_AT)
+  }
+
+  pub fn set_built_at(&mut self, built_at: impl Into<String>) -> &mut Self {
+    self.insert(Self::BUILT_AT, built_at);
+    self
+  }
+
+  /// Returns the git commit the bundle is built from.
+  pub fn git_commit(&self) -> Option<&str> {
+    self.get(Self::GIT_COMMIT)
+  }
+
+  pub fn set_git_commit(&mut self, commit: impl Into<String>) -> &mut Self {
+    self.insert(Self::GIT_COMMIT, commit);
+    self
+  }
+
+  /// Returns the semver range of host app versions the bundle is compatible with.
+  pub fn compatible_app_version(&self) -> Option<&str> {
+    self.get(Self::COMPATIBLE_APP_VERSION)
+  }
+
+  pub fn set_compatible_app_version(&mut self, version: impl Into<String>) -> &mut Self {
+    self.insert(Self::COMPATIBLE_APP_VERSION, version);
+    self
+  }
+
+  /// Returns the native capabilities the bundle requires from the host app.
+  pub fn required_capabilities(&self) -> Vec<String> {
+    self
+      .get(Self::REQUIRED_CAPABILITIES)
+      .map(|x| {
+        x.split(',')
+          .map(|x| x.trim())
+          .filter(|x| !x.is_empty())
+          .map(String::from)
+          .collect()
+      })
+      .unwrap_or_default()
+  }
+
+  pub fn set_required_capabilities<I, S>(&mut self, capabilities: I) -> &mut Self
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+  {
+    let value = capabilities
+      .into_iter()
+      .map(|x| x.as_ref().to_string())
+      .collect::<Vec<_>>()
+      .join(",");
+    self.insert(Self::REQUIRED_CAPABILITIES, value);
+    self
+  }
+
+  /// Returns the routing fallback for paths not found in the bundle.
+  pub fn fallback(&self) -> Option<&str> {
+    self.get(Self::FALLBACK)
+  }
+
+  pub fn set_fallback(&mut self, fallback: impl Into<String>) -> &mut Self {
+    self.insert(Self::FALLBACK, fallback);
+    self
+  }
+
+  /// Returns the entry path served with status 404.
+  pub fn not_found(&self) -> Option<&str> {
+    self.get(Self::NOT_FOUND)
+  }
+
+  pub fn set_not_found(&mut self, path: impl Into<String>) -> &mut Self {
+    self.insert(Self::NOT_FOUND, path);
+    self
+  }
+
+  /// Returns the response header rules.
+  pub fn headers(&self) -> Option<&str> {
+    self.get(Self::HEADERS)
+  }
+
+  pub fn set_headers(&mut self, rules: impl Into<String>) -> &mut Self {
+    self.insert(Self::HEADERS, rules);
+    self
+  }
+
+  /// Returns the entry paths warmed up when the bundle is loaded.
+  pub fn warmup(&self) -> Vec<String> {
+    self
+      .get(Self::WARMUP)
+      .map(|x| {
+        x.split(',')
+          .map(|x| x.trim())
+          .filter(|x| !x.is_empty())
+          .map(String::from)
+          .collect()
+      })
+      .unwrap_or_default()
+  }
+
+  pub fn set_warmup<I, S>(&mut self, paths: I) -> &mut Self
+  where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+  {
+    let value = paths
+      .into_iter()
+      .map(|x| x.as_ref().to_string())
+      .collect::<Vec<_>>()
+      .join(",");
+    self.insert(Self::WARMUP, value);
+    self
+  }
+}
+
+fn write_metadata(metadata: &BundleMetadata) -> crate::Result<Vec<u8>> {
+  let config = config::standard().with_big_endian();
+  let bytes = encode_to_vec(&metadata.entries, config).map_err(|e| crate::Error::Encode {
+    error: e,
+    message: "fail to encode metadata".to_string(),
+  })?;
+  Ok(bytes)
+}
+
+/// Returns the size of the metadata to store in the header of the given format version.
+pub(crate) fn metadata_size(version: Version, metadata: &BundleMetadata) -> crate::Result<u32> {
+  match version {
+    Version::V1 => Ok(0),
+    Version::V2 => Ok(write_metadata(metadata)?.len() as u32),
+  }
+}
+
+fn write_trailer(size: u32) -> Vec<u8> {
+  let mut bytes = size.to_be_bytes().to_vec();
+  bytes.extend_from_slice(&BundleMetadata::MAGIC);
+  bytes
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct MetadataWriterOptions {
+  pub(crate) checksum_seed: u32,
+}
+
+impl MetadataWriterOptions {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn checksum_seed(&mut self, seed: u32) -> &mut Self {
+    self.checksum_seed = seed;
+    self
+  }
+}
+
+/// Writes the metadata section in the layout of the given format version.
+pub struct MetadataWriter<W: Write> {
+  w: W,
+  version: Version,
+  options: MetadataWriterOptions,
+}
+
+impl<W: Write> MetadataWriter<W> {
+  pub fn new(w: W, version: Version) -> Self {
+    Self::new_with_options(w, version, Default::default())
+  }
+
+  pub fn new_with_options(w: W, version: Version, options: MetadataWriterOptions) -> Self {
+    Self {
+      w,
+      version,
+      options,
+    }
+  }
+
+  pub fn write_metadata(&mut self, metadata: &BundleMetadata) -> crate::Result<Vec<u8>> {
+    let bytes = write_metadata(metadata)?;
+    self.w.write_all(&bytes)?;
+    Ok(bytes)
+  }
+
+  pub fn write_checksum(&mut self, checksum: u32) -> crate::Result<Vec<u8>> {
+    let bytes = write_checksum(checksum);
+    self.w.write_all(&bytes)?;
+    Ok(bytes)
+  }
+
+  pub fn write_trailer(&mut self, size: u32) -> crate::Result<Vec<u8>> {
+    let bytes = write_trailer(size);
+    self.w.write_all(&bytes)?;
+    Ok(bytes)
+  }
+}
+
+impl<W: Write> Writer<BundleMetadata> for MetadataWriter<W> {
+  fn write(&mut self, metadata: &BundleMetadata) -> crate::Result<usize> {
+    if self.version == Version::V1 && metadata.is_empty() {
+      return Ok(0);
+    }
+    let mut bytes = self.write_metadata(metadata)?;
+    let size = bytes.len() as u32;
+    let checksum = make_checksum(self.options.checksum_seed, &bytes);
+    bytes.extend(self.write_checksum(checksum)?);
+    match self.version {
+      Version::V1 => bytes.extend(self.write_trailer(size)?),
+      Version::V2 => {}
+    }
+    Ok(bytes.len())
+  }
+}
+
+#[cfg(feature = "async")]
+pub struct AsyncMetadataWriter<W: AsyncWrite + Unpin> {
+  w: W,
+  version: Version,
+  options: MetadataWriterOptions,
+}
+
+#[cfg(feature = "async")]
+impl<W: AsyncWrite + Unpin> AsyncMetadataWriter<W> {
+  pub fn new(w: W, version: Version) -> Self {
+    Self::new_with_options(w, version, Default::default())
+  }
+
+  pub fn new_with_options(w: W, version: Version, options: MetadataWriterOptions) -> Self {
+    Self {
+      w,
+      version,
+      options,
+    }
+  }
+
+  pub async fn write_metadata(&mut self, metadata: &BundleMetadata) -> crate::Result<Vec<u8>> {
+    let bytes = write_metadata(metadata)?;
+    self.w.write_all(&bytes).await?;
+    Ok(bytes)
+  }
+
+  pub async fn write_checksum(&mut self, checksum: u32) -> crate::Result<Vec<u8>> {
+    let bytes = write_checksum(checksum);
+    self.w.write_all(&bytes).await?;
+    Ok(bytes)
+  }
+
+  pub async fn write_trailer(&mut self, size: u32) -> crate::Result<Vec<u8>> {
+    let bytes = write_trailer(size);
+    self.w.write_all(&bytes).await?;
+    Ok(bytes)
+  }
+}
+
+#[cfg(feature = "async")]
+impl<W: AsyncWrite + Unpin> AsyncWriter<BundleMetadata> for AsyncMetadataWriter<W> {
+  async fn write(&mut self, metadata: &BundleMetadata) -> crate::Result<usize> {
+    if self.version == Version::V1 && metadata.is_empty() {
+      return Ok(0);
+    }
+    let mut bytes = self.write_metadata(metadata).await?;
+    let size = bytes.len() as u32;
+    let checksum = make_checksum(self.options.checksum_seed, &bytes);
+    bytes.extend(self.write_checksum(checksum).await?);
+    match self.version {
+      Version::V1 => bytes.extend(self.write_trailer(size).await?),
+      Version::V2 => {}
+    }
+    Ok(bytes.len())
+  }
+}
+
+const FOOTER_LEN: usize = BundleMetadata::SIZE_BYTES_LEN + BundleMetadata::MAGIC_LEN;
+
+fn read_footer() -> (SeekFrom, [u8; FOOTER_LEN]) {
+  (SeekFrom::End(-(FOOTER_LEN as i64)), [0u8; FOOTER_LEN])
+}
+
+fn parse_footer(
+  header: &Header,
+  file_len: u64,
+  buf: &[u8; FOOTER_LEN],
+) -> crate::Result<Option<u32>> {
+  if buf[BundleMetadata::SIZE_BYTES_LEN..] != BundleMetadata::MAGIC {
+    return Ok(None);
+  }
+  let size = u32::from_be_bytes(buf[..BundleMetadata::SIZE_BYTES_LEN].try_into().unwrap());
+  let section_len = size as u64 + BundleMetadata::TRAILER_LEN as u64;
+  if file_len < header.index_end_offset() + section_len {
+    return Err(crate::Error::InvalidMetadata);
+  }
+  Ok(Some(size))
+}
+
+fn read_trailer_section(file_len: u64, size: u32) -> (u64, u32) {
+  let offset = file_len - size as u64 - BundleMetadata::TRAILER_LEN as u64;
+  (offset, size)
+}
+
+fn read_header_section(header: &Header) -> (u64, u32) {
+  (header.end_offset(), header.metadata_size())
+}
+
+/// Returns the length of a metadata section of `size` bytes, followed by its checksum.
+fn section_len(size: u32, options: &MetadataReaderOptions) -> crate::Result<u64> {
+  check_limit("metadata", size as u64, options.max_size.map(u64::from))?;
+  Ok(size as u64 + CHECKSUM_LEN as u64)
+}
+
+fn parse_section(buf: &[u8], options: &MetadataReaderOptions) -> crate::Result<BundleMetadata> {
+  let (data, checksum) = buf.split_at(buf.len() - CHECKSUM_LEN);
+  if options.verify_checksum
+    && parse_checksum(checksum) != make_checksum(options.checksum_seed, data)
+  {
+    return Err(crate::Error::InvalidMetadataChecksum);
+  }
+  let config = config::standard()
+    .with_big_endian()
+    .with_limit::<DECODE_LIMIT>();
+  let (entries, _): (BTreeMap<String, String>, _) =
+    decode_from_slice(data, config).map_err(|e| crate::Error::Decode {
+      error: e,
+      message: "fail to decode metadata".to_string(),
+    })?;
+  Ok(BundleMetadata { entries })
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct MetadataReaderOptions {
+  pub checksum_seed: u32,
+  pub verify_checksum: bool,
+  /// Maximum size of the metadata section in bytes.
+  pub max_size: Option<u32>,
+}
+
+impl MetadataReaderOptions {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn checksum_seed(mut self, seed: u32) -> Self {
+    self.checksum_seed = seed;
+    self
+  }
+
+  pub fn verify_checksum(mut self, verify: bool) -> Self {
+    self.verify_checksum = verify;
+    self
+  }
+
+  pub fn max_size(mut self, size: u32) -> Self {
+    self.max_size = Some(size);
+    self
+  }
+}
+
+/// Reads the metadata section of a bundle, dispatching on the header version.
+///
+/// Version 1 bundles without the section are read as empty metadata.
+pub struct MetadataReader<R: Read + Seek> {
+  r: R,
+  header: Header,
+  options: MetadataReaderOptions,
+}
+
+impl<R: Read + Seek> MetadataReader<R> {
+  pub fn new(r: R, header: Header) -> Self {
+    Self::new_with_options(r, header, Default::default())
+  }
+
+  pub fn new_with_options(r: R, header: Header, options: MetadataReaderOptions) -> Self {
+    Self { r, header, options }
+  }
+
+  /// Reads the size of the metadata section stored after the data section (version 1).
+  ///
+  /// Returns `None` if the bundle has no such section.
+  pub fn read_trailer_size(&mut self) -> crate::Result<Option<u32>> {
+    if self.header.version() != Version::V1 {
+      return Ok(None);
+    }
+    let file_len = self.r.seek(SeekFrom::End(0))?;
+    if file_len < self.header.index_end_offset() + BundleMetadata::TRAILER_LEN as u64 {
+      return Ok(None);
+    }
+    let (pos, mut buf) = read_footer();
+    self.r.seek(pos)?;
+    self.r.read_exact(&mut buf)?;
+    parse_footer(&self.header, file_len, &buf)
+  }
+
+  /// Reads the length in bytes of the metadata section stored after the data section.
+  ///
+  /// Returns 0 if there is no such section.
+  pub fn read_trailer_len(&mut self) -> crate::Result<u64> {
+    let size = self.read_trailer_size()?;
+    Ok(size.map_or(0, |x| x as u64 + BundleMetadata::TRAILER_LEN as u64))
+  }
+}
+
+impl<R: Read + Seek> Reader<BundleMetadata> for MetadataReader<R> {
+  fn read(&mut self) -> crate::Result<BundleMetadata> {
+    let (offset, size) = match self.header.version() {
+      Version::V1 => match self.read_trailer_size()? {
+        Some(size) => {
+          let file_len = self.r.seek(SeekFrom::End(0))?;
+          read_trailer_section(file_len, size)
+        }
+        None => return Ok(BundleMetadata::default()),
+      },
+      Version::V2 => read_header_section(&self.header),
+    };
+    let len = section_len(size, &self.options)?;
+    self.r.seek(SeekFrom::Start(offset))?;
+    let buf = read_len(&mut self.r, len)?;
+    parse_section(&buf, &self.options)
+  }
+}
+
+#[cfg(feature = "async")]
+pub struct AsyncMetadataReader<R: AsyncRead + AsyncSeek + Unpin> {
+  r: R,
+  header: Header,
+  options: MetadataReaderOptions,
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncRead + AsyncSeek + Unpin> AsyncMetadataReader<R> {
+  pub fn new(r: R, header: Header) -> Self {
+    Self::new_with_options(r, header, Default::default())
+  }
+
+  pub fn new_with_options(r: R, header: Header, options: MetadataReaderOptions) -> Self {
+    Self { r, header, options }
+  }
+
+  /// Reads the size of the metadata section stored after the data section (version 1).
+  ///
+  /// Returns `None` if the bundle has no such section.
+  pub async fn read_trailer_size(&mut self) -> crate::Result<Option<u32>> {
+    if self.header.version() != Version::V1 {
+      return Ok(None);
+    }
+    let file_len = self.r.seek(SeekFrom::End(0)).await?;
+    if file_len < self.header.index_end_offset() + BundleMetadata::TRAILER_LEN as u64 {
+      return Ok(None);
+    }
+    let (pos, mut buf) = read_footer();
+    self.r.seek(pos).await?;
+    self.r.read_exact(&mut buf).await?;
+    parse_footer(&self.header, file_len, &buf)
+  }
+
+  /// Reads the length in bytes of the metadata section stored after the data section.
+  ///
+  /// Returns 0 if there is no such section.
+  pub async fn read_trailer_len(&mut self) -> crate::Result<u64> {
+    let size = self.read_trailer_size().await?;
+    Ok(size.map_or(0, |x| x as u64 + BundleMetadata::TRAILER_LEN as u64))
+  }
+}
+
+#[cfg(feature = "async")]
+impl<R: AsyncRead + AsyncSeek + Unpin> AsyncReader<BundleMetadata> for AsyncMetadataReader<R> {
+  async fn read(&mut self) -> crate::Result<BundleMetadata> {
+    let (offset, size) = match self.header.version() {
+      Version::V1 => match self.read_trailer_size().await? {
+        Some(size) => {
+          let file_len = self.r.seek(SeekFrom::End(0)).await?;
+          read_trailer_section(file_len, size)
+        }
+        None => return Ok(BundleMetadata::default()),
+      },
+      Version::V2 => read_header_section(&self.header),
+    };
+    let len = section_len(size, &self.options)?;
+    self.r.seek(SeekFrom::Start(offset)).await?;
+    let buf = async_read_len(&mut self.r, len).await?;
+    parse_section(&buf, &self.options)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Cursor;
+
+  const V1_HEADER: Header = Header::new(Version::V1, 0);
+
+  fn bundle_like(metadata: &BundleMetadata) -> Vec<u8> {
+    let mut buf = vec![0u8; V1_HEADER.index_end_offset() as usize + 10];
+    MetadataWriter::new(&mut buf, Version::V1)
+      .write(metadata)
+      .unwrap();
+    buf
+  }
+
+  #[test]
+  fn accessors() {
+    let mut metadata = BundleMetadata::new();
+    metadata
+      .set_name("app")
+      .set_version("1.0.0")
+      .set_required_capabilities(["fs", "notification"])
+      .set_warmup(["/index.html", "/assets/app.js"]);
+    assert_eq!(metadata.name(), Some("app"));
+    assert_eq!(metadata.version(), Some("1.0.0"));
+    assert_eq!(
+      metadata.required_capabilities(),
+      vec!["fs".to_string(), "notification".to_string()]
+    );
+    assert_eq!(metadata.warmup(), vec!["/index.html", "/assets/app.js"]);
+    assert_eq!(metadata.git_commit(), None);
+  }
+
+  #[test]
+  fn read_and_write() {
+    let mut metadata = BundleMetadata::new();
+    metadata.set_name("app").set_git_commit("abc1234");
+    metadata.insert("custom", "value");
+    let buf = bundle_like(&metadata);
+    assert_eq!(&buf[buf.len() - BundleMetadata::MAGIC_LEN..], b"wvb-meta");
+    let options = MetadataReaderOptions::new().verify_checksum(true);
+    let mut reader = MetadataReader::new_with_options(Cursor::new(&buf), V1_HEADER, options);
+    assert_eq!(reader.read().unwrap(), metadata);
+    assert_eq!(
+      reader.read_trailer_len().unwrap(),
+      (buf.len() - V1_HEADER.index_end_offset() as usize - 10) as u64
+    );
+  }
+
+  #[test]
+  fn read_and_write_v2() {
+    let mut metadata = BundleMetadata::new();
+    metadata.set_name("app").set_version("1.0.0");
+    let size = metadata_size(Version::V2, &metadata).unwrap();
+    let header = Header::new(Version::V2, 0).with_metadata_size(size);
+    let mut buf = vec![0u8; header.end_offset() as usize];
+    let len = MetadataWriter::new(&mut buf, Version::V2)
+      .write(&metadata)
+      .unwrap();
+    assert_eq!(len, size as usize + CHECKSUM_LEN);
+    let options = MetadataReaderOptions::new().verify_checksum(true);
+    let mut reader = MetadataReader::new_with_options(Cursor::new(&buf), header, options);
+    assert_eq!(reader.read().unwrap(), metadata);
+    assert_eq!(reader.read_trailer_len().unwrap(), 0);
+  }
+
+  #[test]
+  fn empty() {
+    let buf = bundle_like(&BundleMetadata::new());
+    assert_eq!(buf.len(), V1_HEADER.index_end_offset() as usize + 10);
+    let mut reader = MetadataReader::new(Cursor::new(&buf), V1_HEADER);
+    assert!(reader.read().unwrap().is_empty());
+    assert_eq!(reader.read_trailer_len().unwrap(), 0);
+  }
+
+  #[test]
+  fn invalid_checksum() {
+    let mut metadata = BundleMetadata::new();
+    metadata.set_name("app");
+    let mut buf = bundle_like(&metadata);
+    let len = buf.len();
+    buf[len - BundleMetadata::TRAILER_LEN - 1] ^= 0xff;
+    let options = MetadataReaderOptions::new().verify_checksum(true);
+    let mut reader = MetadataReader::new_with_options(Cursor::new(&buf), V1_HEADER, options);
+    assert!(matches!(
+      reader.read(),
+      Err(crate::Error::InvalidMetadataChecksum)
+    ));
+  }
+
+  #[cfg(feature = "async")]
+  #[tokio::test]
+  async fn async_read_and_write() {
+    let mut metadata = BundleMetadata::new();
+    metadata.set_name("app").set_version("1.0.0");
+    let mut buf = vec![0u8; V1_HEADER.index_end_offset() as usize];
+    AsyncMetadataWriter::new(&mut buf, Version::V1)
+      .write(&metadata)
+      .await
+      .unwrap();
+    let mut reader = AsyncMetadataReader::new(Cursor::new(&buf), V1_HEADER);
+    assert_eq!(reader.read().await.unwrap(), metadata);
+  }
+}
@@ -0,0 +1,134 @@
+use crate::bundle::{Bundle, BundleReader, BundleWriter};
+use crate::reader::Reader;
+use crate::version::Version;
+use crate::writer::Writer;
+use std::io::{Read, Seek, Write};
+
+#[cfg(feature = "async")]
+use crate::bundle::{AsyncBundleReader, AsyncBundleWriter};
+#[cfg(feature = "async")]
+use crate::reader::AsyncReader;
+#[cfg(feature = "async")]
+use crate::writer::AsyncWriter;
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncSeek, AsyncWrite};
+
+/// Rewrites a bundle into the newest format version ([`Version::LATEST`]).
+///
+/// Returns the format version the bundle was read from. File contents, headers and metadata
+/// are kept as is, but the written bytes differ, so integrity hashes and signatures of the
+/// bundle file must be computed again.
+///
+/// # Example
+///
+/// ```no_run
+/// use std::fs::File;
+/// use wvb::migrate;
+///
+/// let input = File::open("app.wvb").unwrap();
+/// let output = File::create("app.v2.wvb").unwrap();
+/// let from = migrate(input, output).unwrap();
+/// println!("migrated from {from}");
+/// ```
+pub fn migrate<R: Read + Seek, W: Write>(r: R, w: W) -> crate::Result<Version> {
+  migrate_to(r, w, Version::LATEST)
+}
+
+/// Rewrites a bundle into the given format version.
+///
+/// Returns the format version the bundle was read from.
+pub fn migrate_to<R: Read + Seek, W: Write>(
+  r: R,
+  w: W,
+  version: Version,
+) -> crate::Result<Version> {
+  let bundle: Bundle = BundleReader::new(r).read()?;
+  BundleWriter::new_with_version(w, version).write(&bundle)?;
+  Ok(bundle.descriptor().header().version())
+}
+
+/// Asynchronously rewrites a bundle into the newest format version ([`Version::LATEST`]).
+///
+/// Returns the format version the bundle was read from.
+#[cfg(feature = "async")]
+pub async fn async_migrate<R, W>(r: R, w: W) -> crate::Result<Version>
+where
+  R: AsyncRead + AsyncSeek + Unpin,
+  W: AsyncWrite + Unpin,
+{
+  async_migrate_to(r, w, Version::LATEST).await
+}
+
+/// Asynchronously rewrites a bundle into the given format version.
+///
+/// Returns the format version the bundle was read from.
+#[cfg(feature = "async")]
+pub async fn async_migrate_to<R, W>(r: R, w: W, version: Version) -> crate::Result<Version>
+where
+  R: AsyncRead + AsyncSeek + Unpin,
+  W: AsyncWrite + Unpin,
+{
+  let bundle = AsyncReader::<Bundle>::read(&mut AsyncBundleReader::new(r)).await?;
+  AsyncBundleWriter::new_with_version(w, version)
+    .write(&bundle)
+    .await?;
+  Ok(bundle.descriptor().header().version())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{BundleDescriptor, BundleEntry};
+  use std::io::Cursor;
+
+  fn v1_bundle() -> Vec<u8> {
+    let mut builder = Bundle::builder();
+    builder.insert_entry(
+      "/index.html",
+      BundleEntry::new(b"<h1>Hello</h1>", "text/html", None),
+    );
+    builder.metadata_mut().set_name("app").set_version("1.0.0");
+    let bundle = builder.build().unwrap();
+    let mut data = vec![];
+    BundleWriter::new(Cursor::new(&mut data))
+      .write(&bundle)
+      .unwrap();
+    data
+  }
+
+  #[test]
+  fn migrate_v1_to_latest() {
+    let v1 = v1_bundle();
+    let mut v2 = vec![];
+    let from = migrate(Cursor::new(&v1), Cursor::new(&mut v2)).unwrap();
+    assert_eq!(from, Version::V1);
+
+    let descriptor: BundleDescriptor = BundleReader::new(Cursor::new(&v2)).read().unwrap();
+    assert_eq!(descriptor.header().version(), Version::LATEST);
+    assert_eq!(descriptor.metadata().name(), Some("app"));
+    let bundle: Bundle = BundleReader::new(Cursor::new(&v2)).read().unwrap();
+    assert_eq!(
+      bundle.get_data("/index.html").unwrap().unwrap(),
+      b"<h1>Hello</h1>"
+    );
+
+    // migrating back gives the original bytes
+    let mut back = vec![];
+    let from = migrate_to(Cursor::new(&v2), Cursor::new(&mut back), Version::V1).unwrap();
+    assert_eq!(from, Version::V2);
+    assert_eq!(back, v1);
+  }
+
+  #[cfg(feature = "async")]
+  #[tokio::test]
+  async fn async_migrate_v1_to_latest() {
+    let v1 = v1_bundle();
+    let mut v2 = vec![];
+    let from = async_migrate(Cursor::new(&v1), Cursor::new(&mut v2))
+      .await
+      .unwrap();
+    assert_eq!(from, Version::V1);
+    let descriptor: BundleDescriptor = BundleReader::new(Cursor::new(&v2)).read().unwrap();
+    assert_eq!(descriptor.header().version(), Version::V2);
+  }
+}
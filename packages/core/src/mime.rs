@@ -0,0 +1,102 @@
+//! Content type detection for files added to a bundle.
+//!
+//! Used by [`crate::BundleBuilder::add_dir`], and by bindings detecting the content type of
+//! entries inserted without one.
+
+use std::path::Path;
+
+const OCTET_STREAM: &str = "application/octet-stream";
+const TEXT_PLAIN: &str = "text/plain";
+
+/// Returns the content type of a web resource with the extension, if it is well known.
+///
+/// See [common MIME types](https://developer.mozilla.org/en-US/docs/Web/HTTP/Guides/MIME_types/Common_types).
+fn from_extension(ext: &str) -> Option<&'static str> {
+  let mime = match ext.to_ascii_lowercase().as_str() {
+    "html" | "htm" => "text/html",
+    "css" | "less" | "sass" | "styl" => "text/css",
+    "js" | "mjs" | "cjs" => "text/javascript",
+    "json" | "map" => "application/json",
+    "jsonld" => "application/ld+json",
+    "webmanifest" => "application/manifest+json",
+    "wasm" => "application/wasm",
+    "xml" => "application/xml",
+    "txt" => TEXT_PLAIN,
+    "csv" => "text/csv",
+    "md" => "text/markdown",
+    "svg" => "image/svg+xml",
+    "png" => "image/png",
+    "jpg" | "jpeg" => "image/jpeg",
+    "gif" => "image/gif",
+    "webp" => "image/webp",
+    "avif" => "image/avif",
+    "ico" => "image/vnd.microsoft.icon",
+    "woff" => "font/woff",
+    "woff2" => "font/woff2",
+    "ttf" => "font/ttf",
+    "otf" => "font/otf",
+    "mp3" => "audio/mpeg",
+    "wav" => "audio/wav",
+    "mp4" => "video/mp4",
+    "webm" => "video/webm",
+    "pdf" => "application/pdf",
+    "rtf" => "application/rtf",
+    "bin" => OCTET_STREAM,
+    _ => return None,
+  };
+  Some(mime)
+}
+
+/// Detects the content type of a file by sniffing its data, falling back to the extension
+/// of its path.
+///
+/// SVG files are never sniffed, and data sniffed as `text/plain` is refined by the
+/// extension, since text formats can't be told apart by their data. Files which are neither
+/// sniffed nor have a well known extension are `application/octet-stream`.
+///
+/// # Example
+///
+/// ```
+/// use wvb::mime::content_type;
+///
+/// assert_eq!(content_type("/assets/app.js", b""), "text/javascript");
+/// assert_eq!(content_type("/logo", b"\x89PNG\r\n\x1a\n"), "image/png");
+/// ```
+pub fn content_type(path: impl AsRef<Path>, data: &[u8]) -> String {
+  let ext = path.as_ref().extension().and_then(|x| x.to_str());
+  // SVG is XML, which sniffing can't tell apart
+  let sniffed = if ext.is_some_and(|x| x.eq_ignore_ascii_case("svg")) {
+    None
+  } else {
+    infer::get(data).map(|x| x.mime_type())
+  };
+  match sniffed {
+    Some(mime) if mime != TEXT_PLAIN => mime.to_string(),
+    _ => ext
+      .and_then(from_extension)
+      .unwrap_or(OCTET_STREAM)
+      .to_string(),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn detect() {
+    assert_eq!(content_type("app.js", b""), "text/javascript");
+    assert_eq!(content_type("/INDEX.HTML", b""), "text/html");
+    assert_eq!(content_type("styles/app.less", b""), "text/css");
+    // sniffed data takes precedence over extensions
+    assert_eq!(content_type("data.json", b"\x89PNG\r\n\x1a\n"), "image/png");
+    assert_eq!(content_type("logo", b"\x89PNG\r\n\x1a\n"), "image/png");
+    assert_eq!(content_type("logo.unknown", b"GIF89a"), "image/gif");
+    assert_eq!(
+      content_type("icon.svg", b"<?xml version=\"1.0\"?><svg></svg>"),
+      "image/svg+xml"
+    );
+    assert_eq!(content_type("LICENSE", b"MIT License"), OCTET_STREAM);
+    assert_eq!(content_type(".hidden", &[0xff, 0xfe, 0x00]), OCTET_STREAM);
+  }
+}
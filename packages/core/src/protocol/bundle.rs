@@ -1,21 +1,226 @@
+use crate::BundleDescriptor;
+use crate::protocol::cache::EntryCache;
+use crate::protocol::cors::{self, Cors};
+use crate::protocol::fallback::Fallback;
+use crate::protocol::header_rules::HeaderRules;
+use crate::protocol::stream::{entry_stream, once_stream};
 use crate::protocol::uri::{DefaultUriResolver, UriResolver};
+use crate::protocol::warmup::{self, Warmup};
 use crate::source::BundleSource;
 use async_trait::async_trait;
+use bytes::Bytes;
 use http::{HeaderValue, Method, Request, Response, StatusCode, header};
 use http_range::HttpRange;
+use std::collections::HashMap;
 use std::fmt::Formatter;
-use std::sync::Arc;
-use tokio::io::AsyncWriteExt;
+use std::io::SeekFrom;
+use std::sync::{Arc, Mutex, Weak};
+use tokio::io::AsyncSeekExt;
+
+/// Default maximum size of the ranges sent in a response.
+const DEFAULT_MAX_RANGE_SIZE: u64 = 1000 * 1024;
+
+/// Maximum decompressed size of the entries cached when streamed.
+const MAX_STREAM_CACHED_SIZE: u64 = 1024 * 1024;
+
+/// Builder for creating a [`BundleProtocol`].
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(feature = "protocol")]
+/// # {
+/// use wvb::protocol::{BundleProtocol, Fallback, PathUriResolver};
+/// use wvb::source::BundleSource;
+/// use std::sync::Arc;
+///
+/// let source = Arc::new(BundleSource::builder().builtin_dir("./bundles").build());
+/// // serves `bundle://localhost/app/index.html` from bundle "app"
+/// let protocol = BundleProtocol::builder(source)
+///     .uri_resolver(PathUriResolver)
+///     .fallback(Fallback::spa())
+///     .build();
+/// # }
+/// ```
+pub struct BundleProtocolBuilder {
+  source: Arc<BundleSource>,
+  uri_resolver: Box<dyn UriResolver + 'static>,
+  fallback: Option<Fallback>,
+  header_rules: Option<HeaderRules>,
+  overlays: HashMap<String, Vec<String>>,
+  cache: Option<Arc<EntryCache>>,
+  warmup: Option<Warmup>,
+  cors: Option<Cors>,
+  max_range_size: u64,
+}
+
+impl BundleProtocolBuilder {
+  pub fn new(source: Arc<BundleSource>) -> Self {
+    Self {
+      source,
+      uri_resolver: Box::new(DefaultUriResolver),
+      fallback: None,
+      header_rules: None,
+      overlays: HashMap::new(),
+      cache: None,
+      warmup: None,
+      cors: None,
+      max_range_size: DEFAULT_MAX_RANGE_SIZE,
+    }
+  }
+
+  /// Set how request URIs are resolved into bundle names and file paths.
+  ///
+  /// Defaults to [`DefaultUriResolver`], which takes the bundle name from the host.
+  #[must_use]
+  pub fn uri_resolver(mut self, resolver: impl UriResolver + 'static) -> Self {
+    self.uri_resolver = Box::new(resolver);
+    self
+  }
+
+  /// Set the routing fallback for paths not found in a bundle.
+  ///
+  /// Used for bundles which don't declare their own fallback in metadata.
+  ///
+  /// ```no_run
+  /// # #[cfg(feature = "protocol")]
+  /// # {
+  /// use wvb::protocol::{BundleProtocol, Fallback};
+  /// use wvb::source::BundleSource;
+  /// use std::sync::Arc;
+  ///
+  /// let source = Arc::new(BundleSource::builder().builtin_dir("./bundles").build());
+  /// let protocol = BundleProtocol::builder(source)
+  ///   .fallback(Fallback::spa())
+  ///   .build();
+  /// # }
+  /// ```
+  #[must_use]
+  pub fn fallback(mut self, fallback: Fallback) -> Self {
+    self.fallback = Some(fallback);
+    self
+  }
+
+  /// Set response header rules applied to every bundle.
+  ///
+  /// Rules declared in bundle metadata are applied after these, so a bundle can override
+  /// headers set by the app.
+  #[must_use]
+  pub fn header_rules(mut self, rules: HeaderRules) -> Self {
+    self.header_rules = Some(rules);
+    self
+  }
+
+  /// Overlay `overlay` on `bundle`, so entries are looked up in `overlay` first and fall back
+  /// to `bundle`.
+  ///
+  /// Overlays added later are looked up first. Overlays which are not installed in the source
+  /// are skipped, so a patch bundle can be released only when needed.
+  #[must_use]
+  pub fn overlay(mut self, bundle: impl Into<String>, overlay: impl Into<String>) -> Self {
+    let overlays = self.overlays.entry(bundle.into()).or_default();
+    overlays.insert(0, overlay.into());
+    self
+  }
+
+  /// Cache decompressed entries in memory, so repeated requests don't read and decompress
+  /// them again.
+  ///
+  /// Entries of a bundle are invalidated when its descriptor is unloaded from the source,
+  /// which happens when its version is switched. A cache can be shared by several protocols.
+  ///
+  /// With [`StreamProtocol::handle_stream`](super::StreamProtocol::handle_stream), only
+  /// entries up to 1 MiB are cached, and larger entries are served from the cache only when
+  /// already cached by [`Protocol::handle`](super::Protocol::handle) or warmup.
+  ///
+  /// ```no_run
+  /// # #[cfg(feature = "protocol")]
+  /// # {
+  /// use wvb::protocol::{BundleProtocol, EntryCache};
+  /// use wvb::source::BundleSource;
+  /// use std::sync::Arc;
+  ///
+  /// let source = Arc::new(BundleSource::builder().builtin_dir("./bundles").build());
+  /// let protocol = BundleProtocol::builder(source)
+  ///     .cache(EntryCache::new(32 * 1024 * 1024))
+  ///     .build();
+  /// let stats = protocol.cache().unwrap().stats();
+  /// println!("hits: {}, misses: {}", stats.hits, stats.misses);
+  /// # }
+  /// ```
+  #[must_use]
+  pub fn cache(mut self, cache: impl Into<Arc<EntryCache>>) -> Self {
+    self.cache = Some(cache.into());
+    self
+  }
+
+  /// Set the entries decompressed into the cache in the background when a bundle is loaded,
+  /// for bundles which don't declare their own warmup in metadata.
+  ///
+  /// Warmup requires a [`cache`](Self::cache).
+  #[must_use]
+  pub fn warmup(mut self, warmup: Warmup) -> Self {
+    self.warmup = Some(warmup);
+    self
+  }
+
+  /// Set the CORS policy, so pages of other schemes or hosts can fetch from bundles.
+  ///
+  /// Without a policy, responses carry no CORS headers besides those of header rules and
+  /// entries, and `OPTIONS` requests are answered with the allowed methods only.
+  #[must_use]
+  pub fn cors(mut self, cors: Cors) -> Self {
+    self.cors = Some(cors);
+    self
+  }
+
+  /// Set the maximum size in bytes of the ranges sent in a response to a range request,
+  /// which defaults to 1000 KiB.
+  ///
+  /// Ranges are cut to the limit in the requested order and ranges past it are left out, so
+  /// media players request the rest afterwards. Use `u64::MAX` to send ranges in full.
+  #[must_use]
+  pub fn max_range_size(mut self, size: u64) -> Self {
+    self.max_range_size = size.max(1);
+    self
+  }
+
+  pub fn build(self) -> BundleProtocol {
+    if let Some(cache) = &self.cache {
+      let cache = Arc::downgrade(cache);
+      self.source.on_unload_descriptor(move |bundle_name| {
+        if let Some(cache) = cache.upgrade() {
+          cache.invalidate(bundle_name);
+        }
+      });
+    }
+    BundleProtocol {
+      source: self.source,
+      uri_resolver: self.uri_resolver,
+      fallback: self.fallback,
+      header_rules: self.header_rules,
+      overlays: self.overlays,
+      cache: self.cache,
+      warmup: self.warmup,
+      cors: self.cors,
+      max_range_size: self.max_range_size,
+      settings: Mutex::default(),
+    }
+  }
+}
 
 /// Protocol handler for serving files from bundle sources.
 ///
 /// `BundleProtocol` implements the `Protocol` trait to serve web resources from
 /// `.wvb` bundle files stored in a `BundleSource`. It supports:
 ///
-/// - GET and HEAD HTTP methods
-/// - HTTP Range requests for streaming large files (video, audio)
+/// - GET and HEAD HTTP methods, and OPTIONS for CORS preflight requests (see [`Cors`])
+/// - HTTP Range requests for streaming large files (video, audio), including
+///   `multipart/byteranges` and `If-Range`
 /// - Content-Type and custom HTTP headers from bundle index
+/// - Response header rules matched on entry paths (see [`HeaderRules`])
 /// - Custom URI resolution for flexible URL-to-bundle mapping
+/// - Mounting bundles under path prefixes and overlaying patch bundles
 ///
 /// # URI Format
 ///
@@ -29,6 +234,10 @@ use tokio::io::AsyncWriteExt;
 /// - `bundle://app/index.html` → bundle "app", file "/index.html"
 /// - `app://myapp/assets/logo.png` → bundle "myapp", file "/assets/logo.png"
 ///
+/// Other layouts are supported with [`BundleProtocol::builder`] and a [`UriResolver`], such as
+/// [`PathUriResolver`](super::PathUriResolver), [`SingleBundleUriResolver`](super::SingleBundleUriResolver)
+/// and [`MappingUriResolver`](super::MappingUriResolver).
+///
 /// # Example
 ///
 /// ```no_run
@@ -78,9 +287,43 @@ use tokio::io::AsyncWriteExt;
 /// assert_eq!(response.status(), 206); // Partial Content
 /// # };
 /// ```
+///
+/// # Mounts and Overlays
+///
+/// An app split into a shell and feature bundles can mount each bundle under a path prefix
+/// with [`MountUriResolver`](super::MountUriResolver). Entries of a bundle can be patched
+/// without releasing it again by overlaying another bundle, which is looked up first:
+///
+/// ```no_run
+/// # #[cfg(feature = "protocol")]
+/// # {
+/// use wvb::protocol::{BundleProtocol, MountUriResolver};
+/// use wvb::source::BundleSource;
+/// use std::sync::Arc;
+///
+/// let source = Arc::new(BundleSource::builder().builtin_dir("./bundles").build());
+/// let protocol = BundleProtocol::builder(source)
+///     .uri_resolver(
+///         MountUriResolver::new()
+///             .mount("/", "shell")
+///             .mount("/features/billing", "billing"),
+///     )
+///     .overlay("shell", "shell-patch")
+///     .build();
+/// # }
+/// ```
 pub struct BundleProtocol {
   source: Arc<BundleSource>,
   uri_resolver: Box<dyn UriResolver + 'static>,
+  fallback: Option<Fallback>,
+  header_rules: Option<HeaderRules>,
+  overlays: HashMap<String, Vec<String>>,
+  cache: Option<Arc<EntryCache>>,
+  warmup: Option<Warmup>,
+  cors: Option<Cors>,
+  max_range_size: u64,
+  /// Settings of the loaded descriptor of each bundle.
+  settings: Mutex<HashMap<String, Arc<BundleSettings>>>,
 }
 
 impl std::fmt::Debug for BundleProtocol {
@@ -113,10 +356,42 @@ impl BundleProtocol {
   /// # }
   /// ```
   pub fn new(source: Arc<BundleSource>) -> Self {
-    Self {
-      source,
-      uri_resolver: Box::new(DefaultUriResolver),
-    }
+    BundleProtocolBuilder::new(source).build()
+  }
+
+  /// Creates a builder to customize URI resolution, overlays, the routing fallback, header
+  /// rules, caching and CORS.
+  pub fn builder(source: Arc<BundleSource>) -> BundleProtocolBuilder {
+    BundleProtocolBuilder::new(source)
+  }
+
+  /// Returns the entry cache, if caching is enabled.
+  pub fn cache(&self) -> Option<&EntryCache> {
+    self.cache.as_deref()
+  }
+
+  /// Decompresses the warmup entries of a bundle into the cache and waits until done.
+  ///
+  /// Bundles are warmed up in the background when first loaded, this allows warming up a
+  /// bundle ahead of its first request. Returns the number of entries added to the cache,
+  /// which is `0` when caching is disabled or the bundle declares no warmup.
+  pub async fn warmup(&self, bundle_name: &str) -> crate::Result<usize> {
+    let (version, descriptor) = self.source.load_versioned_descriptor(bundle_name).await?;
+    let settings = self.settings_of(bundle_name, &descriptor)?;
+    let (cache, warmup) = match self.warmup_of(&settings) {
+      Some(x) => x,
+      None => return Ok(0),
+    };
+    cache.start_warmup(bundle_name, &version);
+    warmup::warmup(
+      self.source.clone(),
+      cache,
+      bundle_name.to_string(),
+      version,
+      descriptor,
+      warmup,
+    )
+    .await
   }
 }
 
@@ -128,205 +403,612 @@ impl super::Protocol for BundleProtocol {
     err(level = "error")
   ))]
   async fn handle(&self, request: Request<Vec<u8>>) -> crate::Result<super::ProtocolResponse> {
-    let name = self
-      .uri_resolver
-      .resolve_bundle(request.uri())
-      .ok_or(crate::Error::BundleNotFound)?;
-    let path = self.uri_resolver.resolve_path(request.uri());
+    let (parts, body) = self.prepare(&request).await?.into_parts();
+    let body = match body {
+      Body::Empty => Vec::new(),
+      Body::Entry {
+        bundle,
+        version,
+        descriptor,
+        path,
+        range,
+      } => match self
+        .read_entry(&bundle, &version, &descriptor, &path)
+        .await?
+      {
+        Some(data) => match range {
+          Some((start, end)) => extract_buf(&data, start, end),
+          None => data.to_vec(),
+        },
+        None => return not_found(),
+      },
+      Body::Multipart {
+        bundle,
+        version,
+        descriptor,
+        path,
+        ranges,
+        boundary,
+      } => match self
+        .read_multipart(&bundle, &version, &descriptor, &path, &ranges, &boundary)
+        .await?
+      {
+        Some(data) => data,
+        None => return not_found(),
+      },
+    };
+    let response = Response::from_parts(parts, body.into());
+    trace_response(&response);
+    Ok(response)
+  }
+}
+
+#[async_trait]
+impl super::StreamProtocol for BundleProtocol {
+  #[cfg_attr(feature = "tracing", tracing::instrument(
+    skip_all,
+    fields(request.method = request.method().to_string(), request.uri = request.uri().to_string()),
+    err(level = "error")
+  ))]
+  async fn handle_stream(
+    &self,
+    request: Request<Vec<u8>>,
+  ) -> crate::Result<super::StreamProtocolResponse> {
+    let (parts, body) = self.prepare(&request).await?.into_parts();
+    let body = match body {
+      Body::Empty => once_stream(Vec::new()),
+      Body::Entry {
+        bundle,
+        version,
+        descriptor,
+        path,
+        range,
+      } => {
+        let entry = match descriptor.index().get_entry(&path) {
+          Some(x) => x,
+          None => return not_found().map(into_stream_response),
+        };
+        // small entries are read at once and cached, but large entries are streamed without
+        // being cached, so large media don't evict everything else
+        let cached = match &self.cache {
+          Some(_) if entry.content_length() <= MAX_STREAM_CACHED_SIZE => {
+            self
+              .read_entry(&bundle, &version, &descriptor, &path)
+              .await?
+          }
+          Some(cache) => cache.get(&bundle, &version, &path),
+          None => None,
+        };
+        if let Some(data) = cached {
+          let data = match range {
+            Some((start, end)) => data.slice(start as usize..=end as usize),
+            None => data,
+          };
+          let response = Response::from_parts(parts, once_stream(data));
+          trace_response(&response);
+          return Ok(response);
+        }
+        let mut reader = self.source.reader_for(&bundle, &version).await?;
+        reader
+          .seek(SeekFrom::Start(
+            descriptor.header().index_end_offset() + entry.offset(),
+          ))
+          .await?;
+        entry_stream(
+          reader,
+          entry.len(),
+          entry.content_length(),
+          range,
+          descriptor.verify_data,
+        )
+      }
+      // parts are few and small, so they are read at once
+      Body::Multipart {
+        bundle,
+        version,
+        descriptor,
+        path,
+        ranges,
+        boundary,
+      } => match self
+        .read_multipart(&bundle, &version, &descriptor, &path, &ranges, &boundary)
+        .await?
+      {
+        Some(data) => once_stream(data),
+        None => return not_found().map(into_stream_response),
+      },
+    };
+    let response = Response::from_parts(parts, body);
+    trace_response(&response);
+    Ok(response)
+  }
+}
 
-    #[cfg(feature = "tracing")]
-    tracing::info!(bundle_name = name, path = path);
+/// Body of a response, read once the headers are prepared.
+#[derive(Default)]
+enum Body {
+  #[default]
+  Empty,
+  /// The whole entry, or the bytes `start..=end` of it.
+  Entry {
+    bundle: String,
+    version: String,
+    descriptor: Arc<BundleDescriptor>,
+    path: String,
+    range: Option<(u64, u64)>,
+  },
+  /// `multipart/byteranges` of the given ranges of the entry.
+  Multipart {
+    bundle: String,
+    version: String,
+    descriptor: Arc<BundleDescriptor>,
+    path: String,
+    ranges: Vec<(u64, u64)>,
+    boundary: String,
+  },
+}
 
-    let response = self.handle_inner(&name, &path, request).await?;
+fn trace_response<T>(_response: &Response<T>) {
+  #[cfg(feature = "tracing")]
+  {
+    use crate::protocol::http_ext::HttpHeadersTracingInfo;
+    tracing::info!(
+      response.status = _response.status().as_u16(),
+      response.headers = _response.headers().tracing_info()
+    );
+  }
+}
 
-    #[cfg(feature = "tracing")]
-    {
-      use crate::protocol::http_ext::HttpHeadersTracingInfo;
-      tracing::info!(
-        response.status = response.status().as_u16(),
-        response.headers = response.headers().tracing_info()
-      );
-    }
+/// Settings a bundle declares in its metadata, parsed once per loaded descriptor.
+struct BundleSettings {
+  /// Descriptor the settings are parsed from.
+  descriptor: Weak<BundleDescriptor>,
+  header_rules: Option<HeaderRules>,
+  fallback: Option<Fallback>,
+  warmup: Option<Warmup>,
+}
 
-    Ok(response)
+impl BundleSettings {
+  fn from_descriptor(descriptor: &Arc<BundleDescriptor>) -> crate::Result<Self> {
+    let metadata = descriptor.metadata();
+    Ok(Self {
+      descriptor: Arc::downgrade(descriptor),
+      header_rules: HeaderRules::from_metadata(metadata)?,
+      fallback: Fallback::from_metadata(metadata),
+      warmup: Warmup::from_metadata(metadata),
+    })
   }
 }
 
+/// Bundle, or overlay of a bundle, in which entries are looked up.
+#[derive(Clone)]
+struct Layer {
+  bundle: String,
+  version: String,
+  descriptor: Arc<BundleDescriptor>,
+  settings: Arc<BundleSettings>,
+}
+
+/// Entry to serve, found in a bundle or one of its overlays.
+struct ResolvedEntry {
+  bundle: String,
+  version: String,
+  descriptor: Arc<BundleDescriptor>,
+  path: String,
+  status: StatusCode,
+  /// Header rules of the bundle, and of the overlay serving the entry.
+  header_rules: [Option<Arc<BundleSettings>>; 2],
+}
+
 impl BundleProtocol {
-  async fn handle_inner(
-    &self,
-    bundle_name: &str,
-    path: &str,
-    request: Request<Vec<u8>>,
-  ) -> crate::Result<super::ProtocolResponse> {
-    if !(request.method() == Method::GET || request.method() == Method::HEAD) {
+  async fn prepare(&self, request: &Request<Vec<u8>>) -> crate::Result<Response<Body>> {
+    // preflight requests don't depend on the bundle
+    if request.method() == Method::OPTIONS {
+      if let Some(cors) = &self.cors
+        && let Some(response) = cors.preflight(request.headers())?
+      {
+        return Ok(response);
+      }
       let response = Response::builder()
-        .status(StatusCode::METHOD_NOT_ALLOWED)
-        .body(Vec::new().into())?;
+        .status(StatusCode::NO_CONTENT)
+        .header(header::ALLOW, cors::ALLOW)
+        .body(Body::Empty)?;
       return Ok(response);
     }
 
-    let mut resp = Response::builder();
-    let descriptor = self.source.load_descriptor(bundle_name).await?;
+    // other methods are rejected before resolving the bundle, which may not exist
+    let mut response = if request.method() == Method::GET || request.method() == Method::HEAD {
+      let name = self
+        .uri_resolver
+        .resolve_bundle(request.uri())
+        .ok_or(crate::Error::BundleNotFound)?;
+      let path = self.uri_resolver.resolve_path(request.uri());
+      let route = self.uri_resolver.resolve_route(request.uri());
 
-    if let Some(entry) = descriptor.index().get_entry(&path) {
-      let resp_headers = resp.headers_mut().unwrap();
-      resp_headers.clone_from(entry.headers());
-      resp_headers.insert(
-        header::CONTENT_TYPE,
-        HeaderValue::from_str(entry.content_type()).unwrap(),
-      );
-      resp_headers.insert(
-        header::CONTENT_LENGTH,
-        HeaderValue::from(entry.content_length()),
-      );
+      #[cfg(feature = "tracing")]
+      tracing::info!(bundle_name = name, path = path);
 
-      if let Some(range_header) = request
-        .headers()
-        .get(header::RANGE)
-        .and_then(|x| x.to_str().map(|x| x.to_string()).ok())
-      {
-        resp_headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
-        resp_headers.insert(
-          header::ACCESS_CONTROL_EXPOSE_HEADERS,
-          HeaderValue::from_static("content-range"),
-        );
+      self.prepare_inner(&name, &path, &route, request).await?
+    } else {
+      Response::builder()
+        .status(StatusCode::METHOD_NOT_ALLOWED)
+        .header(header::ALLOW, cors::ALLOW)
+        .body(Body::Empty)?
+    };
+    if let Some(cors) = &self.cors {
+      cors.apply(request.headers(), response.headers_mut());
+    }
+    Ok(response)
+  }
 
-        let len = entry.content_length();
-        let not_satisfiable = || {
-          Response::builder()
-            .status(StatusCode::RANGE_NOT_SATISFIABLE)
-            .header(header::CONTENT_RANGE, format!("bytes */{len}"))
-            .body(Vec::new().into())
-            .map_err(Into::into)
-        };
+  /// Loads the descriptors of the bundle and its installed overlays, in lookup order.
+  async fn load_layers(&self, bundle_name: &str) -> crate::Result<Vec<Layer>> {
+    let (version, descriptor) = self.source.load_versioned_descriptor(bundle_name).await?;
+    let base = Layer {
+      bundle: bundle_name.to_string(),
+      version,
+      settings: self.settings_of(bundle_name, &descriptor)?,
+      descriptor,
+    };
+    self.spawn_warmup(&base);
+    let overlays = self.overlays.get(bundle_name).map(|x| x.as_slice());
+    let mut layers = Vec::with_capacity(overlays.map(|x| x.len()).unwrap_or(0) + 1);
+    for name in overlays.unwrap_or_default() {
+      match self.source.load_versioned_descriptor(name).await {
+        Ok((version, descriptor)) => {
+          let layer = Layer {
+            bundle: name.clone(),
+            version,
+            settings: self.settings_of(name, &descriptor)?,
+            descriptor,
+          };
+          self.spawn_warmup(&layer);
+          layers.push(layer);
+        }
+        Err(crate::Error::BundleNotFound) => {}
+        Err(e) => return Err(e),
+      }
+    }
+    layers.push(base);
+    Ok(layers)
+  }
+
+  /// Returns the settings of a loaded descriptor, which are parsed again only once the
+  /// source loads another descriptor for the bundle.
+  fn settings_of(
+    &self,
+    bundle_name: &str,
+    descriptor: &Arc<BundleDescriptor>,
+  ) -> crate::Result<Arc<BundleSettings>> {
+    let mut settings = self.settings.lock().unwrap();
+    // the weak reference keeps the allocation, so the address is not reused by another
+    // descriptor
+    if let Some(x) = settings.get(bundle_name)
+      && std::ptr::eq(x.descriptor.as_ptr(), Arc::as_ptr(descriptor))
+    {
+      return Ok(x.clone());
+    }
+    let x = Arc::new(BundleSettings::from_descriptor(descriptor)?);
+    settings.insert(bundle_name.to_string(), x.clone());
+    Ok(x)
+  }
+
+  /// Cache and warmup to use for a bundle, if both are available.
+  fn warmup_of(&self, settings: &BundleSettings) -> Option<(Arc<EntryCache>, Warmup)> {
+    let cache = self.cache.clone()?;
+    let warmup = settings.warmup.clone().or_else(|| self.warmup.clone())?;
+    Some((cache, warmup))
+  }
+
+  /// Starts warming up a bundle in the background, unless already started for its version.
+  fn spawn_warmup(&self, layer: &Layer) {
+    let (cache, warmup) = match self.warmup_of(&layer.settings) {
+      Some(x) => x,
+      None => return,
+    };
+    if !cache.start_warmup(&layer.bundle, &layer.version) {
+      return;
+    }
+    let source = self.source.clone();
+    let layer = layer.clone();
+    tokio::spawn(async move {
+      let _result = warmup::warmup(
+        source,
+        cache,
+        layer.bundle,
+        layer.version,
+        layer.descriptor,
+        warmup,
+      )
+      .await;
+      #[cfg(feature = "tracing")]
+      if let Err(e) = _result {
+        tracing::warn!(error = %e, "fail to warm up bundle entries");
+      }
+    });
+  }
+
+  /// Resolves the entry to serve and its status, applying overlays and the routing fallback.
+  ///
+  /// The fallback of the bundle itself is used, and its entries are looked up in overlays too.
+  async fn resolve_entry(
+    &self,
+    bundle_name: &str,
+    path: &str,
+    request_path: &str,
+  ) -> crate::Result<Option<ResolvedEntry>> {
+    let layers = self.load_layers(bundle_name).await?;
+    let base = &layers.last().unwrap().settings;
+    let find = |path: &str, status: StatusCode| {
+      layers
+        .iter()
+        .find(|x| x.descriptor.index().contains_path(path))
+        .map(|x| ResolvedEntry {
+          bundle: x.bundle.clone(),
+          version: x.version.clone(),
+          descriptor: x.descriptor.clone(),
+          path: path.to_string(),
+          status,
+          // rules of an overlay serving the entry apply on top of the rules of the bundle
+          header_rules: [
+            Some(base.clone()),
+            (x.bundle != bundle_name).then(|| x.settings.clone()),
+          ],
+        })
+    };
+    if let Some(x) = find(path, StatusCode::OK) {
+      return Ok(Some(x));
+    }
+    let fallback = match base.fallback.as_ref().or(self.fallback.as_ref()) {
+      Some(x) => x,
+      None => return Ok(None),
+    };
+    if let Some(entry) = fallback.resolve(request_path)
+      && let Some(x) = find(entry, StatusCode::OK)
+    {
+      return Ok(Some(x));
+    }
+    Ok(
+      fallback
+        .not_found_entry()
+        .and_then(|x| find(x, StatusCode::NOT_FOUND)),
+    )
+  }
+
+  /// Prepares the status and headers of the response, and which body to send.
+  async fn prepare_inner(
+    &self,
+    bundle_name: &str,
+    path: &str,
+    route: &str,
+    request: &Request<Vec<u8>>,
+  ) -> crate::Result<Response<Body>> {
+    let ResolvedEntry {
+      bundle,
+      version,
+      descriptor,
+      path,
+      status,
+      header_rules,
+    } = match self.resolve_entry(bundle_name, path, route).await? {
+      Some(x) => x,
+      None => return empty(StatusCode::NOT_FOUND),
+    };
+    let entry = match descriptor.index().get_entry(&path) {
+      Some(x) => x,
+      None => return empty(StatusCode::NOT_FOUND),
+    };
+    let mut resp = Response::builder().status(status);
+
+    let resp_headers = resp.headers_mut().unwrap();
+    let bundle_rules = header_rules
+      .iter()
+      .flatten()
+      .filter_map(|x| x.header_rules.as_ref());
+    for rules in self.header_rules.iter().chain(bundle_rules) {
+      rules.apply(&path, resp_headers);
+    }
+    for name in entry.headers().keys() {
+      resp_headers.remove(name);
+    }
+    for (name, value) in entry.headers() {
+      resp_headers.append(name, value.clone());
+    }
+    resp_headers.insert(
+      header::CONTENT_TYPE,
+      HeaderValue::from_str(entry.content_type()).unwrap(),
+    );
+    resp_headers.insert(
+      header::CONTENT_LENGTH,
+      HeaderValue::from(entry.content_length()),
+    );
+
+    resp_headers.insert(header::ACCEPT_RANGES, HeaderValue::from_static("bytes"));
 
-        let ranges = if let Ok(ranges) = HttpRange::parse(&range_header, len) {
-          ranges
-            .iter()
-            // map the output to spec range <start-end>, example: 0-499
-            .map(|x| (x.start, x.start + x.length - 1))
-            .collect::<Vec<_>>()
+    let is_head = request.method() == Method::HEAD;
+    // a range of a changed entry would be mixed with the copy of the client, so the whole
+    // entry is sent unless `If-Range` matches
+    let range_header = request
+      .headers()
+      .get(header::RANGE)
+      .filter(|_| status == StatusCode::OK && if_range_matches(request.headers(), resp_headers))
+      .and_then(|x| x.to_str().map(|x| x.to_string()).ok());
+    let range_header = match range_header {
+      Some(x) => x,
+      None => {
+        let body = if is_head {
+          Body::Empty
         } else {
-          return not_satisfiable();
+          Body::Entry {
+            bundle,
+            version,
+            descriptor: descriptor.clone(),
+            path,
+            range: None,
+          }
         };
+        return Ok(resp.body(body)?);
+      }
+    };
 
-        /// The Maximum bytes we send in one range
-        const MAX_LEN: u64 = 1000 * 1024;
-        let adjust_end =
-          |start: u64, end: u64, len: u64| start + (end - start).min(len - start).min(MAX_LEN - 1);
-
-        // single-part range header
-        let response = if ranges.len() == 1 {
-          let &(start, mut end) = ranges.first().unwrap();
-          // check if a range is not satisfiable
-          //
-          // this should be already taken care of by the range parsing library
-          // but checking here again for extra assurance
-          if start >= len || end >= len || end < start {
-            return not_satisfiable();
-          }
-          end = adjust_end(start, end, len);
-
-          resp_headers.insert(
-            header::CONTENT_RANGE,
-            HeaderValue::from_str(&format!("bytes {start}-{end}/{len}")).unwrap(),
-          );
-          resp_headers.insert(header::CONTENT_LENGTH, HeaderValue::from(end + 1 - start));
-          resp = resp.status(StatusCode::PARTIAL_CONTENT);
-
-          if request.method() == Method::HEAD {
-            resp.body(Vec::new().into())
-          } else {
-            let reader = self.source.reader(bundle_name).await?;
-            let buf = if let Some(data) = descriptor.async_get_data(reader, &path).await? {
-              extract_buf(&data, start, end)
-            } else {
-              return not_found();
-            };
-            resp.body(buf.into())
-          }
+    resp_headers.insert(
+      header::ACCESS_CONTROL_EXPOSE_HEADERS,
+      HeaderValue::from_static("content-range"),
+    );
+
+    let len = entry.content_length();
+    let not_satisfiable = || {
+      Response::builder()
+        .status(StatusCode::RANGE_NOT_SATISFIABLE)
+        .header(header::CONTENT_RANGE, format!("bytes */{len}"))
+        .body(Body::Empty)
+        .map_err(Into::into)
+    };
+
+    let ranges = if let Ok(ranges) = HttpRange::parse(&range_header, len) {
+      ranges
+        .iter()
+        // map the output to spec range <start-end>, example: 0-499
+        .map(|x| (x.start, x.start + x.length - 1))
+        .collect::<Vec<_>>()
+    } else {
+      return not_satisfiable();
+    };
+
+    // ranges are cut to the size limit of the response, in the requested order
+    let mut remaining = self.max_range_size;
+    let ranges = ranges
+      .into_iter()
+      // this should be already taken care of by the range parsing library
+      // but checking here again for extra assurance
+      .filter(|&(start, end)| start < len && end < len && start <= end)
+      .map_while(|(start, end)| {
+        if remaining == 0 {
+          return None;
+        }
+        let end = end.min(start.saturating_add(remaining - 1));
+        remaining -= end + 1 - start;
+        Some((start, end))
+      })
+      .collect::<Vec<_>>();
+    match ranges.as_slice() {
+      [] => not_satisfiable(),
+      &[(start, end)] => {
+        resp_headers.insert(
+          header::CONTENT_RANGE,
+          HeaderValue::from_str(&format!("bytes {start}-{end}/{len}")).unwrap(),
+        );
+        resp_headers.insert(header::CONTENT_LENGTH, HeaderValue::from(end + 1 - start));
+        let body = if is_head {
+          Body::Empty
         } else {
-          let ranges = ranges
-            .iter()
-            .filter_map(|&(start, mut end)| {
-              // filter out unsatisfiable ranges
-              //
-              // this should be already taken care of by the range parsing library
-              // but checking here again for extra assurance
-              if start >= len || end >= len || end < start {
-                None
-              } else {
-                // adjust end byte for MAX_LEN
-                end = adjust_end(start, end, len);
-                Some((start, end))
-              }
-            })
-            .collect::<Vec<_>>();
-
-          let boundary = random_boundary();
-          let boundary_sep = format!("\r\n--{boundary}\r\n");
-
-          resp_headers.insert(
-            header::CONTENT_TYPE,
-            HeaderValue::from_str(&format!("multipart/byteranges; boundary={boundary}")).unwrap(),
-          );
-          resp = resp.status(StatusCode::PARTIAL_CONTENT);
-
-          if request.method() == Method::HEAD {
-            resp.body(Vec::new().into())
-          } else {
-            let reader = self.source.reader(bundle_name).await?;
-            let buf = if let Some(data) = descriptor.async_get_data(reader, &path).await? {
-              let mut buf = Vec::new();
-              for (start, end) in ranges {
-                buf.write_all(boundary_sep.as_bytes()).await?;
-                buf
-                  .write_all(
-                    format!("{}: {}\r\n", header::CONTENT_TYPE, entry.content_type()).as_bytes(),
-                  )
-                  .await?;
-                buf
-                  .write_all(
-                    format!("{}: bytes {start}-{end}/{len}\r\n", header::CONTENT_RANGE).as_bytes(),
-                  )
-                  .await?;
-                buf.write_all("\r\n".as_bytes()).await?;
-
-                let range_buf = extract_buf(&data, start, end);
-                buf.extend_from_slice(&range_buf);
-              }
-              buf.write_all(boundary_sep.as_bytes()).await?;
-              buf
-            } else {
-              return not_found();
-            };
-            resp.body(buf.into())
+          Body::Entry {
+            bundle,
+            version,
+            descriptor: descriptor.clone(),
+            path,
+            range: Some((start, end)),
           }
-        }?;
-        return Ok(response);
+        };
+        Ok(resp.status(StatusCode::PARTIAL_CONTENT).body(body)?)
       }
-
-      if request.method() == Method::HEAD {
-        let response = resp.body(Vec::new().into())?;
-        return Ok(response);
+      _ => {
+        let boundary = random_boundary();
+        resp_headers.insert(
+          header::CONTENT_TYPE,
+          HeaderValue::from_str(&format!("multipart/byteranges; boundary={boundary}")).unwrap(),
+        );
+        let multipart_len = ranges
+          .iter()
+          .map(|&(start, end)| {
+            part_header(&boundary, entry.content_type(), start, end, len).len() as u64
+              + (end + 1 - start)
+              + 2
+          })
+          .sum::<u64>()
+          + multipart_end(&boundary).len() as u64;
+        resp_headers.insert(header::CONTENT_LENGTH, HeaderValue::from(multipart_len));
+        let body = if is_head {
+          Body::Empty
+        } else {
+          Body::Multipart {
+            bundle,
+            version,
+            descriptor: descriptor.clone(),
+            path,
+            ranges,
+            boundary,
+          }
+        };
+        Ok(resp.status(StatusCode::PARTIAL_CONTENT).body(body)?)
       }
+    }
+  }
 
-      let reader = self.source.reader(bundle_name).await?;
-      let data = if let Some(data) = descriptor.async_get_data(reader, &path).await? {
-        data
-      } else {
-        return not_found();
-      };
+  /// Reads the decompressed data of an entry, from the cache when enabled.
+  async fn read_entry(
+    &self,
+    bundle_name: &str,
+    version: &str,
+    descriptor: &BundleDescriptor,
+    path: &str,
+  ) -> crate::Result<Option<Bytes>> {
+    if let Some(data) = self
+      .cache
+      .as_ref()
+      .and_then(|x| x.get(bundle_name, version, path))
+    {
+      return Ok(Some(data));
+    }
+    let reader = self.source.reader_for(bundle_name, version).await?;
+    let data = match descriptor.async_get_data(reader, path).await? {
+      Some(x) => Bytes::from(x),
+      None => return Ok(None),
+    };
+    if let Some(cache) = &self.cache {
+      cache.insert(bundle_name, version, path, data.clone());
+    }
+    Ok(Some(data))
+  }
 
-      let response = resp.body(data.into())?;
-      Ok(response)
-    } else {
-      not_found()
+  async fn read_multipart(
+    &self,
+    bundle_name: &str,
+    version: &str,
+    descriptor: &BundleDescriptor,
+    path: &str,
+    ranges: &[(u64, u64)],
+    boundary: &str,
+  ) -> crate::Result<Option<Vec<u8>>> {
+    let entry = match descriptor.index().get_entry(path) {
+      Some(x) => x,
+      None => return Ok(None),
+    };
+    let data = match self
+      .read_entry(bundle_name, version, descriptor, path)
+      .await?
+    {
+      Some(x) => x,
+      None => return Ok(None),
+    };
+    let len = entry.content_length();
+    let mut buf = Vec::new();
+    for &(start, end) in ranges {
+      buf
+        .extend_from_slice(part_header(boundary, entry.content_type(), start, end, len).as_bytes());
+      buf.extend_from_slice(&extract_buf(&data, start, end));
+      buf.extend_from_slice(b"\r\n");
     }
+    buf.extend_from_slice(multipart_end(boundary).as_bytes());
+    Ok(Some(buf))
   }
 }
 
+fn empty(status: StatusCode) -> crate::Result<Response<Body>> {
+  let resp = Response::builder().status(status).body(Body::Empty)?;
+  Ok(resp)
+}
+
 fn not_found() -> crate::Result<super::ProtocolResponse> {
   let resp = Response::builder()
     .status(StatusCode::NOT_FOUND)
@@ -334,12 +1016,53 @@ fn not_found() -> crate::Result<super::ProtocolResponse> {
   Ok(resp)
 }
 
+fn into_stream_response(response: super::ProtocolResponse) -> super::StreamProtocolResponse {
+  response.map(|x| once_stream(x.into_owned()))
+}
+
+/// Returns the delimiter and headers of a part of a `multipart/byteranges` body.
+fn part_header(boundary: &str, content_type: &str, start: u64, end: u64, len: u64) -> String {
+  format!(
+    "--{boundary}\r\n{}: {content_type}\r\n{}: bytes {start}-{end}/{len}\r\n\r\n",
+    header::CONTENT_TYPE,
+    header::CONTENT_RANGE
+  )
+}
+
+/// Returns the close delimiter of a `multipart/byteranges` body.
+fn multipart_end(boundary: &str) -> String {
+  format!("--{boundary}--\r\n")
+}
+
+/// Whether the `If-Range` condition of a request holds, so its `Range` is honored.
+///
+/// Requests without `If-Range` always hold. Otherwise the value must exactly match the strong
+/// `ETag` or the `Last-Modified` of the response, which entries get from their headers or
+/// header rules.
+fn if_range_matches(request: &http::HeaderMap, response: &http::HeaderMap) -> bool {
+  let value = match request.get(header::IF_RANGE) {
+    Some(x) => x.as_bytes().trim_ascii(),
+    None => return true,
+  };
+  let validator = if value.starts_with(b"W/") {
+    // weak entity tags can't validate ranges
+    return false;
+  } else if value.starts_with(b"\"") {
+    header::ETAG
+  } else {
+    header::LAST_MODIFIED
+  };
+  response
+    .get(validator)
+    .is_some_and(|x| !x.as_bytes().starts_with(b"W/") && x.as_bytes().trim_ascii() == value)
+}
+
 fn random_boundary() -> String {
   let mut values = [0_u8; 30];
   getrandom::fill(&mut values).expect("failed to get random bytes");
   values[..]
     .iter()
-    .map(|&val| format!("{val:x}"))
+    .map(|&val| format!("{val:02x}"))
     .fold(String::new(), |mut acc, x| {
       acc.push_str(x.as_str());
       acc
@@ -365,7 +1088,8 @@ fn extract_buf(data: &[u8], start: u64, end: u64) -> Vec<u8> {
 mod tests {
   use super::*;
   use crate::protocol::Protocol;
-  use crate::testing::Fixtures;
+  use crate::testing::{Fixtures, MockBundle, MockSource};
+  use crate::{BundleEntry, BundleMetadata};
 
   #[tokio::test]
   async fn smoke() {
@@ -688,25 +1412,763 @@ mod tests {
   }
 
   #[tokio::test]
-  async fn not_allowed() {
-    let fixture = Fixtures::bundles();
-    let source = Arc::new(
-      BundleSource::builder()
-        .builtin_dir(fixture.get_path("builtin"))
-        .remote_dir(fixture.get_path("remote"))
-        .build(),
-    );
-    let protocol = Arc::new(BundleProtocol::new(source.clone()));
+  async fn multipart_ranges() {
+    let data = (0..100u8).collect::<Vec<_>>();
+    let mut entry_headers = http::HeaderMap::new();
+    entry_headers.insert(header::ETAG, "\"v1\"".parse().unwrap());
+    let mut mock = MockSource::new();
+    mock.add_builtin_bundle(MockBundle::new("app", "1.0.0").with_entry(
+      "/data.bin",
+      BundleEntry::new(&data, "application/octet-stream", Some(entry_headers)),
+    ));
+    let source = Arc::new(mock.get_source());
+    let request = |method: &str, headers: &[(&str, &str)]| {
+      let mut builder = Request::builder()
+        .uri("https://app.wvb/data.bin")
+        .method(method);
+      for (name, value) in headers {
+        builder = builder.header(*name, *value);
+      }
+      builder.body(vec![]).unwrap()
+    };
+
+    let protocol = BundleProtocol::new(source.clone());
     let resp = protocol
-      .handle(
-        Request::builder()
-          .uri("https://app.wvb/build.png")
-          .method("POST")
-          .body(vec![])
-          .unwrap(),
-      )
+      .handle(request("GET", &[("range", "bytes=0-3,10-11")]))
       .await
       .unwrap();
-    assert_eq!(resp.status(), 405);
+    assert_eq!(resp.status(), 206);
+    let content_type = resp.headers().get(header::CONTENT_TYPE).unwrap();
+    let boundary = content_type
+      .to_str()
+      .unwrap()
+      .strip_prefix("multipart/byteranges; boundary=")
+      .unwrap();
+    let mut expected = format!(
+      "--{boundary}\r\ncontent-type: application/octet-stream\r\ncontent-range: bytes 0-3/100\r\n\r\n"
+    )
+    .into_bytes();
+    expected.extend_from_slice(&data[0..=3]);
+    expected.extend_from_slice(
+      format!(
+        "\r\n--{boundary}\r\ncontent-type: application/octet-stream\r\ncontent-range: bytes 10-11/100\r\n\r\n"
+      )
+      .as_bytes(),
+    );
+    expected.extend_from_slice(&data[10..=11]);
+    expected.extend_from_slice(format!("\r\n--{boundary}--\r\n").as_bytes());
+    assert_eq!(resp.body().to_vec(), expected);
+    assert_eq!(
+      resp.headers().get(header::CONTENT_LENGTH).unwrap(),
+      &expected.len().to_string()
+    );
+
+    let resp = protocol
+      .handle(request("HEAD", &[("range", "bytes=0-3,10-11")]))
+      .await
+      .unwrap();
+    assert_eq!(
+      resp.headers().get(header::CONTENT_LENGTH).unwrap(),
+      &expected.len().to_string()
+    );
+
+    // ranges are honored only when `If-Range` matches
+    let resp = protocol
+      .handle(request(
+        "GET",
+        &[("range", "bytes=0-3"), ("if-range", "\"v1\"")],
+      ))
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 206);
+    for if_range in ["\"v0\"", "W/\"v1\"", "Wed, 21 Oct 2015 07:28:00 GMT"] {
+      let resp = protocol
+        .handle(request(
+          "GET",
+          &[("range", "bytes=0-3"), ("if-range", if_range)],
+        ))
+        .await
+        .unwrap();
+      assert_eq!(resp.status(), 200);
+      assert_eq!(resp.body().to_vec(), data);
+    }
+
+    // ranges are cut to the size limit, leaving out ranges past it
+    let protocol = BundleProtocol::builder(source).max_range_size(12).build();
+    let resp = protocol
+      .handle(request("GET", &[("range", "bytes=0-9,20-29,40-49")]))
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 206);
+    let body = resp.body().to_vec();
+    assert!(String::from_utf8_lossy(&body).contains("content-range: bytes 20-21/100\r\n"));
+    assert!(!String::from_utf8_lossy(&body).contains("bytes 40-"));
+    let resp = protocol
+      .handle(request("GET", &[("range", "bytes=50-99")]))
+      .await
+      .unwrap();
+    assert_eq!(
+      resp.headers().get(header::CONTENT_RANGE).unwrap(),
+      "bytes 50-61/100"
+    );
+    assert_eq!(resp.body().to_vec(), data[50..=61]);
+  }
+
+  #[tokio::test]
+  async fn not_allowed() {
+    let fixture = Fixtures::bundles();
+    let source = Arc::new(
+      BundleSource::builder()
+        .builtin_dir(fixture.get_path("builtin"))
+        .remote_dir(fixture.get_path("remote"))
+        .build(),
+    );
+    let protocol = Arc::new(BundleProtocol::new(source.clone()));
+    let resp = protocol
+      .handle(
+        Request::builder()
+          .uri("https://app.wvb/build.png")
+          .method("POST")
+          .body(vec![])
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 405);
+    assert_eq!(
+      resp.headers().get(header::ALLOW).unwrap(),
+      "GET, HEAD, OPTIONS"
+    );
+
+    // the method is checked before the bundle is resolved
+    let resp = protocol
+      .handle(
+        Request::builder()
+          .uri("https://unknown.wvb/index.html")
+          .method("POST")
+          .body(vec![])
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 405);
+  }
+
+  #[tokio::test]
+  async fn cors() {
+    let mut mock = MockSource::new();
+    mock.add_builtin_bundle(MockBundle::new("app", "1.0.0").with_entry(
+      "/data.json",
+      BundleEntry::new(b"{}", "application/json", None),
+    ));
+    let source = Arc::new(mock.get_source());
+    let request = |method: &str, headers: &[(&str, &str)]| {
+      let mut builder = Request::builder()
+        .uri("https://app.wvb/data.json")
+        .method(method);
+      for (name, value) in headers {
+        builder = builder.header(*name, *value);
+      }
+      builder.body(vec![]).unwrap()
+    };
+
+    // without a policy, OPTIONS only answers the allowed methods
+    let protocol = BundleProtocol::new(source.clone());
+    let resp = protocol.handle(request("OPTIONS", &[])).await.unwrap();
+    assert_eq!(resp.status(), 204);
+    assert_eq!(
+      resp.headers().get(header::ALLOW).unwrap(),
+      "GET, HEAD, OPTIONS"
+    );
+    let resp = protocol
+      .handle(request("GET", &[("origin", "tauri://localhost")]))
+      .await
+      .unwrap();
+    assert!(
+      !resp
+        .headers()
+        .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+    );
+
+    let protocol = BundleProtocol::builder(source)
+      .cors(
+        Cors::new()
+          .allow_scheme("tauri")
+          .allow_header(header::AUTHORIZATION)
+          .expose_header(header::ETAG)
+          .max_age(600),
+      )
+      .build();
+    let resp = protocol
+      .handle(request(
+        "OPTIONS",
+        &[
+          ("origin", "tauri://localhost"),
+          ("access-control-request-method", "GET"),
+          ("access-control-request-headers", "authorization"),
+        ],
+      ))
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 204);
+    let headers = resp.headers();
+    assert_eq!(
+      headers.get(header::ACCESS_CONTROL_ALLOW_ORIGIN).unwrap(),
+      "tauri://localhost"
+    );
+    assert_eq!(
+      headers.get(header::ACCESS_CONTROL_ALLOW_METHODS).unwrap(),
+      "GET, HEAD"
+    );
+    assert_eq!(
+      headers.get(header::ACCESS_CONTROL_ALLOW_HEADERS).unwrap(),
+      "authorization"
+    );
+    assert_eq!(headers.get(header::ACCESS_CONTROL_MAX_AGE).unwrap(), "600");
+    assert_eq!(headers.get(header::VARY).unwrap(), "origin");
+
+    for headers in [
+      vec![
+        ("origin", "https://example.com"),
+        ("access-control-request-method", "GET"),
+      ],
+      vec![
+        ("origin", "tauri://localhost"),
+        ("access-control-request-method", "PUT"),
+      ],
+      vec![
+        ("origin", "tauri://localhost"),
+        ("access-control-request-method", "GET"),
+        ("access-control-request-headers", "x-custom"),
+      ],
+    ] {
+      let resp = protocol.handle(request("OPTIONS", &headers)).await.unwrap();
+      assert_eq!(resp.status(), 403);
+      assert!(
+        !resp
+          .headers()
+          .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+      );
+    }
+
+    let resp = protocol
+      .handle(request(
+        "GET",
+        &[("origin", "tauri://localhost"), ("range", "bytes=0-0")],
+      ))
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 206);
+    assert_eq!(
+      resp
+        .headers()
+        .get(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+        .unwrap(),
+      "tauri://localhost"
+    );
+    assert_eq!(
+      resp
+        .headers()
+        .get(header::ACCESS_CONTROL_EXPOSE_HEADERS)
+        .unwrap(),
+      "content-range, etag"
+    );
+
+    // errors are readable too
+    let resp = protocol
+      .handle(
+        Request::builder()
+          .uri("https://app.wvb/missing.json")
+          .header("origin", "tauri://localhost")
+          .body(vec![])
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 404);
+    assert!(
+      resp
+        .headers()
+        .contains_key(header::ACCESS_CONTROL_ALLOW_ORIGIN)
+    );
+  }
+
+  fn get(uri: &str) -> Request<Vec<u8>> {
+    Request::builder()
+      .uri(uri)
+      .method("GET")
+      .body(vec![])
+      .unwrap()
+  }
+
+  #[tokio::test]
+  async fn fallback() {
+    let mut metadata = BundleMetadata::new();
+    metadata.set_fallback("spa").set_not_found("/404.html");
+    let mut mock = MockSource::new();
+    mock
+      .add_builtin_bundle(
+        MockBundle::new("app", "1.0.0")
+          .with_bundle_metadata(metadata)
+          .with_entry("/index.html", BundleEntry::new(b"index", "text/html", None))
+          .with_entry("/404.html", BundleEntry::new(b"404", "text/html", None)),
+      )
+      .add_builtin_bundle(
+        MockBundle::new("docs", "1.0.0")
+          .with_entry("/index.html", BundleEntry::new(b"docs", "text/html", None)),
+      )
+      .set_builtin_current_version("app", "1.0.0")
+      .set_builtin_current_version("docs", "1.0.0");
+    let protocol = BundleProtocol::new(Arc::new(mock.get_source()));
+
+    let resp = protocol
+      .handle(get("https://app.wvb/settings/profile"))
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.body().as_ref(), b"index");
+    let resp = protocol
+      .handle(get("https://app.wvb/missing.js"))
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 404);
+    assert_eq!(resp.body().as_ref(), b"404");
+
+    // bundle without fallback metadata uses the fallback of the protocol
+    let resp = protocol
+      .handle(get("https://docs.wvb/guide"))
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 404);
+    let protocol = BundleProtocol::builder(Arc::new(mock.get_source()))
+      .fallback(Fallback::spa())
+      .build();
+    let resp = protocol
+      .handle(get("https://docs.wvb/guide"))
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.body().as_ref(), b"docs");
+  }
+
+  #[tokio::test]
+  async fn uri_resolver() {
+    let mut mock = MockSource::new();
+    mock
+      .add_builtin_bundle(
+        MockBundle::new("app", "1.0.0")
+          .with_entry("/index.html", BundleEntry::new(b"index", "text/html", None)),
+      )
+      .set_builtin_current_version("app", "1.0.0");
+    let source = Arc::new(mock.get_source());
+
+    let protocol = BundleProtocol::builder(source.clone())
+      .uri_resolver(crate::protocol::PathUriResolver)
+      .fallback(Fallback::spa())
+      .build();
+    let resp = protocol
+      .handle(get("bundle://localhost/app/index.html"))
+      .await
+      .unwrap();
+    assert_eq!(resp.body().as_ref(), b"index");
+    // fallback rules match the route inside the bundle
+    let resp = protocol
+      .handle(get("bundle://localhost/app/settings"))
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.body().as_ref(), b"index");
+
+    let protocol = BundleProtocol::builder(source.clone())
+      .uri_resolver(crate::protocol::SingleBundleUriResolver::new("app"))
+      .build();
+    let resp = protocol.handle(get("bundle://localhost/")).await.unwrap();
+    assert_eq!(resp.body().as_ref(), b"index");
+
+    let protocol = BundleProtocol::builder(source)
+      .uri_resolver(crate::protocol::MappingUriResolver::new([(
+        "example.com".to_string(),
+        "app".to_string(),
+      )]))
+      .build();
+    let resp = protocol
+      .handle(get("https://example.com/index.html"))
+      .await
+      .unwrap();
+    assert_eq!(resp.body().as_ref(), b"index");
+    let err = protocol
+      .handle(get("https://other.com/index.html"))
+      .await
+      .unwrap_err();
+    assert!(matches!(err, crate::Error::BundleNotFound));
+  }
+
+  #[tokio::test]
+  async fn mounts_and_overlays() {
+    let mut mock = MockSource::new();
+    mock
+      .add_builtin_bundle(
+        MockBundle::new("shell", "1.0.0")
+          .with_entry("/index.html", BundleEntry::new(b"shell", "text/html", None))
+          .with_entry("/app.js", BundleEntry::new(b"app", "text/javascript", None)),
+      )
+      .set_builtin_current_version("shell", "1.0.0")
+      .add_builtin_bundle(MockBundle::new("shell-patch", "1.0.0").with_entry(
+        "/app.js",
+        BundleEntry::new(b"patched", "text/javascript", None),
+      ))
+      .set_builtin_current_version("shell-patch", "1.0.0")
+      .add_builtin_bundle(MockBundle::new("billing", "1.0.0").with_entry(
+        "/index.html",
+        BundleEntry::new(b"billing", "text/html", None),
+      ))
+      .set_builtin_current_version("billing", "1.0.0");
+    let protocol = BundleProtocol::builder(Arc::new(mock.get_source()))
+      .uri_resolver(
+        crate::protocol::MountUriResolver::new()
+          .mount("/", "shell")
+          .mount("/features/billing", "billing"),
+      )
+      .overlay("shell", "shell-patch")
+      .overlay("billing", "billing-patch")
+      .fallback(Fallback::spa())
+      .build();
+
+    let resp = protocol.handle(get("bundle://localhost/")).await.unwrap();
+    assert_eq!(resp.body().as_ref(), b"shell");
+    let resp = protocol
+      .handle(get("bundle://localhost/app.js"))
+      .await
+      .unwrap();
+    assert_eq!(resp.body().as_ref(), b"patched");
+    // routes of a mounted bundle fall back to its own entry
+    let resp = protocol
+      .handle(get("bundle://localhost/features/billing/invoices"))
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(resp.body().as_ref(), b"billing");
+    // overlays which are not installed are skipped
+    let resp = protocol
+      .handle(get("bundle://localhost/features/billing/index.html"))
+      .await
+      .unwrap();
+    assert_eq!(resp.body().as_ref(), b"billing");
+    let resp = protocol
+      .handle(get("bundle://localhost/features/billing/app.js"))
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 404);
+  }
+
+  #[tokio::test]
+  async fn cache() {
+    use crate::protocol::{EntryCache, EntryCacheStats, StreamProtocol, collect_stream};
+
+    let mut mock = MockSource::new();
+    mock
+      .add_builtin_bundle(
+        MockBundle::new("app", "1.0.0")
+          .with_entry("/index.html", BundleEntry::new(b"index", "text/html", None)),
+      )
+      .set_builtin_current_version("app", "1.0.0");
+    let source = Arc::new(mock.get_source());
+    let protocol = BundleProtocol::builder(source.clone())
+      .cache(EntryCache::new(1024))
+      .build();
+
+    for _ in 0..3 {
+      let resp = protocol
+        .handle(get("https://app.wvb/index.html"))
+        .await
+        .unwrap();
+      assert_eq!(resp.body().as_ref(), b"index");
+    }
+    let resp = protocol
+      .handle_stream(
+        Request::builder()
+          .uri("https://app.wvb/index.html")
+          .header(header::RANGE, "bytes=1-2")
+          .body(vec![])
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(collect_stream(resp.into_body()).await.unwrap(), b"nd");
+    assert_eq!(
+      protocol.cache().unwrap().stats(),
+      EntryCacheStats {
+        hits: 3,
+        misses: 1,
+        entries: 1,
+        size: 5,
+      }
+    );
+
+    source.unload_descriptor("app");
+    assert_eq!(protocol.cache().unwrap().stats().entries, 0);
+
+    // streamed entries are cached too
+    let resp = protocol
+      .handle_stream(get("https://app.wvb/index.html"))
+      .await
+      .unwrap();
+    assert_eq!(collect_stream(resp.into_body()).await.unwrap(), b"index");
+    let resp = protocol
+      .handle_stream(get("https://app.wvb/index.html"))
+      .await
+      .unwrap();
+    assert_eq!(collect_stream(resp.into_body()).await.unwrap(), b"index");
+    assert_eq!(
+      protocol.cache().unwrap().stats(),
+      EntryCacheStats {
+        hits: 4,
+        misses: 2,
+        entries: 1,
+        size: 5,
+      }
+    );
+  }
+
+  #[tokio::test]
+  async fn warmup() {
+    use crate::protocol::{EntryCache, Warmup};
+
+    let html = br#"<html><head><script type="module" src="/assets/app.js"></script><link rel="stylesheet" href="assets/app.css"></head></html>"#;
+    let mut mock = MockSource::new();
+    mock
+      .add_builtin_bundle(
+        MockBundle::new("app", "1.0.0")
+          .with_entry("/index.html", BundleEntry::new(html, "text/html", None))
+          .with_entry(
+            "/assets/app.js",
+            BundleEntry::new(b"app", "text/javascript", None),
+          )
+          .with_entry(
+            "/assets/app.css",
+            BundleEntry::new(b"css", "text/css", None),
+          )
+          .with_entry(
+            "/assets/lazy.js",
+            BundleEntry::new(b"lazy", "text/javascript", None),
+          ),
+      )
+      .set_builtin_current_version("app", "1.0.0");
+    let source = Arc::new(mock.get_source());
+    let protocol = BundleProtocol::builder(source.clone())
+      .cache(EntryCache::new(1024))
+      .warmup(Warmup::index_html())
+      .build();
+
+    assert_eq!(protocol.warmup("app").await.unwrap(), 3);
+    assert_eq!(protocol.warmup("app").await.unwrap(), 0);
+    let resp = protocol
+      .handle(get("https://app.wvb/assets/app.css"))
+      .await
+      .unwrap();
+    assert_eq!(resp.body().as_ref(), b"css");
+    let stats = protocol.cache().unwrap().stats();
+    assert_eq!((stats.hits, stats.misses, stats.entries), (1, 0, 3));
+
+    // warmed up in the background when loaded again
+    source.unload_descriptor("app");
+    protocol
+      .handle(get("https://app.wvb/assets/lazy.js"))
+      .await
+      .unwrap();
+    for _ in 0..100 {
+      if protocol.cache().unwrap().stats().entries == 4 {
+        return;
+      }
+      tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+    }
+    panic!("bundle is not warmed up");
+  }
+
+  #[tokio::test]
+  async fn header_rules() {
+    let mut metadata = BundleMetadata::new();
+    metadata.set_headers(
+      "/assets/**\n  Cache-Control: public, max-age=31536000, immutable\n/**/*.html\n  Content-Security-Policy: default-src 'self'\n",
+    );
+    let mut entry_headers = http::HeaderMap::new();
+    entry_headers.insert(header::CACHE_CONTROL, "no-store".parse().unwrap());
+    let mut mock = MockSource::new();
+    mock
+      .add_builtin_bundle(
+        MockBundle::new("app", "1.0.0")
+          .with_bundle_metadata(metadata)
+          .with_entry("/index.html", BundleEntry::new(b"index", "text/html", None))
+          .with_entry(
+            "/assets/app.js",
+            BundleEntry::new(b"app", "text/javascript", None),
+          )
+          .with_entry(
+            "/assets/sw.js",
+            BundleEntry::new(b"sw", "text/javascript", Some(entry_headers)),
+          ),
+      )
+      .set_builtin_current_version("app", "1.0.0");
+    let rules = HeaderRules::new()
+      .rule("/**", [("Cross-Origin-Opener-Policy", "same-origin")])
+      .unwrap()
+      .rule("/**/*.html", [("Content-Security-Policy", "default-src *")])
+      .unwrap();
+    let protocol = BundleProtocol::builder(Arc::new(mock.get_source()))
+      .header_rules(rules)
+      .build();
+
+    let resp = protocol
+      .handle(get("https://app.wvb/index.html"))
+      .await
+      .unwrap();
+    // bundle rules override the rules of the protocol
+    assert_eq!(
+      resp.headers().get("content-security-policy").unwrap(),
+      "default-src 'self'"
+    );
+    assert_eq!(
+      resp.headers().get("cross-origin-opener-policy").unwrap(),
+      "same-origin"
+    );
+    assert!(resp.headers().get(header::CACHE_CONTROL).is_none());
+
+    let resp = protocol
+      .handle(get("https://app.wvb/assets/app.js"))
+      .await
+      .unwrap();
+    assert_eq!(
+      resp.headers().get(header::CACHE_CONTROL).unwrap(),
+      "public, max-age=31536000, immutable"
+    );
+    assert!(resp.headers().get("content-security-policy").is_none());
+
+    // entry headers take precedence over rules
+    let resp = protocol
+      .handle(get("https://app.wvb/assets/sw.js"))
+      .await
+      .unwrap();
+    assert_eq!(
+      resp.headers().get(header::CACHE_CONTROL).unwrap(),
+      "no-store"
+    );
+
+    // metadata is parsed again only for another descriptor
+    let descriptor = protocol.source.load_descriptor("app").await.unwrap();
+    let settings = protocol.settings_of("app", &descriptor).unwrap();
+    assert!(Arc::ptr_eq(
+      &settings,
+      &protocol.settings_of("app", &descriptor).unwrap()
+    ));
+    protocol.source.unload_descriptor("app");
+    let descriptor = protocol.source.load_descriptor("app").await.unwrap();
+    assert!(!Arc::ptr_eq(
+      &settings,
+      &protocol.settings_of("app", &descriptor).unwrap()
+    ));
+  }
+
+  #[tokio::test]
+  async fn verify_data() {
+    use crate::VerifyOptions;
+    use crate::protocol::{StreamProtocol, collect_stream};
+
+    let mut mock = MockSource::new();
+    mock
+      .add_builtin_bundle(
+        MockBundle::new("app", "1.0.0")
+          .with_entry("/index.html", BundleEntry::new(b"index", "text/html", None)),
+      )
+      .set_builtin_current_version("app", "1.0.0");
+    let source = mock.get_source();
+    // corrupts the checksum of the entry, leaving its data intact
+    let descriptor = source.fetch_descriptor("app").await.unwrap();
+    let entry = descriptor.index().get_entry("/index.html").unwrap();
+    let offset = descriptor.header().index_end_offset() + entry.offset() + entry.len();
+    let filepath = source.filepath("app").await.unwrap();
+    let mut file = std::fs::read(&filepath).unwrap();
+    file[offset as usize] ^= 0xff;
+    std::fs::write(&filepath, file).unwrap();
+
+    let protocol = BundleProtocol::new(Arc::new(source));
+    let resp = protocol
+      .handle(get("https://app.wvb/index.html"))
+      .await
+      .unwrap();
+    assert_eq!(resp.body().as_ref(), b"index");
+
+    let source = BundleSource::builder()
+      .builtin_dir(mock.get_source().builtin_dir())
+      .verify_data(VerifyOptions::new())
+      .build();
+    let protocol = BundleProtocol::new(Arc::new(source));
+    let err = protocol
+      .handle(get("https://app.wvb/index.html"))
+      .await
+      .unwrap_err();
+    assert!(matches!(err, crate::Error::ChecksumMismatch));
+    let resp = protocol
+      .handle_stream(get("https://app.wvb/index.html"))
+      .await
+      .unwrap();
+    assert!(matches!(
+      collect_stream(resp.into_body()).await,
+      Err(crate::Error::ChecksumMismatch)
+    ));
+  }
+
+  #[tokio::test]
+  async fn stream() {
+    use crate::protocol::{StreamProtocol, collect_stream};
+    use futures_util::StreamExt;
+
+    let data = (0..300_000u32)
+      .map(|x| (x / 5) as u8 ^ (x % 11) as u8)
+      .collect::<Vec<_>>();
+    let mut mock = MockSource::new();
+    mock
+      .add_builtin_bundle(
+        MockBundle::new("app", "1.0.0")
+          .with_entry("/video.mp4", BundleEntry::new(&data, "video/mp4", None)),
+      )
+      .set_builtin_current_version("app", "1.0.0");
+    let protocol = BundleProtocol::new(Arc::new(mock.get_source()));
+
+    let resp = protocol
+      .handle_stream(get("https://app.wvb/video.mp4"))
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_eq!(
+      resp.headers().get(header::CONTENT_LENGTH).unwrap(),
+      "300000"
+    );
+    let chunks = resp.into_body().collect::<Vec<_>>().await;
+    assert!(chunks.len() > 1);
+    let body = chunks
+      .into_iter()
+      .flat_map(|x| x.unwrap().to_vec())
+      .collect::<Vec<_>>();
+    assert_eq!(body, data);
+
+    let resp = protocol
+      .handle_stream(
+        Request::builder()
+          .uri("https://app.wvb/video.mp4")
+          .header(header::RANGE, "bytes=100000-100099")
+          .body(vec![])
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 206);
+    let body = collect_stream(resp.into_body()).await.unwrap();
+    assert_eq!(body, data[100_000..100_100]);
+
+    let resp = protocol
+      .handle_stream(get("https://app.wvb/missing.mp4"))
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 404);
   }
 }
@@ -0,0 +1,238 @@
+use bytes::Bytes;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Bundle name, version and entry path.
+type Key = (String, String, String);
+
+/// Statistics of an [`EntryCache`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EntryCacheStats {
+  /// Number of lookups served from the cache.
+  pub hits: u64,
+  /// Number of lookups not found in the cache.
+  pub misses: u64,
+  /// Number of cached entries.
+  pub entries: usize,
+  /// Total size of cached entries in bytes.
+  pub size: usize,
+}
+
+#[derive(Default)]
+struct Lru {
+  entries: HashMap<Key, (Bytes, u64)>,
+  /// Keys by last access, least recently used first.
+  order: BTreeMap<u64, Key>,
+  tick: u64,
+  size: usize,
+}
+
+impl Lru {
+  fn touch(&mut self, key: &Key) -> Option<Bytes> {
+    let tick = self.tick + 1;
+    let (data, last) = self.entries.get_mut(key)?;
+    let key = self.order.remove(last).unwrap();
+    *last = tick;
+    let data = data.clone();
+    self.order.insert(tick, key);
+    self.tick = tick;
+    Some(data)
+  }
+
+  fn remove(&mut self, key: &Key) {
+    if let Some((data, last)) = self.entries.remove(key) {
+      self.order.remove(&last);
+      self.size -= data.len();
+    }
+  }
+}
+
+/// Size-bounded LRU cache of decompressed entries.
+///
+/// Entries are keyed by bundle name, version and path, so a new version of a bundle is never
+/// served from entries of the previous one. Entries larger than the cache are not cached.
+///
+/// Used by [`super::BundleProtocol`], see [`super::BundleProtocolBuilder::cache`].
+pub struct EntryCache {
+  max_size: usize,
+  lru: Mutex<Lru>,
+  /// Bundle versions whose warmup is started.
+  warmed: Mutex<HashSet<(String, String)>>,
+  hits: AtomicU64,
+  misses: AtomicU64,
+}
+
+impl std::fmt::Debug for EntryCache {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("EntryCache")
+      .field("max_size", &self.max_size)
+      .field("stats", &self.stats())
+      .finish()
+  }
+}
+
+impl EntryCache {
+  /// Creates a cache holding up to `max_size` bytes of decompressed entries.
+  pub fn new(max_size: usize) -> Self {
+    Self {
+      max_size,
+      lru: Mutex::default(),
+      warmed: Mutex::default(),
+      hits: AtomicU64::new(0),
+      misses: AtomicU64::new(0),
+    }
+  }
+
+  pub fn max_size(&self) -> usize {
+    self.max_size
+  }
+
+  pub fn stats(&self) -> EntryCacheStats {
+    let lru = self.lru.lock().unwrap();
+    EntryCacheStats {
+      hits: self.hits.load(Ordering::Relaxed),
+      misses: self.misses.load(Ordering::Relaxed),
+      entries: lru.entries.len(),
+      size: lru.size,
+    }
+  }
+
+  /// Removes every cached entry of a bundle.
+  pub fn invalidate(&self, bundle_name: &str) {
+    let mut lru = self.lru.lock().unwrap();
+    let keys = lru
+      .entries
+      .keys()
+      .filter(|(name, _, _)| name == bundle_name)
+      .cloned()
+      .collect::<Vec<_>>();
+    for key in &keys {
+      lru.remove(key);
+    }
+    self
+      .warmed
+      .lock()
+      .unwrap()
+      .retain(|(name, _)| name != bundle_name);
+  }
+
+  /// Removes every cached entry.
+  pub fn clear(&self) {
+    *self.lru.lock().unwrap() = Lru::default();
+    self.warmed.lock().unwrap().clear();
+  }
+
+  /// Marks the warmup of a bundle version as started, returning `false` if it already was.
+  pub(crate) fn start_warmup(&self, bundle_name: &str, version: &str) -> bool {
+    self
+      .warmed
+      .lock()
+      .unwrap()
+      .insert((bundle_name.to_string(), version.to_string()))
+  }
+
+  /// Returns a cached entry without counting the lookup or marking the entry as used.
+  pub(crate) fn peek(&self, bundle_name: &str, version: &str, path: &str) -> Option<Bytes> {
+    let key = (
+      bundle_name.to_string(),
+      version.to_string(),
+      path.to_string(),
+    );
+    self
+      .lru
+      .lock()
+      .unwrap()
+      .entries
+      .get(&key)
+      .map(|(data, _)| data.clone())
+  }
+
+  pub(crate) fn get(&self, bundle_name: &str, version: &str, path: &str) -> Option<Bytes> {
+    let key = (
+      bundle_name.to_string(),
+      version.to_string(),
+      path.to_string(),
+    );
+    let data = self.lru.lock().unwrap().touch(&key);
+    let counter = if data.is_some() {
+      &self.hits
+    } else {
+      &self.misses
+    };
+    counter.fetch_add(1, Ordering::Relaxed);
+    data
+  }
+
+  pub(crate) fn insert(&self, bundle_name: &str, version: &str, path: &str, data: Bytes) {
+    if data.len() > self.max_size {
+      return;
+    }
+    let key = (
+      bundle_name.to_string(),
+      version.to_string(),
+      path.to_string(),
+    );
+    let mut lru = self.lru.lock().unwrap();
+    lru.remove(&key);
+    while lru.size + data.len() > self.max_size {
+      let (_, oldest) = lru.order.pop_first().unwrap();
+      let (evicted, _) = lru.entries.remove(&oldest).unwrap();
+      lru.size -= evicted.len();
+    }
+    lru.tick += 1;
+    let tick = lru.tick;
+    lru.size += data.len();
+    lru.order.insert(tick, key.clone());
+    lru.entries.insert(key, (data, tick));
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn data(len: usize) -> Bytes {
+    Bytes::from(vec![0; len])
+  }
+
+  #[test]
+  fn evict_least_recently_used() {
+    let cache = EntryCache::new(100);
+    cache.insert("app", "1.0.0", "/a.js", data(40));
+    cache.insert("app", "1.0.0", "/b.js", data(40));
+    assert!(cache.get("app", "1.0.0", "/a.js").is_some());
+    cache.insert("app", "1.0.0", "/c.js", data(40));
+    assert!(cache.get("app", "1.0.0", "/b.js").is_none());
+    assert!(cache.get("app", "1.0.0", "/a.js").is_some());
+    assert!(cache.get("app", "1.0.0", "/c.js").is_some());
+    assert!(cache.get("app", "1.1.0", "/c.js").is_none());
+    assert_eq!(
+      cache.stats(),
+      EntryCacheStats {
+        hits: 3,
+        misses: 2,
+        entries: 2,
+        size: 80,
+      }
+    );
+
+    // too large to be cached
+    cache.insert("app", "1.0.0", "/video.mp4", data(101));
+    assert_eq!(cache.stats().entries, 2);
+  }
+
+  #[test]
+  fn invalidate() {
+    let cache = EntryCache::new(100);
+    cache.insert("app", "1.0.0", "/a.js", data(10));
+    cache.insert("app", "1.1.0", "/a.js", data(10));
+    cache.insert("other", "1.0.0", "/a.js", data(10));
+    cache.invalidate("app");
+    assert_eq!(cache.stats().entries, 1);
+    assert_eq!(cache.stats().size, 10);
+    assert!(cache.get("other", "1.0.0", "/a.js").is_some());
+    cache.clear();
+    assert_eq!(cache.stats().entries, 0);
+  }
+}
@@ -0,0 +1,331 @@
+use http::{HeaderMap, HeaderName, HeaderValue, Method, Response, StatusCode, header};
+
+/// Methods served by [`super::BundleProtocol`], sent in the `Allow` header.
+pub(crate) const ALLOW: &str = "GET, HEAD, OPTIONS";
+
+/// Cross-origin resource sharing policy of a protocol.
+///
+/// Pages served from another scheme or host (such as `tauri://localhost` fetching from
+/// `bundle://app`) can only read responses carrying `Access-Control-Allow-Origin`, and
+/// requests with custom headers are preceded by an `OPTIONS` preflight request. Requests from
+/// allowed origins get the CORS headers, and preflight requests from other origins, or for
+/// methods and headers which are not allowed, are rejected with status 403.
+///
+/// Origins are allowed by exact match (e.g. `https://tauri.localhost`), by scheme (e.g. every
+/// `tauri://` origin) or all at once with `*`. The `null` origin of sandboxed pages is only
+/// allowed by an exact `null` or `*`.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "protocol")]
+/// # {
+/// use http::header;
+/// use wvb::protocol::Cors;
+///
+/// let cors = Cors::new()
+///   .allow_scheme("tauri")
+///   .allow_origin("http://tauri.localhost")
+///   .allow_header(header::AUTHORIZATION)
+///   .max_age(600);
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cors {
+  origins: Vec<String>,
+  schemes: Vec<String>,
+  methods: Vec<Method>,
+  headers: Vec<HeaderName>,
+  any_header: bool,
+  expose_headers: Vec<HeaderName>,
+  credentials: bool,
+  max_age: Option<u64>,
+}
+
+impl Default for Cors {
+  fn default() -> Self {
+    Self {
+      origins: vec![],
+      schemes: vec![],
+      methods: vec![Method::GET, Method::HEAD],
+      headers: vec![],
+      any_header: false,
+      expose_headers: vec![],
+      credentials: false,
+      max_age: None,
+    }
+  }
+}
+
+impl Cors {
+  /// Policy allowing no origin, and `GET` and `HEAD` requests once origins are allowed.
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Policy allowing every origin and request header.
+  pub fn permissive() -> Self {
+    Self::new().allow_origin("*").allow_any_header()
+  }
+
+  /// Allow an origin such as `https://tauri.localhost`, or every origin with `*`.
+  pub fn allow_origin(mut self, origin: impl Into<String>) -> Self {
+    self
+      .origins
+      .push(origin.into().trim_end_matches('/').to_string());
+    self
+  }
+
+  /// Allow every origin with the scheme, such as `tauri`.
+  pub fn allow_scheme(mut self, scheme: impl Into<String>) -> Self {
+    let scheme = scheme.into();
+    self.schemes.push(
+      scheme
+        .trim_end_matches("://")
+        .trim_end_matches(':')
+        .to_string(),
+    );
+    self
+  }
+
+  /// Set the methods allowed by preflight requests, which defaults to `GET` and `HEAD`.
+  ///
+  /// Bundles only serve `GET` and `HEAD` requests, so other methods are ignored: allowing them
+  /// would let pages send requests which are answered with status 405.
+  pub fn allow_methods(mut self, methods: impl IntoIterator<Item = Method>) -> Self {
+    self.methods = methods
+      .into_iter()
+      .filter(|x| x == Method::GET || x == Method::HEAD)
+      .collect();
+    self
+  }
+
+  /// Allow a request header in preflight requests.
+  ///
+  /// [CORS-safelisted request headers](https://fetch.spec.whatwg.org/#cors-safelisted-request-header)
+  /// such as `Accept` don't need to be allowed.
+  pub fn allow_header(mut self, name: HeaderName) -> Self {
+    self.headers.push(name);
+    self
+  }
+
+  /// Allow every request header in preflight requests.
+  pub fn allow_any_header(mut self) -> Self {
+    self.any_header = true;
+    self
+  }
+
+  /// Expose a response header to scripts, in addition to the safelisted ones.
+  pub fn expose_header(mut self, name: HeaderName) -> Self {
+    self.expose_headers.push(name);
+    self
+  }
+
+  /// Allow requests with credentials, such as cookies.
+  ///
+  /// Allowed origins are then always sent back as is, since browsers reject `*` with
+  /// credentials.
+  pub fn allow_credentials(mut self, allow: bool) -> Self {
+    self.credentials = allow;
+    self
+  }
+
+  /// Set how long in seconds the result of a preflight request can be cached.
+  pub fn max_age(mut self, seconds: u64) -> Self {
+    self.max_age = Some(seconds);
+    self
+  }
+
+  /// Returns the `Access-Control-Allow-Origin` value for a request origin, if allowed.
+  fn allow_origin_value(&self, origin: &HeaderValue) -> Option<HeaderValue> {
+    let any = self.origins.iter().any(|x| x == "*");
+    if any && !self.credentials {
+      return Some(HeaderValue::from_static("*"));
+    }
+    let value = origin.to_str().ok()?;
+    let allowed = any
+      || self.origins.iter().any(|x| x == value)
+      || value
+        .split_once("://")
+        .is_some_and(|(scheme, _)| self.schemes.iter().any(|x| x.eq_ignore_ascii_case(scheme)));
+    allowed.then(|| origin.clone())
+  }
+
+  fn allows_method(&self, method: &HeaderValue) -> bool {
+    self
+      .methods
+      .iter()
+      .any(|x| x.as_str().as_bytes() == method.as_bytes())
+  }
+
+  fn allows_headers(&self, headers: &HeaderValue) -> bool {
+    if self.any_header {
+      return true;
+    }
+    let headers = match headers.to_str() {
+      Ok(x) => x,
+      Err(_) => return false,
+    };
+    headers
+      .split(',')
+      .map(|x| x.trim())
+      .filter(|x| !x.is_empty())
+      .all(|x| {
+        self
+          .headers
+          .iter()
+          .any(|y| y.as_str().eq_ignore_ascii_case(x))
+      })
+  }
+
+  fn set_allow_origin(&self, value: HeaderValue, headers: &mut HeaderMap) {
+    if value != "*" {
+      headers.append(header::VARY, HeaderValue::from_static("origin"));
+    }
+    headers.insert(header::ACCESS_CONTROL_ALLOW_ORIGIN, value);
+    if self.credentials {
+      headers.insert(
+        header::ACCESS_CONTROL_ALLOW_CREDENTIALS,
+        HeaderValue::from_static("true"),
+      );
+    }
+  }
+
+  /// Adds CORS headers to the response of a request from an allowed origin.
+  pub(crate) fn apply(&self, request: &HeaderMap, headers: &mut HeaderMap) {
+    let value = match request
+      .get(header::ORIGIN)
+      .and_then(|x| self.allow_origin_value(x))
+    {
+      Some(x) => x,
+      None => return,
+    };
+    self.set_allow_origin(value, headers);
+    if !self.expose_headers.is_empty() {
+      // keeps headers already exposed by the protocol, such as `Content-Range`
+      let exposed = headers
+        .get(header::ACCESS_CONTROL_EXPOSE_HEADERS)
+        .and_then(|x| x.to_str().ok())
+        .into_iter()
+        .map(|x| x.to_string())
+        .chain(self.expose_headers.iter().map(|x| x.to_string()))
+        .collect::<Vec<_>>()
+        .join(", ");
+      if let Ok(value) = HeaderValue::from_str(&exposed) {
+        headers.insert(header::ACCESS_CONTROL_EXPOSE_HEADERS, value);
+      }
+    }
+  }
+
+  /// Responds to a preflight request, or returns `None` when it's not a preflight request.
+  pub(crate) fn preflight<T: Default>(
+    &self,
+    request: &HeaderMap,
+  ) -> crate::Result<Option<Response<T>>> {
+    let (origin, method) = match (
+      request.get(header::ORIGIN),
+      request.get(header::ACCESS_CONTROL_REQUEST_METHOD),
+    ) {
+      (Some(origin), Some(method)) => (origin, method),
+      _ => return Ok(None),
+    };
+    let request_headers = request.get(header::ACCESS_CONTROL_REQUEST_HEADERS);
+    let value = match self.allow_origin_value(origin) {
+      Some(x)
+        if self.allows_method(method) && request_headers.is_none_or(|x| self.allows_headers(x)) =>
+      {
+        x
+      }
+      _ => {
+        let response = Response::builder()
+          .status(StatusCode::FORBIDDEN)
+          .header(header::VARY, "origin")
+          .body(T::default())?;
+        return Ok(Some(response));
+      }
+    };
+    let mut response = Response::builder()
+      .status(StatusCode::NO_CONTENT)
+      .header(header::ALLOW, ALLOW)
+      .body(T::default())?;
+    let headers = response.headers_mut();
+    self.set_allow_origin(value, headers);
+    let methods = self
+      .methods
+      .iter()
+      .map(|x| x.as_str())
+      .collect::<Vec<_>>()
+      .join(", ");
+    if let Ok(value) = HeaderValue::from_str(&methods) {
+      headers.insert(header::ACCESS_CONTROL_ALLOW_METHODS, value);
+    }
+    let allow_headers = match request_headers {
+      // echo requested headers, since `*` is not honored with credentials
+      Some(x) if self.any_header => Some(x.clone()),
+      _ if self.headers.is_empty() => None,
+      _ => HeaderValue::from_str(
+        &self
+          .headers
+          .iter()
+          .map(|x| x.as_str())
+          .collect::<Vec<_>>()
+          .join(", "),
+      )
+      .ok(),
+    };
+    if let Some(value) = allow_headers {
+      headers.insert(header::ACCESS_CONTROL_ALLOW_HEADERS, value);
+    }
+    if let Some(max_age) = self.max_age {
+      headers.insert(header::ACCESS_CONTROL_MAX_AGE, HeaderValue::from(max_age));
+    }
+    Ok(Some(response))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn value(value: &'static str) -> HeaderValue {
+    HeaderValue::from_static(value)
+  }
+
+  #[test]
+  fn allow_origin() {
+    let cors = Cors::new()
+      .allow_scheme("tauri://")
+      .allow_origin("https://app.example.com/");
+    assert_eq!(
+      cors.allow_origin_value(&value("tauri://localhost")),
+      Some(value("tauri://localhost"))
+    );
+    assert_eq!(
+      cors.allow_origin_value(&value("https://app.example.com")),
+      Some(value("https://app.example.com"))
+    );
+    assert_eq!(cors.allow_origin_value(&value("https://example.com")), None);
+    assert_eq!(cors.allow_origin_value(&value("null")), None);
+
+    let cors = Cors::permissive();
+    assert_eq!(cors.allow_origin_value(&value("null")), Some(value("*")));
+    let cors = cors.allow_credentials(true);
+    assert_eq!(
+      cors.allow_origin_value(&value("https://example.com")),
+      Some(value("https://example.com"))
+    );
+  }
+
+  #[test]
+  fn allow_headers() {
+    let cors = Cors::new().allow_header(header::AUTHORIZATION);
+    assert!(cors.allows_headers(&value("Authorization")));
+    assert!(!cors.allows_headers(&value("authorization, x-custom")));
+    assert!(cors.allows_method(&value("GET")));
+    assert!(!cors.allows_method(&value("POST")));
+    // only methods served by bundles are allowed
+    let cors = Cors::new().allow_methods([Method::GET, Method::POST]);
+    assert!(cors.allows_method(&value("GET")));
+    assert!(!cors.allows_method(&value("POST")));
+  }
+}
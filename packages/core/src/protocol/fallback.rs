@@ -0,0 +1,178 @@
+use crate::BundleMetadata;
+
+/// Rule serving an entry for navigation requests under a path prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FallbackRule {
+  pub prefix: String,
+  pub entry: String,
+}
+
+impl FallbackRule {
+  pub fn new(prefix: impl Into<String>, entry: impl Into<String>) -> Self {
+    Self {
+      prefix: prefix.into(),
+      entry: entry.into(),
+    }
+  }
+
+  fn matches(&self, path: &str) -> bool {
+    let prefix = self.prefix.trim_end_matches('/');
+    match path.strip_prefix(prefix) {
+      Some(rest) => rest.is_empty() || rest.starts_with('/'),
+      None => false,
+    }
+  }
+}
+
+/// Routing fallback for paths which are not found in a bundle.
+///
+/// Navigation requests (paths whose last segment has no extension, such as `/settings/profile`)
+/// are served the entry of the longest matching rule, which lets client-side routed apps
+/// (SPA) handle them. Other misses are served the not found entry with status 404, if any.
+///
+/// Bundles can declare their own fallback in metadata with [`BundleMetadata::FALLBACK`] and
+/// [`BundleMetadata::NOT_FOUND`], which takes precedence over the fallback of the protocol.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "protocol")]
+/// # {
+/// use wvb::protocol::Fallback;
+///
+/// let fallback = Fallback::new()
+///   .rule("/admin", "/admin/index.html")
+///   .rule("/", "/index.html")
+///   .not_found("/404.html");
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Fallback {
+  rules: Vec<FallbackRule>,
+  not_found: Option<String>,
+}
+
+impl Fallback {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Fallback serving `/index.html` for every navigation request.
+  pub fn spa() -> Self {
+    Self::new().rule("/", "/index.html")
+  }
+
+  /// Add a rule serving `entry` for navigation requests under `prefix`.
+  pub fn rule(mut self, prefix: impl Into<String>, entry: impl Into<String>) -> Self {
+    self.rules.push(FallbackRule::new(prefix, entry));
+    // longest prefix wins
+    self.rules.sort_by(|a, b| {
+      b.prefix
+        .trim_end_matches('/')
+        .len()
+        .cmp(&a.prefix.trim_end_matches('/').len())
+    });
+    self
+  }
+
+  /// Set the entry served with status 404 when nothing else matches.
+  pub fn not_found(mut self, entry: impl Into<String>) -> Self {
+    self.not_found = Some(entry.into());
+    self
+  }
+
+  pub fn rules(&self) -> &[FallbackRule] {
+    &self.rules
+  }
+
+  pub fn not_found_entry(&self) -> Option<&str> {
+    self.not_found.as_deref()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.rules.is_empty() && self.not_found.is_none()
+  }
+
+  /// Reads the fallback declared in bundle metadata.
+  ///
+  /// Returns `None` when the metadata declares neither a fallback nor a not found entry.
+  pub fn from_metadata(metadata: &BundleMetadata) -> Option<Self> {
+    let mut fallback = Self::new();
+    if let Some(value) = metadata.fallback() {
+      for rule in value.split(',').map(|x| x.trim()).filter(|x| !x.is_empty()) {
+        fallback = match rule.split_once('=') {
+          Some((prefix, entry)) => fallback.rule(prefix.trim(), entry.trim()),
+          None if rule.eq_ignore_ascii_case("spa") => fallback.rule("/", "/index.html"),
+          None => fallback.rule("/", rule),
+        };
+      }
+    }
+    if let Some(entry) = metadata.not_found() {
+      fallback = fallback.not_found(entry);
+    }
+    if fallback.is_empty() {
+      None
+    } else {
+      Some(fallback)
+    }
+  }
+
+  /// Returns the entry to serve for a navigation request of the given request path.
+  pub(crate) fn resolve(&self, request_path: &str) -> Option<&str> {
+    if !is_navigation(request_path) {
+      return None;
+    }
+    self
+      .rules
+      .iter()
+      .find(|x| x.matches(request_path))
+      .map(|x| x.entry.as_str())
+  }
+}
+
+/// Whether the path looks like a page navigation rather than an asset request.
+fn is_navigation(path: &str) -> bool {
+  match path.rsplit('/').next() {
+    Some(last) => !last.contains('.'),
+    None => true,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn resolve() {
+    let fallback = Fallback::new()
+      .rule("/", "/index.html")
+      .rule("/admin/", "/admin/index.html");
+    assert_eq!(fallback.resolve("/settings/profile"), Some("/index.html"));
+    assert_eq!(fallback.resolve("/admin"), Some("/admin/index.html"));
+    assert_eq!(fallback.resolve("/admin/users"), Some("/admin/index.html"));
+    assert_eq!(fallback.resolve("/administrator"), Some("/index.html"));
+    assert_eq!(fallback.resolve("/assets/app.js"), None);
+  }
+
+  #[test]
+  fn from_metadata() {
+    let mut metadata = BundleMetadata::new();
+    assert_eq!(Fallback::from_metadata(&metadata), None);
+
+    metadata.set_fallback("spa");
+    assert_eq!(Fallback::from_metadata(&metadata), Some(Fallback::spa()));
+
+    metadata
+      .set_fallback("/docs=/docs/index.html, /=/app.html")
+      .set_not_found("/404.html");
+    assert_eq!(
+      Fallback::from_metadata(&metadata),
+      Some(
+        Fallback::new()
+          .rule("/", "/app.html")
+          .rule("/docs", "/docs/index.html")
+          .not_found("/404.html")
+      )
+    );
+  }
+}
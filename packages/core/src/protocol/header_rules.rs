@@ -0,0 +1,306 @@
+use crate::BundleMetadata;
+use crate::glob::Glob;
+use http::{HeaderMap, HeaderName, HeaderValue};
+use std::fmt::{Display, Formatter};
+
+/// Headers added to responses for entries matching a glob pattern.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeaderRule {
+  pattern: String,
+  glob: Glob,
+  headers: Vec<(HeaderName, HeaderValue)>,
+}
+
+impl HeaderRule {
+  pub fn pattern(&self) -> &str {
+    &self.pattern
+  }
+
+  pub fn headers(&self) -> &[(HeaderName, HeaderValue)] {
+    &self.headers
+  }
+
+  fn matches(&self, path: &str) -> bool {
+    self.glob.is_match(path)
+  }
+}
+
+/// Response header rules applied to entries of a bundle.
+///
+/// Each rule maps a glob pattern on the entry path to headers. Patterns support `*` (any
+/// characters except `/`), `**` (any number of path segments), `?` (a single character) and
+/// `{a,b}` (alternatives). Patterns are limited to [`HeaderRules::MAX_PATTERN_LEN`] bytes and
+/// [`HeaderRules::MAX_ALTERNATIVES`] brace alternatives. Rules are applied in order, so a later rule overrides headers set
+/// by an earlier one, and headers of the entry itself ([`crate::IndexEntry::headers`]) take
+/// precedence over every rule.
+///
+/// Rules are stored in bundle metadata under [`BundleMetadata::HEADERS`] in the following
+/// text format, where a pattern line is followed by indented `Name: value` lines:
+///
+/// ```text
+/// /assets/**
+///   Cache-Control: public, max-age=31536000, immutable
+/// /**/*.html
+///   Content-Security-Policy: default-src 'self'
+///   Cross-Origin-Opener-Policy: same-origin
+///   Cross-Origin-Embedder-Policy: require-corp
+/// ```
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "protocol")]
+/// # {
+/// use wvb::BundleMetadata;
+/// use wvb::protocol::HeaderRules;
+///
+/// let rules = HeaderRules::new()
+///   .rule("/assets/**", [("Cache-Control", "public, max-age=31536000, immutable")])
+///   .unwrap()
+///   .rule("/**/*.html", [("Content-Security-Policy", "default-src 'self'")])
+///   .unwrap();
+///
+/// let mut metadata = BundleMetadata::new();
+/// metadata.set_headers(rules.to_string());
+/// assert_eq!(HeaderRules::from_metadata(&metadata).unwrap(), Some(rules));
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeaderRules {
+  rules: Vec<HeaderRule>,
+}
+
+impl HeaderRules {
+  /// Maximum length of a pattern in bytes.
+  pub const MAX_PATTERN_LEN: usize = 256;
+  /// Maximum number of patterns the `{a,b}` alternatives of a pattern expand into.
+  pub const MAX_ALTERNATIVES: usize = crate::glob::MAX_ALTERNATIVES;
+
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Add a rule setting `headers` on entries matching `pattern`.
+  ///
+  /// Fails when the pattern is too long, or a header name or value is invalid.
+  pub fn rule<I, K, V>(mut self, pattern: impl Into<String>, headers: I) -> crate::Result<Self>
+  where
+    I: IntoIterator<Item = (K, V)>,
+    K: AsRef<str>,
+    V: AsRef<str>,
+  {
+    let pattern = pattern.into();
+    let glob = compile(&pattern)?;
+    let headers = headers
+      .into_iter()
+      .map(|(name, value)| parse_header(name.as_ref(), value.as_ref(), &pattern))
+      .collect::<crate::Result<Vec<_>>>()?;
+    self.rules.push(HeaderRule {
+      pattern,
+      glob,
+      headers,
+    });
+    Ok(self)
+  }
+
+  pub fn rules(&self) -> &[HeaderRule] {
+    &self.rules
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.rules.is_empty()
+  }
+
+  /// Parses rules from the text format.
+  pub fn parse(value: &str) -> crate::Result<Self> {
+    let mut rules: Vec<HeaderRule> = vec![];
+    for line in value.lines() {
+      let trimmed = line.trim();
+      if trimmed.is_empty() || trimmed.starts_with('#') {
+        continue;
+      }
+      if !line.starts_with(char::is_whitespace) {
+        rules.push(HeaderRule {
+          pattern: trimmed.to_string(),
+          glob: compile(trimmed)?,
+          headers: vec![],
+        });
+        continue;
+      }
+      let rule = rules
+        .last_mut()
+        .ok_or_else(|| invalid(trimmed, "header is not preceded by a pattern"))?;
+      let (name, value) = trimmed
+        .split_once(':')
+        .ok_or_else(|| invalid(trimmed, "expected `Name: value`"))?;
+      let header = parse_header(name.trim(), value.trim(), &rule.pattern)?;
+      rule.headers.push(header);
+    }
+    Ok(Self { rules })
+  }
+
+  /// Reads the rules declared in bundle metadata.
+  ///
+  /// Returns `None` when the metadata declares no rules.
+  pub fn from_metadata(metadata: &BundleMetadata) -> crate::Result<Option<Self>> {
+    match metadata.headers() {
+      Some(value) => {
+        let rules = Self::parse(value)?;
+        Ok(if rules.is_empty() { None } else { Some(rules) })
+      }
+      None => Ok(None),
+    }
+  }
+
+  /// Sets the headers of every rule matching the entry path.
+  pub(crate) fn apply(&self, path: &str, headers: &mut HeaderMap) {
+    for rule in self.rules.iter().filter(|x| x.matches(path)) {
+      for (name, value) in &rule.headers {
+        headers.insert(name.clone(), value.clone());
+      }
+    }
+  }
+}
+
+impl Display for HeaderRules {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    for rule in &self.rules {
+      writeln!(f, "{}", rule.pattern)?;
+      for (name, value) in &rule.headers {
+        writeln!(
+          f,
+          "  {}: {}",
+          name,
+          String::from_utf8_lossy(value.as_bytes())
+        )?;
+      }
+    }
+    Ok(())
+  }
+}
+
+fn invalid(rule: &str, reason: impl Into<String>) -> crate::Error {
+  crate::Error::InvalidHeaderRule {
+    rule: rule.to_string(),
+    reason: reason.into(),
+  }
+}
+
+fn compile(pattern: &str) -> crate::Result<Glob> {
+  if pattern.len() > HeaderRules::MAX_PATTERN_LEN {
+    return Err(invalid(
+      pattern,
+      format!(
+        "pattern is longer than {} bytes",
+        HeaderRules::MAX_PATTERN_LEN
+      ),
+    ));
+  }
+  Glob::new(pattern).map_err(|reason| invalid(pattern, reason))
+}
+
+fn parse_header(
+  name: &str,
+  value: &str,
+  pattern: &str,
+) -> crate::Result<(HeaderName, HeaderValue)> {
+  let name =
+    HeaderName::from_bytes(name.as_bytes()).map_err(|e| invalid(pattern, e.to_string()))?;
+  let value = HeaderValue::from_str(value).map_err(|e| invalid(pattern, e.to_string()))?;
+  Ok((name, value))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn rule(pattern: &str) -> HeaderRule {
+    HeaderRule {
+      pattern: pattern.to_string(),
+      glob: Glob::new(pattern).unwrap(),
+      headers: vec![],
+    }
+  }
+
+  #[test]
+  fn glob() {
+    assert!(rule("/index.html").matches("/index.html"));
+    assert!(rule("/*.html").matches("/index.html"));
+    assert!(!rule("/*.html").matches("/docs/index.html"));
+    assert!(rule("/**/*.html").matches("/index.html"));
+    assert!(rule("/**/*.html").matches("/docs/guide/index.html"));
+    assert!(rule("/assets/**").matches("/assets/js/app.js"));
+    assert!(!rule("/assets/**").matches("/static/app.js"));
+    assert!(rule("/assets/*.{js,css}").matches("/assets/app.css"));
+    assert!(!rule("/assets/*.{js,css}").matches("/assets/app.png"));
+    assert!(rule("/app.?s").matches("/app.js"));
+    assert!(rule("/{a,b}/{c,d}.js").matches("/b/c.js"));
+    assert!(!rule("/{a,b}/{c,d}.js").matches("/b/e.js"));
+  }
+
+  #[test]
+  fn glob_without_backtracking() {
+    let pattern = "/*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*a*b";
+    let path = format!("/{}", "a".repeat(10_000));
+    assert!(!rule(pattern).matches(&path));
+    assert!(rule("/**/**/**/**/**/**/x").matches("/a/b/c/d/e/f/g/h/x"));
+  }
+
+  #[test]
+  fn reject_large_pattern() {
+    let long = format!("/{}", "a".repeat(HeaderRules::MAX_PATTERN_LEN));
+    assert!(HeaderRules::parse(&long).is_err());
+    assert!(
+      HeaderRules::new()
+        .rule(long, [("cache-control", "no-cache")])
+        .is_err()
+    );
+    assert!(HeaderRules::parse(&"{a,b}".repeat(6)).is_err());
+    assert!(HeaderRules::parse(&"{a,b}".repeat(5)).is_ok());
+  }
+
+  #[test]
+  fn parse() {
+    let rules = HeaderRules::parse(
+      "# hashed assets\n/assets/**\n  Cache-Control: public, max-age=31536000, immutable\n\n/**/*.html\n  Content-Security-Policy: default-src 'self'\n  Cross-Origin-Opener-Policy: same-origin\n",
+    )
+    .unwrap();
+    assert_eq!(
+      rules,
+      HeaderRules::new()
+        .rule(
+          "/assets/**",
+          [("cache-control", "public, max-age=31536000, immutable")]
+        )
+        .unwrap()
+        .rule(
+          "/**/*.html",
+          [
+            ("content-security-policy", "default-src 'self'"),
+            ("cross-origin-opener-policy", "same-origin"),
+          ]
+        )
+        .unwrap()
+    );
+    assert_eq!(HeaderRules::parse(&rules.to_string()).unwrap(), rules);
+
+    assert!(HeaderRules::parse("  Cache-Control: no-cache").is_err());
+    assert!(HeaderRules::parse("/*\n  Cache-Control").is_err());
+    assert!(HeaderRules::parse("/*\n  Bad Name: value").is_err());
+  }
+
+  #[test]
+  fn apply() {
+    let rules = HeaderRules::new()
+      .rule("/**", [("cache-control", "no-cache")])
+      .unwrap()
+      .rule("/assets/**", [("cache-control", "immutable")])
+      .unwrap();
+    let mut headers = HeaderMap::new();
+    rules.apply("/index.html", &mut headers);
+    assert_eq!(headers.get("cache-control").unwrap(), "no-cache");
+    let mut headers = HeaderMap::new();
+    rules.apply("/assets/app.js", &mut headers);
+    assert_eq!(headers.get("cache-control").unwrap(), "immutable");
+  }
+}
@@ -0,0 +1,164 @@
+//! WebSocket passthrough for hot module reload (HMR) of dev servers.
+//!
+//! Webviews can't open WebSocket connections over custom schemes, so HMR clients (Vite,
+//! webpack) which derive their socket URL from `location` fail to connect when the app is
+//! loaded through a [`super::LocalProtocol`]. HTML responses get a small script which redirects
+//! WebSocket connections to the custom host to the mapped dev server instead.
+
+/// Converts an http(s) URL into the matching ws(s) URL.
+pub(crate) fn to_websocket_url(url: &str) -> String {
+  if let Some(rest) = url.strip_prefix("https://") {
+    return format!("wss://{rest}");
+  }
+  if let Some(rest) = url.strip_prefix("http://") {
+    return format!("ws://{rest}");
+  }
+  url.to_string()
+}
+
+fn is_html(headers: &http::HeaderMap) -> bool {
+  headers
+    .get(http::header::CONTENT_TYPE)
+    .and_then(|x| x.to_str().ok())
+    .map(|x| x.trim_start().starts_with("text/html"))
+    .unwrap_or(false)
+}
+
+/// Whether the body is sent as is, so the script can be spliced into it.
+fn is_identity_encoded(headers: &http::HeaderMap) -> bool {
+  headers
+    .get_all(http::header::CONTENT_ENCODING)
+    .iter()
+    .flat_map(|x| x.to_str().unwrap_or("unknown").split(','))
+    .all(|x| {
+      let x = x.trim();
+      x.is_empty() || x.eq_ignore_ascii_case("identity")
+    })
+}
+
+fn redirect_script(host: &str, websocket_base: &str) -> String {
+  let mapping = serde_json::json!({ host: websocket_base.trim_end_matches('/') });
+  format!(
+    "<script>(function(){{var m={mapping};var W=window.WebSocket;\
+function S(u,p){{try{{var x=new URL(u,location.href);var t=m[x.hostname];\
+if(t){{u=t+x.pathname+x.search;}}}}catch(e){{}}return p===undefined?new W(u):new W(u,p);}}\
+S.prototype=W.prototype;S.CONNECTING=0;S.OPEN=1;S.CLOSING=2;S.CLOSED=3;\
+window.WebSocket=S;}})();</script>"
+  )
+}
+
+/// Injects the WebSocket redirect script into an HTML body.
+///
+/// The script is placed right after the opening `<head>` tag so it runs before any HMR
+/// client, or at the start of the document when there is no `<head>`. Returns `None` when
+/// the response is not HTML or its body is encoded (e.g. gzip).
+pub(crate) fn inject(
+  headers: &http::HeaderMap,
+  body: &[u8],
+  host: &str,
+  websocket_base: &str,
+) -> Option<Vec<u8>> {
+  if !is_html(headers) || !is_identity_encoded(headers) {
+    return None;
+  }
+  let script = redirect_script(host, websocket_base);
+  let lower = body.to_ascii_lowercase();
+  // `<head` followed by `>` or whitespace, so `<header>` doesn't match
+  let pos = lower
+    .windows(6)
+    .position(|x| x.starts_with(b"<head") && (x[5] == b'>' || x[5].is_ascii_whitespace()))
+    .and_then(|start| {
+      lower[start..]
+        .iter()
+        .position(|x| *x == b'>')
+        .map(|end| start + end + 1)
+    })
+    .unwrap_or(0);
+  let mut injected = Vec::with_capacity(body.len() + script.len());
+  injected.extend_from_slice(&body[..pos]);
+  injected.extend_from_slice(script.as_bytes());
+  injected.extend_from_slice(&body[pos..]);
+  Some(injected)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn html_headers() -> http::HeaderMap {
+    let mut headers = http::HeaderMap::new();
+    headers.insert(
+      http::header::CONTENT_TYPE,
+      "text/html; charset=utf-8".parse().unwrap(),
+    );
+    headers
+  }
+
+  #[test]
+  fn websocket_url() {
+    assert_eq!(
+      to_websocket_url("http://localhost:3000"),
+      "ws://localhost:3000"
+    );
+    assert_eq!(
+      to_websocket_url("https://localhost:3000/"),
+      "wss://localhost:3000/"
+    );
+  }
+
+  #[test]
+  fn inject_after_head() {
+    let body = b"<html><HEAD lang=\"en\"><title>a</title></head></html>";
+    let injected = inject(&html_headers(), body, "app", "ws://localhost:3000").unwrap();
+    let injected = String::from_utf8(injected).unwrap();
+    assert!(injected.starts_with("<html><HEAD lang=\"en\"><script>"));
+    assert!(injected.contains(r#"{"app":"ws://localhost:3000"}"#));
+    assert!(injected.ends_with("</script><title>a</title></head></html>"));
+  }
+
+  #[test]
+  fn inject_skip_header() {
+    let body = b"<header>x</header><head><title>a</title></head>";
+    let injected = inject(&html_headers(), body, "app", "ws://localhost").unwrap();
+    let injected = String::from_utf8(injected).unwrap();
+    assert!(injected.starts_with("<header>x</header><head><script>"));
+
+    let injected = inject(
+      &html_headers(),
+      b"<header>x</header>",
+      "app",
+      "ws://localhost",
+    )
+    .unwrap();
+    let injected = String::from_utf8(injected).unwrap();
+    assert!(injected.starts_with("<script>"));
+  }
+
+  #[test]
+  fn inject_without_head() {
+    let injected = inject(&html_headers(), b"<p>hi</p>", "app", "ws://localhost").unwrap();
+    let injected = String::from_utf8(injected).unwrap();
+    assert!(injected.starts_with("<script>"));
+    assert!(injected.ends_with("</script><p>hi</p>"));
+  }
+
+  #[test]
+  fn skip_non_html() {
+    let mut headers = http::HeaderMap::new();
+    headers.insert(
+      http::header::CONTENT_TYPE,
+      "text/javascript".parse().unwrap(),
+    );
+    assert!(inject(&headers, b"<head>", "app", "ws://localhost").is_none());
+  }
+
+  #[test]
+  fn skip_encoded() {
+    let mut headers = html_headers();
+    headers.insert(http::header::CONTENT_ENCODING, "gzip".parse().unwrap());
+    assert!(inject(&headers, b"\x1f\x8b\x08", "app", "ws://localhost").is_none());
+
+    headers.insert(http::header::CONTENT_ENCODING, "identity".parse().unwrap());
+    assert!(inject(&headers, b"<head>", "app", "ws://localhost").is_some());
+  }
+}
@@ -0,0 +1,272 @@
+use super::{
+  BundleProtocol, LocalProtocol, Protocol, ProtocolResponse, StreamProtocol,
+  StreamProtocolResponse, once_stream,
+};
+use async_trait::async_trait;
+use dashmap::DashMap;
+use std::sync::atomic::{AtomicU8, Ordering};
+use std::time::{Duration, Instant};
+
+/// Which protocol a [`HybridProtocol`] serves requests from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HybridMode {
+  /// Proxy to the local dev server when it is reachable, otherwise serve from bundles.
+  #[default]
+  Auto,
+  /// Always proxy to the local dev server.
+  Local,
+  /// Always serve from bundles.
+  Bundle,
+}
+
+impl HybridMode {
+  fn from_u8(value: u8) -> Self {
+    match value {
+      1 => Self::Local,
+      2 => Self::Bundle,
+      _ => Self::Auto,
+    }
+  }
+
+  fn as_u8(self) -> u8 {
+    match self {
+      Self::Auto => 0,
+      Self::Local => 1,
+      Self::Bundle => 2,
+    }
+  }
+}
+
+/// Protocol handler switching between a [`LocalProtocol`] and a [`BundleProtocol`].
+///
+/// The same app URL works during development, where requests are proxied to the dev server,
+/// and in production, where requests are served from bundles. The mode can be switched at
+/// runtime with [`HybridProtocol::set_mode`].
+///
+/// In [`HybridMode::Auto`], a request is proxied to the dev server first. When the dev server
+/// can't be connected to, the request is served from bundles and the dev server is not tried
+/// again until the retry interval passes.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(feature = "protocol-local")]
+/// # {
+/// use wvb::protocol::{BundleProtocol, HybridMode, HybridProtocol, LocalProtocol};
+/// use wvb::source::BundleSource;
+/// use std::collections::HashMap;
+/// use std::sync::Arc;
+///
+/// let source = Arc::new(BundleSource::builder().builtin_dir("./bundles").build());
+/// let mut hosts = HashMap::new();
+/// hosts.insert("app".to_string(), "http://localhost:3000".to_string());
+///
+/// let protocol = HybridProtocol::new(BundleProtocol::new(source), LocalProtocol::new(hosts));
+/// protocol.set_mode(HybridMode::Bundle);
+/// # }
+/// ```
+pub struct HybridProtocol {
+  bundle: BundleProtocol,
+  local: LocalProtocol,
+  mode: AtomicU8,
+  retry_interval: Duration,
+  unreachable: DashMap<String, Instant>,
+}
+
+impl HybridProtocol {
+  /// Default time to wait before connecting to an unreachable dev server again.
+  pub const DEFAULT_RETRY_INTERVAL: Duration = Duration::from_secs(5);
+
+  pub fn new(bundle: BundleProtocol, local: LocalProtocol) -> Self {
+    Self {
+      bundle,
+      local,
+      mode: AtomicU8::new(HybridMode::default().as_u8()),
+      retry_interval: Self::DEFAULT_RETRY_INTERVAL,
+      unreachable: DashMap::new(),
+    }
+  }
+
+  /// Set the initial mode.
+  pub fn with_mode(self, mode: HybridMode) -> Self {
+    self.set_mode(mode);
+    self
+  }
+
+  /// Set how long an unreachable dev server is skipped in [`HybridMode::Auto`].
+  pub fn with_retry_interval(mut self, interval: Duration) -> Self {
+    self.retry_interval = interval;
+    self
+  }
+
+  pub fn mode(&self) -> HybridMode {
+    HybridMode::from_u8(self.mode.load(Ordering::Relaxed))
+  }
+
+  /// Switch the mode. Takes effect from the next request.
+  pub fn set_mode(&self, mode: HybridMode) {
+    self.mode.store(mode.as_u8(), Ordering::Relaxed);
+    self.unreachable.clear();
+  }
+
+  fn is_unreachable(&self, localhost: &str) -> bool {
+    match self.unreachable.get(localhost) {
+      Some(since) => since.elapsed() < self.retry_interval,
+      None => false,
+    }
+  }
+}
+
+fn clone_request(request: &http::Request<Vec<u8>>) -> http::Request<Vec<u8>> {
+  let mut cloned = http::Request::new(request.body().clone());
+  *cloned.method_mut() = request.method().clone();
+  *cloned.uri_mut() = request.uri().clone();
+  *cloned.version_mut() = request.version();
+  *cloned.headers_mut() = request.headers().clone();
+  cloned
+}
+
+fn is_unreachable_error(e: &crate::Error) -> bool {
+  match e {
+    crate::Error::Reqwest(e) => e.is_connect() || e.is_timeout(),
+    _ => false,
+  }
+}
+
+impl HybridProtocol {
+  /// Proxies the request to the dev server, or returns `None` when it should be served from
+  /// bundles.
+  async fn handle_local(
+    &self,
+    request: &http::Request<Vec<u8>>,
+  ) -> crate::Result<Option<ProtocolResponse>> {
+    match self.mode() {
+      HybridMode::Local => self.local.handle(clone_request(request)).await.map(Some),
+      HybridMode::Bundle => Ok(None),
+      HybridMode::Auto => {
+        let localhost = match self.local.uri_resolver.resolve_localhost(request.uri()) {
+          Some(x) => x,
+          None => return Ok(None),
+        };
+        if self.is_unreachable(&localhost) {
+          return Ok(None);
+        }
+        match self.local.handle(clone_request(request)).await {
+          Ok(resp) => {
+            self.unreachable.remove(&localhost);
+            Ok(Some(resp))
+          }
+          Err(e) if is_unreachable_error(&e) => {
+            #[cfg(feature = "tracing")]
+            tracing::warn!(localhost, "local server is unreachable, fallback to bundle");
+            self.unreachable.insert(localhost, Instant::now());
+            Ok(None)
+          }
+          Err(e) => Err(e),
+        }
+      }
+    }
+  }
+}
+
+#[async_trait]
+impl Protocol for HybridProtocol {
+  async fn handle(&self, request: http::Request<Vec<u8>>) -> crate::Result<ProtocolResponse> {
+    match self.handle_local(&request).await? {
+      Some(resp) => Ok(resp),
+      None => self.bundle.handle(request).await,
+    }
+  }
+}
+
+#[async_trait]
+impl StreamProtocol for HybridProtocol {
+  async fn handle_stream(
+    &self,
+    request: http::Request<Vec<u8>>,
+  ) -> crate::Result<StreamProtocolResponse> {
+    match self.handle_local(&request).await? {
+      Some(resp) => Ok(resp.map(|x| once_stream(x.into_owned()))),
+      None => self.bundle.handle_stream(request).await,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::source::BundleSource;
+  use crate::testing::Fixtures;
+  use std::collections::HashMap;
+  use std::net::TcpListener;
+  use std::sync::Arc;
+  use tiny_http::{Response as TinyResponse, Server as TinyServer};
+
+  fn bundle_protocol() -> BundleProtocol {
+    let fixture = Fixtures::bundles();
+    let source = BundleSource::builder()
+      .builtin_dir(fixture.get_path("builtin"))
+      .remote_dir(fixture.get_path("remote"))
+      .build();
+    BundleProtocol::new(Arc::new(source))
+  }
+
+  fn unreachable_addr() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    format!("http://{addr}")
+  }
+
+  fn server() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = TinyServer::from_listener(listener, None).unwrap();
+    std::thread::spawn(move || {
+      for request in server.incoming_requests() {
+        let _ = request.respond(TinyResponse::from_string("from local"));
+      }
+    });
+    format!("http://{addr}")
+  }
+
+  fn request() -> http::Request<Vec<u8>> {
+    http::Request::builder()
+      .uri("https://app.wvb/index.html")
+      .method("GET")
+      .body(vec![])
+      .unwrap()
+  }
+
+  fn local_protocol(localhost: String) -> LocalProtocol {
+    LocalProtocol::new(HashMap::from([("app.wvb".to_string(), localhost)]))
+  }
+
+  #[tokio::test]
+  async fn auto_proxies_to_reachable_local() {
+    let protocol = HybridProtocol::new(bundle_protocol(), local_protocol(server()));
+    let resp = protocol.handle(request()).await.unwrap();
+    assert_eq!(resp.body().as_ref(), b"from local");
+  }
+
+  #[tokio::test]
+  async fn auto_fallback_to_bundle() {
+    let protocol = HybridProtocol::new(bundle_protocol(), local_protocol(unreachable_addr()));
+    let resp = protocol.handle(request()).await.unwrap();
+    assert_eq!(resp.status(), 200);
+    assert_ne!(resp.body().as_ref(), b"from local");
+    assert!(!protocol.unreachable.is_empty());
+  }
+
+  #[tokio::test]
+  async fn switch_mode() {
+    let protocol = HybridProtocol::new(bundle_protocol(), local_protocol(unreachable_addr()))
+      .with_mode(HybridMode::Local);
+    assert!(protocol.handle(request()).await.is_err());
+
+    protocol.set_mode(HybridMode::Bundle);
+    assert_eq!(protocol.mode(), HybridMode::Bundle);
+    let resp = protocol.handle(request()).await.unwrap();
+    assert_eq!(resp.status(), 200);
+  }
+}
@@ -1,3 +1,5 @@
+use super::hmr;
+use crate::http_config::HttpConfig;
 use async_trait::async_trait;
 use dashmap::DashMap;
 use http;
@@ -51,6 +53,20 @@ pub trait LocalUriResolver: Send + Sync {
     }
     None
   }
+
+  /// Gets the WebSocket URL of the localhost server, used for HMR connections.
+  ///
+  /// # Example
+  ///
+  /// ```text
+  /// Input:  app://myapp/ws?token=abc
+  /// Output: ws://localhost:3000/ws?token=abc
+  /// ```
+  fn get_websocket_uri(&self, uri: &Uri) -> Option<String> {
+    self
+      .get_localhost_uri(uri)
+      .map(|x| hmr::to_websocket_url(&x))
+  }
 }
 
 /// URI resolver using a static host-to-localhost mapping.
@@ -95,6 +111,40 @@ impl LocalUriResolver for MappingLocalUriResolver {
   }
 }
 
+/// Headers which only apply to a single connection and must not be forwarded by proxies.
+const HOP_BY_HOP_HEADERS: &[http::HeaderName] = &[
+  http::header::CONNECTION,
+  http::header::PROXY_AUTHENTICATE,
+  http::header::PROXY_AUTHORIZATION,
+  http::header::TE,
+  http::header::TRAILER,
+  http::header::TRANSFER_ENCODING,
+  http::header::UPGRADE,
+];
+
+/// Copies headers except hop-by-hop headers, including the ones listed in `Connection`.
+fn forward_headers(headers: &http::HeaderMap) -> http::HeaderMap {
+  let connection_headers = headers
+    .get_all(http::header::CONNECTION)
+    .iter()
+    .filter_map(|x| x.to_str().ok())
+    .flat_map(|x| x.split(','))
+    .map(|x| x.trim().to_ascii_lowercase())
+    .collect::<Vec<_>>();
+  let mut forwarded = http::HeaderMap::with_capacity(headers.len());
+  for (name, value) in headers {
+    if HOP_BY_HOP_HEADERS.contains(name)
+      || name == http::header::HOST
+      || name.as_str() == "keep-alive"
+      || connection_headers.iter().any(|x| x == name.as_str())
+    {
+      continue;
+    }
+    forwarded.append(name, value.clone());
+  }
+  forwarded
+}
+
 #[derive(Clone)]
 struct CachedResponse {
   status: http::StatusCode,
@@ -102,13 +152,76 @@ struct CachedResponse {
   body: bytes::Bytes,
 }
 
+impl CachedResponse {
+  /// Only complete responses which can be revalidated are cached.
+  fn is_cacheable(
+    method: &http::Method,
+    status: http::StatusCode,
+    headers: &http::HeaderMap,
+  ) -> bool {
+    if method != http::Method::GET || status != http::StatusCode::OK {
+      return false;
+    }
+    let no_store = headers
+      .get_all(http::header::CACHE_CONTROL)
+      .iter()
+      .filter_map(|x| x.to_str().ok())
+      .any(|x| x.to_ascii_lowercase().contains("no-store"));
+    let vary_all = headers
+      .get_all(http::header::VARY)
+      .iter()
+      .filter_map(|x| x.to_str().ok())
+      .any(|x| x.trim() == "*");
+    let validatable =
+      headers.contains_key(http::header::ETAG) || headers.contains_key(http::header::LAST_MODIFIED);
+    validatable && !no_store && !vary_all
+  }
+
+  fn apply_conditional_headers(&self, headers: &mut http::HeaderMap) {
+    if let Some(etag) = self.headers.get(http::header::ETAG) {
+      headers.insert(http::header::IF_NONE_MATCH, etag.clone());
+    }
+    if let Some(last_modified) = self.headers.get(http::header::LAST_MODIFIED) {
+      headers.insert(http::header::IF_MODIFIED_SINCE, last_modified.clone());
+    }
+  }
+}
+
+fn vary_header_names(headers: &http::HeaderMap) -> Vec<http::HeaderName> {
+  headers
+    .get_all(http::header::VARY)
+    .iter()
+    .filter_map(|x| x.to_str().ok())
+    .flat_map(|x| x.split(','))
+    .filter_map(|x| http::HeaderName::from_bytes(x.trim().as_bytes()).ok())
+    .collect()
+}
+
+/// Cache key of a request: the URL and the values of the request headers the response
+/// varies on.
+fn cache_key(url: &str, vary: &[http::HeaderName], headers: &http::HeaderMap) -> String {
+  let mut key = url.to_string();
+  for name in vary {
+    key.push('\n');
+    key.push_str(name.as_str());
+    key.push(':');
+    for value in headers.get_all(name) {
+      key.push_str(&String::from_utf8_lossy(value.as_bytes()));
+      key.push(',');
+    }
+  }
+  key
+}
+
 /// Protocol handler that proxies requests to localhost servers.
 ///
 /// `LocalProtocol` forwards requests to local development servers, making it
 /// easy to develop webview applications with hot-reloading. Features:
 ///
 /// - **Host mapping**: Map custom protocol hosts to localhost URLs
-/// - **Response caching**: Cache responses and respect 304 Not Modified
+/// - **Header forwarding**: Request and response headers are forwarded, except hop-by-hop headers
+/// - **Response caching**: Cache responses and revalidate them with conditional requests
+/// - **Connection pooling**: A single HTTP client is shared by all requests
 /// - **Development mode**: Perfect for local development workflows
 ///
 /// # Example
@@ -136,7 +249,10 @@ struct CachedResponse {
 ///
 /// # Caching
 ///
-/// The protocol caches responses and supports HTTP 304 Not Modified:
+/// Responses with an `ETag` or `Last-Modified` header are cached by URL and the request headers
+/// listed in `Vary`. Later requests are sent with `If-None-Match` / `If-Modified-Since`, and
+/// the cached response is served when the server answers 304 Not Modified. Requests which
+/// already carry conditional or `Range` headers are passed through as is.
 ///
 /// ```no_run
 /// # #[cfg(feature = "protocol-local")]
@@ -160,8 +276,11 @@ struct CachedResponse {
 /// # };
 /// ```
 pub struct LocalProtocol {
-  uri_resolver: Box<dyn LocalUriResolver + 'static>,
+  pub(crate) uri_resolver: Box<dyn LocalUriResolver + 'static>,
+  client: reqwest::Client,
   cache: DashMap<String, CachedResponse>,
+  vary: DashMap<String, Vec<http::HeaderName>>,
+  hmr: bool,
 }
 
 impl LocalProtocol {
@@ -189,9 +308,59 @@ impl LocalProtocol {
   pub fn new<T: Into<HashMap<String, String>>>(hosts: T) -> Self {
     Self {
       uri_resolver: Box::new(MappingLocalUriResolver::new(hosts)),
+      client: reqwest::Client::new(),
       cache: DashMap::default(),
+      vary: DashMap::default(),
+      hmr: false,
     }
   }
+
+  /// Creates a new `LocalProtocol` with host-to-localhost mapping and HTTP client configuration.
+  ///
+  /// The client is built once and its connections are reused across requests.
+  pub fn new_with_http<T: Into<HashMap<String, String>>>(
+    hosts: T,
+    http: HttpConfig,
+  ) -> crate::Result<Self> {
+    let client = http.apply(reqwest::ClientBuilder::new()).build()?;
+    Ok(Self {
+      uri_resolver: Box::new(MappingLocalUriResolver::new(hosts)),
+      client,
+      cache: DashMap::default(),
+      vary: DashMap::default(),
+      hmr: false,
+    })
+  }
+
+  /// Enables WebSocket passthrough for hot module reload.
+  ///
+  /// HTML responses get a script which redirects WebSocket connections to the custom host
+  /// to the mapped localhost server, so HMR clients of dev servers connect even though the
+  /// app is loaded through a custom scheme.
+  pub fn with_hmr(mut self, enabled: bool) -> Self {
+    self.hmr = enabled;
+    self
+  }
+
+  /// Returns the localhost WebSocket URL for a custom scheme URL.
+  ///
+  /// ```
+  /// # #[cfg(feature = "protocol-local")]
+  /// # {
+  /// use wvb::protocol::LocalProtocol;
+  /// use std::collections::HashMap;
+  ///
+  /// let protocol = LocalProtocol::new(HashMap::from([(
+  ///   "myapp".to_string(),
+  ///   "http://localhost:5173".to_string(),
+  /// )]));
+  /// let url = protocol.websocket_url(&"app://myapp/?token=abc".parse().unwrap());
+  /// assert_eq!(url.as_deref(), Some("ws://localhost:5173/?token=abc"));
+  /// # }
+  /// ```
+  pub fn websocket_url(&self, uri: &Uri) -> Option<String> {
+    self.uri_resolver.get_websocket_uri(uri)
+  }
 }
 
 #[async_trait]
@@ -213,36 +382,81 @@ impl super::Protocol for LocalProtocol {
     #[cfg(feature = "tracing")]
     tracing::info!(localhost_uri = url);
 
-    let mut builder = http::Response::builder();
-
-    let client = reqwest::ClientBuilder::new();
-    let mut proxy_builder = client.build()?.request(request.method().clone(), &url);
-    proxy_builder = proxy_builder.body(request.body().clone());
-    let r = proxy_builder.send().await?;
-    let mut response = None;
-    if r.status() == http::StatusCode::NOT_MODIFIED {
-      response = self.cache.get(&url)
+    let (parts, body) = request.into_parts();
+    let mut headers = forward_headers(&parts.headers);
+    if self.hmr {
+      // the HMR script can only be injected into bodies which are not compressed
+      headers.remove(http::header::ACCEPT_ENCODING);
     }
-    let response = if let Some(r) = response {
-      r
+    let revalidate = parts.method == http::Method::GET
+      && !headers.contains_key(http::header::IF_NONE_MATCH)
+      && !headers.contains_key(http::header::IF_MODIFIED_SINCE)
+      && !headers.contains_key(http::header::RANGE);
+    let cached = if revalidate {
+      let vary = self.vary.get(&url).map(|x| x.clone()).unwrap_or_default();
+      self
+        .cache
+        .get(&cache_key(&url, &vary, &parts.headers))
+        .map(|x| x.clone())
     } else {
-      let status = r.status();
-      let headers = r.headers().clone();
-      let body = r.bytes().await?;
-      let response = CachedResponse {
-        status,
-        headers,
-        body,
-      };
-      self.cache.insert(url.to_string(), response);
-      self.cache.get(&url).unwrap()
+      None
     };
+    if let Some(ref cached) = cached {
+      cached.apply_conditional_headers(&mut headers);
+    }
+
+    let r = self
+      .client
+      .request(parts.method.clone(), &url)
+      .headers(headers)
+      .body(body)
+      .send()
+      .await?;
+    let response = match cached {
+      Some(cached) if r.status() == http::StatusCode::NOT_MODIFIED => cached,
+      _ => {
+        let status = r.status();
+        let headers = forward_headers(r.headers());
+        let body = r.bytes().await?;
+        let response = CachedResponse {
+          status,
+          headers,
+          body,
+        };
+        if revalidate && CachedResponse::is_cacheable(&parts.method, status, &response.headers) {
+          let vary = vary_header_names(&response.headers);
+          let key = cache_key(&url, &vary, &parts.headers);
+          self.vary.insert(url.to_string(), vary);
+          self.cache.insert(key, response.clone());
+        }
+        response
+      }
+    };
+    let mut body = None;
+    if self.hmr
+      && response.status == http::StatusCode::OK
+      && let (Some(host), Some(localhost)) = (
+        parts.uri.host(),
+        self.uri_resolver.resolve_localhost(&parts.uri),
+      )
+    {
+      body = hmr::inject(
+        &response.headers,
+        &response.body,
+        host,
+        &hmr::to_websocket_url(&localhost),
+      );
+    }
+    let mut builder = http::Response::builder();
     for (name, value) in &response.headers {
+      if body.is_some() && name == http::header::CONTENT_LENGTH {
+        continue;
+      }
       builder = builder.header(name, value);
     }
     let resp = builder
       .status(response.status)
-      .body(response.body.to_vec().into())?;
+      .body(body.unwrap_or_else(|| response.body.to_vec()).into())?;
     #[cfg(feature = "tracing")]
     {
       use crate::protocol::http_ext::HttpHeadersTracingInfo;
@@ -255,6 +469,18 @@ impl super::Protocol for LocalProtocol {
   }
 }
 
+/// Responses of dev servers are cached for revalidation, so bodies are read at once.
+#[async_trait]
+impl super::StreamProtocol for LocalProtocol {
+  async fn handle_stream(
+    &self,
+    request: http::Request<Vec<u8>>,
+  ) -> crate::Result<super::StreamProtocolResponse> {
+    let response = super::Protocol::handle(self, request).await?;
+    Ok(response.map(|x| super::once_stream(x.into_owned())))
+  }
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
@@ -263,8 +489,8 @@ mod tests {
   use std::collections::HashMap;
   use std::iter;
   use std::net::{SocketAddr, TcpListener};
-  use std::sync::Arc;
   use std::sync::atomic::{AtomicUsize, Ordering};
+  use std::sync::{Arc, Mutex};
   use tiny_http::{Header as TinyHeader, Method, Response as TinyResponse, Server as TinyServer};
 
   fn server() -> (SocketAddr, std::thread::JoinHandle<()>) {
@@ -333,4 +559,172 @@ mod tests {
     assert_eq!(second_resp.headers().get("etag").unwrap(), "\"v1\"");
     assert_eq!(first_resp.body().as_ref(), b"Hello World");
   }
+
+  fn echo_server() -> (SocketAddr, Arc<AtomicUsize>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = TinyServer::from_listener(listener, None).unwrap();
+    let full_responses = Arc::new(AtomicUsize::new(0));
+    let counter = full_responses.clone();
+    std::thread::spawn(move || {
+      for request in server.incoming_requests() {
+        let header = |name: &str| {
+          request
+            .headers()
+            .iter()
+            .find(|x| x.field.as_str().as_str().eq_ignore_ascii_case(name))
+            .map(|x| x.value.to_string())
+        };
+        let lang = header("Accept-Language").unwrap_or_default();
+        let etag = format!("\"{lang}\"");
+        if header("If-None-Match").as_deref() == Some(etag.as_str()) {
+          let _ = request.respond(TinyResponse::empty(304));
+          continue;
+        }
+        counter.fetch_add(1, Ordering::SeqCst);
+        let body = format!(
+          "lang={lang};cookie={};proxy-authorization={}",
+          header("Cookie").unwrap_or_default(),
+          header("Proxy-Authorization").unwrap_or_default(),
+        );
+        let mut resp = TinyResponse::from_string(body);
+        resp.add_header(TinyHeader::from_bytes("ETag", etag.as_bytes()).unwrap());
+        resp.add_header(TinyHeader::from_bytes("Vary", "Accept-Language").unwrap());
+        let _ = request.respond(resp);
+      }
+    });
+    (addr, full_responses)
+  }
+
+  fn request(lang: &str) -> http::Request<Vec<u8>> {
+    http::Request::builder()
+      .uri("https://app.wvb/index.html")
+      .method("GET")
+      .header("Accept-Language", lang)
+      .header("Cookie", "session=1")
+      .header("Proxy-Authorization", "secret")
+      .body(Vec::new())
+      .unwrap()
+  }
+
+  #[tokio::test]
+  async fn forward_headers() {
+    let (addr, _) = echo_server();
+    let protocol = LocalProtocol::new_with_http(
+      HashMap::from([("app.wvb".to_string(), format!("http://{addr}"))]),
+      HttpConfig::new().pool_max_idle_per_host(1),
+    )
+    .unwrap();
+    let resp = protocol.handle(request("en")).await.unwrap();
+    assert_eq!(
+      resp.body().as_ref(),
+      b"lang=en;cookie=session=1;proxy-authorization="
+    );
+  }
+
+  #[tokio::test]
+  async fn cache_by_vary() {
+    let (addr, full_responses) = echo_server();
+    let protocol = LocalProtocol::new(HashMap::from([(
+      "app.wvb".to_string(),
+      format!("http://{addr}"),
+    )]));
+    let en = protocol.handle(request("en")).await.unwrap();
+    let ko = protocol.handle(request("ko")).await.unwrap();
+    assert!(en.body().starts_with(b"lang=en"));
+    assert!(ko.body().starts_with(b"lang=ko"));
+    assert_eq!(full_responses.load(Ordering::SeqCst), 2);
+
+    // both are revalidated and served from cache
+    let en = protocol.handle(request("en")).await.unwrap();
+    let ko = protocol.handle(request("ko")).await.unwrap();
+    assert_eq!(en.status(), 200);
+    assert!(en.body().starts_with(b"lang=en"));
+    assert!(ko.body().starts_with(b"lang=ko"));
+    assert_eq!(full_responses.load(Ordering::SeqCst), 2);
+
+    // conditional requests from the webview are passed through
+    let mut conditional = request("en");
+    conditional
+      .headers_mut()
+      .insert("If-None-Match", "\"en\"".parse().unwrap());
+    let resp = protocol.handle(conditional).await.unwrap();
+    assert_eq!(resp.status(), 304);
+  }
+
+  #[tokio::test]
+  async fn hmr() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = TinyServer::from_listener(listener, None).unwrap();
+    std::thread::spawn(move || {
+      for request in server.incoming_requests() {
+        let mut resp = TinyResponse::from_string("<html><head></head></html>");
+        resp.add_header(TinyHeader::from_bytes("Content-Type", "text/html").unwrap());
+        let _ = request.respond(resp);
+      }
+    });
+    let protocol = LocalProtocol::new(HashMap::from([(
+      "app.wvb".to_string(),
+      format!("http://{addr}"),
+    )]))
+    .with_hmr(true);
+    assert_eq!(
+      protocol.websocket_url(&"app://app.wvb/hmr?t=1".parse().unwrap()),
+      Some(format!("ws://{addr}/hmr?t=1"))
+    );
+    let resp = protocol
+      .handle(
+        http::Request::builder()
+          .uri("app://app.wvb/")
+          .body(Vec::new())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    let body = String::from_utf8(resp.body().to_vec()).unwrap();
+    assert!(body.starts_with("<html><head><script>"));
+    assert!(body.contains(&format!("\"app.wvb\":\"ws://{addr}\"")));
+    assert!(resp.headers().get("content-length").is_none());
+  }
+
+  #[tokio::test]
+  async fn hmr_skip_encoded() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = TinyServer::from_listener(listener, None).unwrap();
+    let accept_encoding = Arc::new(Mutex::new(None));
+    let accept_encoding_ = accept_encoding.clone();
+    std::thread::spawn(move || {
+      for request in server.incoming_requests() {
+        *accept_encoding_.lock().unwrap() = request
+          .headers()
+          .iter()
+          .find(|x| x.field.equiv("Accept-Encoding"))
+          .map(|x| x.value.to_string());
+        let mut resp = TinyResponse::from_data(b"\x1f\x8b\x08\x00gzip".to_vec());
+        resp.add_header(TinyHeader::from_bytes("Content-Type", "text/html").unwrap());
+        resp.add_header(TinyHeader::from_bytes("Content-Encoding", "gzip").unwrap());
+        let _ = request.respond(resp);
+      }
+    });
+    let protocol = LocalProtocol::new(HashMap::from([(
+      "app.wvb".to_string(),
+      format!("http://{addr}"),
+    )]))
+    .with_hmr(true);
+    let resp = protocol
+      .handle(
+        http::Request::builder()
+          .uri("app://app.wvb/")
+          .header("Accept-Encoding", "gzip, deflate")
+          .body(Vec::new())
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(*accept_encoding.lock().unwrap(), None);
+    assert_eq!(resp.body().as_ref(), b"\x1f\x8b\x08\x00gzip");
+    assert_eq!(resp.headers().get("content-encoding").unwrap(), "gzip");
+  }
 }
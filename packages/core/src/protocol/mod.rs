@@ -1,19 +1,60 @@
 mod bundle;
+mod cache;
+mod cors;
+mod fallback;
+mod header_rules;
+#[cfg(feature = "protocol-local")]
+mod hmr;
 mod http_ext;
 #[cfg(feature = "protocol-local")]
+mod hybrid;
+#[cfg(feature = "protocol-local")]
 mod local;
+mod stream;
 mod uri;
+mod warmup;
 
 use async_trait::async_trait;
 use std::borrow::Cow;
 
 pub type ProtocolResponse = http::Response<Cow<'static, [u8]>>;
 
+pub type StreamProtocolResponse = http::Response<BodyStream>;
+
 #[async_trait]
 pub trait Protocol: Send + Sync {
   async fn handle(&self, request: http::Request<Vec<u8>>) -> crate::Result<ProtocolResponse>;
 }
 
+/// Protocol handler which streams response bodies instead of reading them into memory.
+///
+/// [`BundleProtocol`] decompresses entries chunk by chunk while they are sent, so serving
+/// large assets doesn't hold them in memory at once. [`Protocol::handle`] remains available
+/// as a convenience for small responses.
+///
+/// Integrations only benefit when they hand the chunks to the webview as they come. The
+/// Tauri adapter can't, as Tauri protocol responses take the whole body, so it collects the
+/// stream into memory before responding.
+#[async_trait]
+pub trait StreamProtocol: Protocol {
+  async fn handle_stream(
+    &self,
+    request: http::Request<Vec<u8>>,
+  ) -> crate::Result<StreamProtocolResponse>;
+}
+
 pub use bundle::*;
+pub use cache::*;
+pub use cors::Cors;
+pub use fallback::*;
+pub use header_rules::*;
+#[cfg(feature = "protocol-local")]
+pub use hybrid::*;
 #[cfg(feature = "protocol-local")]
 pub use local::*;
+pub use stream::{BodyStream, collect_stream, once_stream};
+pub use uri::*;
+pub use warmup::Warmup;
+
+#[cfg(feature = "protocol-local")]
+pub use crate::http_config::HttpConfig;
@@ -0,0 +1,463 @@
+//! Streaming decompression of bundle entries.
+//!
+//! Entries are stored as a single LZ4 block, prefixed with the uncompressed size. The block is
+//! decoded sequentially while it is read, keeping only the last 64 KiB of output which later
+//! matches may refer to, so memory use doesn't grow with the size of the entry. Decoding stops
+//! as soon as the requested range has been produced.
+//!
+//! When the descriptor verifies data, the compressed bytes are hashed while they are read and
+//! checked against the checksum following them before the last chunk is yielded. Chunks
+//! yielded before are not verified yet, so a mismatch ends the stream with an error.
+
+use crate::VerifyOptions;
+use crate::checksum::checksum_hasher;
+use bytes::Bytes;
+use futures_util::Stream;
+use lz4_flex::block::DecompressError;
+use std::hash::Hasher;
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncReadExt, BufReader, ReadBuf, Take};
+use twox_hash::XxHash32;
+
+/// Stream of response body chunks.
+pub type BodyStream = Pin<Box<dyn Stream<Item = crate::Result<Bytes>> + Send + 'static>>;
+
+/// Size of chunks yielded by entry streams.
+pub(crate) const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Farthest offset an LZ4 match may refer back to.
+const WINDOW_SIZE: usize = u16::MAX as usize;
+
+/// Body stream yielding the given bytes at once.
+pub fn once_stream(data: impl Into<Bytes>) -> BodyStream {
+  let data = data.into();
+  if data.is_empty() {
+    return Box::pin(futures_util::stream::empty());
+  }
+  Box::pin(futures_util::stream::once(async move { Ok(data) }))
+}
+
+/// Collects all chunks of a body stream.
+pub async fn collect_stream(mut stream: BodyStream) -> crate::Result<Vec<u8>> {
+  use futures_util::StreamExt;
+
+  let mut buf = vec![];
+  while let Some(chunk) = stream.next().await {
+    buf.extend_from_slice(&chunk?);
+  }
+  Ok(buf)
+}
+
+/// Streams the decompressed bytes `start..=end` of an entry.
+///
+/// The reader must be positioned at the start of the entry data, which is `len` bytes long.
+/// The stream fails when the size prepended to the data is not `content_length`, as ranges
+/// and headers of the response are computed from the index.
+pub(crate) fn entry_stream<R>(
+  reader: R,
+  len: u64,
+  content_length: u64,
+  range: Option<(u64, u64)>,
+  verify: Option<VerifyOptions>,
+) -> BodyStream
+where
+  R: AsyncRead + Unpin + Send + 'static,
+{
+  let decoder = EntryDecoder::new(reader, len, content_length, range, verify);
+  Box::pin(futures_util::stream::try_unfold(
+    decoder,
+    |mut decoder| async move {
+      let chunk = decoder.next_chunk().await?;
+      Ok(chunk.map(|x| (x, decoder)))
+    },
+  ))
+}
+
+/// Reader hashing the bytes read through it.
+struct ChecksumReader<R> {
+  inner: R,
+  hasher: Option<XxHash32>,
+}
+
+impl<R: AsyncRead + Unpin> AsyncRead for ChecksumReader<R> {
+  fn poll_read(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+    buf: &mut ReadBuf<'_>,
+  ) -> Poll<io::Result<()>> {
+    let filled = buf.filled().len();
+    let this = &mut *self;
+    let result = Pin::new(&mut this.inner).poll_read(cx, buf);
+    if let Some(hasher) = &mut this.hasher {
+      hasher.write(&buf.filled()[filled..]);
+    }
+    result
+  }
+}
+
+enum State {
+  Size,
+  Token,
+  Literals { remaining: usize, match_len: usize },
+  Match { offset: usize, remaining: usize },
+  Done,
+}
+
+struct EntryDecoder<R: AsyncRead + Unpin> {
+  r: BufReader<Take<ChecksumReader<R>>>,
+  state: State,
+  /// Whether the data is checked against its checksum, which is done once decoding stops.
+  verified: bool,
+  /// Decoded bytes, starting at `buf_start` of the entry.
+  buf: Vec<u8>,
+  buf_start: u64,
+  /// Bytes before this position are already emitted.
+  emitted: u64,
+  size: u64,
+  /// Decompressed size of the entry in the index.
+  content_length: u64,
+  range: Option<(u64, u64)>,
+}
+
+impl<R: AsyncRead + Unpin> EntryDecoder<R> {
+  fn new(
+    r: R,
+    len: u64,
+    content_length: u64,
+    range: Option<(u64, u64)>,
+    verify: Option<VerifyOptions>,
+  ) -> Self {
+    let r = ChecksumReader {
+      inner: r,
+      hasher: verify.map(|x| checksum_hasher(x.data_checksum_seed)),
+    };
+    Self {
+      r: BufReader::new(r.take(len)),
+      state: State::Size,
+      verified: false,
+      buf: Vec::with_capacity(WINDOW_SIZE + CHUNK_SIZE),
+      buf_start: 0,
+      emitted: 0,
+      size: 0,
+      content_length,
+      range,
+    }
+  }
+
+  fn pos(&self) -> u64 {
+    self.buf_start + self.buf.len() as u64
+  }
+
+  /// Start and exclusive end of the bytes to emit.
+  fn bounds(&self) -> (u64, u64) {
+    match self.range {
+      Some((start, end)) => (start, (end + 1).min(self.size)),
+      None => (0, self.size),
+    }
+  }
+
+  fn pending(&self) -> u64 {
+    let (start, end) = self.bounds();
+    self.pos().min(end).saturating_sub(self.emitted.max(start))
+  }
+
+  async fn next_chunk(&mut self) -> crate::Result<Option<Bytes>> {
+    loop {
+      // stop decoding once the requested bytes are produced
+      if !matches!(self.state, State::Size) && self.pos() >= self.bounds().1 {
+        self.state = State::Done;
+      }
+      let done = matches!(self.state, State::Done);
+      if done && !self.verified {
+        self.verify().await?;
+        self.verified = true;
+      }
+      if self.pending() >= CHUNK_SIZE as u64 || (done && self.pending() > 0) {
+        return Ok(Some(self.emit()));
+      }
+      if done {
+        let (_, end) = self.bounds();
+        if self.pos() < end {
+          return Err(DecompressError::ExpectedAnotherByte.into());
+        }
+        return Ok(None);
+      }
+      self.step().await?;
+      self.trim();
+    }
+  }
+
+  /// Checks the data against the checksum following it, reading the rest of the data when
+  /// decoding stopped early.
+  async fn verify(&mut self) -> crate::Result<()> {
+    if self.r.get_ref().get_ref().hasher.is_none() {
+      return Ok(());
+    }
+    tokio::io::copy(&mut self.r, &mut tokio::io::sink()).await?;
+    let r = self.r.get_mut().get_mut();
+    let checksum = r.inner.read_u32().await?;
+    if r.hasher.as_ref().is_some_and(|x| x.finish_32() != checksum) {
+      return Err(crate::Error::ChecksumMismatch);
+    }
+    Ok(())
+  }
+
+  fn emit(&mut self) -> Bytes {
+    let (start, end) = self.bounds();
+    let from = (self.emitted.max(start) - self.buf_start) as usize;
+    let to = (self.pos().min(end) - self.buf_start) as usize;
+    self.emitted = self.pos();
+    Bytes::copy_from_slice(&self.buf[from..to])
+  }
+
+  /// Drops decoded bytes which are neither pending nor in the match window.
+  fn trim(&mut self) {
+    if self.buf.len() < WINDOW_SIZE * 2 {
+      return;
+    }
+    let (start, _) = self.bounds();
+    let keep_from = self.emitted.max(start).min(self.pos()) - self.buf_start;
+    let drain = (self.buf.len() - WINDOW_SIZE).min(keep_from as usize);
+    self.buf.drain(..drain);
+    self.buf_start += drain as u64;
+  }
+
+  async fn step(&mut self) -> crate::Result<()> {
+    match self.state {
+      State::Size => {
+        self.size = self.r.read_u32_le().await? as u64;
+        if self.size != self.content_length {
+          return Err(crate::Error::InvalidEntry(format!(
+            "decompressed size {} does not match the content length {}",
+            self.size, self.content_length
+          )));
+        }
+        self.state = State::Token;
+      }
+      State::Token => {
+        let token = match self.read_u8().await? {
+          Some(x) => x,
+          None => {
+            self.state = State::Done;
+            return Ok(());
+          }
+        };
+        let mut literals = (token >> 4) as usize;
+        if literals == 15 {
+          literals += self.read_len().await?;
+        }
+        self.state = State::Literals {
+          remaining: literals,
+          match_len: (token & 0xf) as usize,
+        };
+      }
+      State::Literals {
+        remaining,
+        match_len,
+      } if remaining > 0 => {
+        let available = self.r.fill_buf().await?;
+        if available.is_empty() {
+          return Err(DecompressError::LiteralOutOfBounds.into());
+        }
+        let n = available.len().min(remaining).min(CHUNK_SIZE);
+        self.buf.extend_from_slice(&available[..n]);
+        self.r.consume(n);
+        self.state = State::Literals {
+          remaining: remaining - n,
+          match_len,
+        };
+      }
+      State::Literals { match_len, .. } => {
+        // the last sequence of a block ends with literals
+        if self.r.fill_buf().await?.is_empty() {
+          self.state = State::Done;
+          return Ok(());
+        }
+        let offset = self.r.read_u16_le().await? as usize;
+        if offset == 0 {
+          return Err(DecompressError::OffsetZero.into());
+        }
+        if offset > self.buf.len() {
+          return Err(DecompressError::OffsetOutOfBounds.into());
+        }
+        let mut remaining = match_len + 4;
+        if match_len == 15 {
+          remaining += self.read_len().await?;
+        }
+        self.state = State::Match { offset, remaining };
+      }
+      State::Match { offset, remaining } => {
+        let n = remaining.min(CHUNK_SIZE);
+        let mut left = n;
+        while left > 0 {
+          // copies may overlap the bytes they produce, so copy at most `offset` at once
+          let len = left.min(offset);
+          let from = self.buf.len() - offset;
+          self.buf.extend_from_within(from..from + len);
+          left -= len;
+        }
+        self.state = if remaining > n {
+          State::Match {
+            offset,
+            remaining: remaining - n,
+          }
+        } else {
+          State::Token
+        };
+      }
+      State::Done => {}
+    }
+    if self.pos() > self.size {
+      return Err(
+        DecompressError::OutputTooSmall {
+          expected: self.pos() as usize,
+          actual: self.size as usize,
+        }
+        .into(),
+      );
+    }
+    Ok(())
+  }
+
+  async fn read_u8(&mut self) -> crate::Result<Option<u8>> {
+    let available = self.r.fill_buf().await?;
+    match available.first() {
+      Some(&x) => {
+        self.r.consume(1);
+        Ok(Some(x))
+      }
+      None => Ok(None),
+    }
+  }
+
+  async fn read_len(&mut self) -> crate::Result<usize> {
+    let mut len = 0;
+    loop {
+      let x = self
+        .read_u8()
+        .await?
+        .ok_or(DecompressError::ExpectedAnotherByte)?;
+      len += x as usize;
+      if x != u8::MAX {
+        return Ok(len);
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::io::Cursor;
+
+  fn data(len: usize) -> Vec<u8> {
+    // compressible but not trivially so
+    (0..len)
+      .map(|i| ((i / 7) as u8).wrapping_mul(31) ^ (i % 13) as u8)
+      .collect()
+  }
+
+  async fn decode(data: &[u8], range: Option<(u64, u64)>) -> crate::Result<Vec<u8>> {
+    let compressed = lz4_flex::compress_prepend_size(data);
+    let len = compressed.len() as u64;
+    collect_stream(entry_stream(
+      Cursor::new(compressed),
+      len,
+      data.len() as u64,
+      range,
+      None,
+    ))
+    .await
+  }
+
+  #[tokio::test]
+  async fn decode_entry() {
+    for len in [0, 1, 100, CHUNK_SIZE, 300_000] {
+      let data = data(len);
+      assert_eq!(decode(&data, None).await.unwrap(), data);
+    }
+    // incompressible data is stored as long literals
+    let random = (0..200_000u32)
+      .map(|x| (x.wrapping_mul(2654435761) >> 13) as u8)
+      .collect::<Vec<_>>();
+    assert_eq!(decode(&random, None).await.unwrap(), random);
+    // long runs are stored as long overlapping matches
+    let zeros = vec![0u8; 500_000];
+    assert_eq!(decode(&zeros, None).await.unwrap(), zeros);
+  }
+
+  #[tokio::test]
+  async fn decode_range() {
+    let data = data(400_000);
+    assert_eq!(decode(&data, Some((0, 9))).await.unwrap(), data[..10]);
+    assert_eq!(
+      decode(&data, Some((250_000, 330_000))).await.unwrap(),
+      data[250_000..=330_000]
+    );
+    assert_eq!(
+      decode(&data, Some((399_990, 399_999))).await.unwrap(),
+      data[399_990..]
+    );
+  }
+
+  #[tokio::test]
+  async fn truncated() {
+    let compressed = lz4_flex::compress_prepend_size(&data(10_000));
+    let len = compressed.len() as u64 - 10;
+    let stream = entry_stream(Cursor::new(compressed), len, 10_000, None, None);
+    assert!(collect_stream(stream).await.is_err());
+  }
+
+  #[tokio::test]
+  async fn size_mismatch() {
+    let compressed = lz4_flex::compress_prepend_size(&data(10_000));
+    let len = compressed.len() as u64;
+    let stream = entry_stream(Cursor::new(compressed), len, 20_000, None, None);
+    assert!(matches!(
+      collect_stream(stream).await,
+      Err(crate::Error::InvalidEntry(_))
+    ));
+  }
+
+  #[tokio::test]
+  async fn verify_checksum() {
+    let data = data(200_000);
+    let compressed = lz4_flex::compress_prepend_size(&data);
+    let len = compressed.len() as u64;
+    let checksum = crate::checksum::make_checksum(0, &compressed);
+    let mut file = compressed.clone();
+    file.extend_from_slice(&checksum.to_be_bytes());
+    let verify = Some(VerifyOptions::new());
+    for range in [None, Some((0, 9))] {
+      let stream = entry_stream(
+        Cursor::new(file.clone()),
+        len,
+        data.len() as u64,
+        range,
+        verify,
+      );
+      assert!(collect_stream(stream).await.is_ok());
+    }
+
+    let mut file = compressed.clone();
+    file.extend_from_slice(&(checksum ^ 1).to_be_bytes());
+    // a mismatch is found even when the range ends early
+    for range in [None, Some((0, 9))] {
+      let stream = entry_stream(
+        Cursor::new(file.clone()),
+        len,
+        data.len() as u64,
+        range,
+        verify,
+      );
+      assert!(matches!(
+        collect_stream(stream).await,
+        Err(crate::Error::ChecksumMismatch)
+      ));
+    }
+    let stream = entry_stream(Cursor::new(file), len, data.len() as u64, None, None);
+    assert_eq!(collect_stream(stream).await.unwrap(), data);
+  }
+}
@@ -1,11 +1,67 @@
 use http::Uri;
+use std::collections::HashMap;
+use std::sync::Arc;
 
+/// Resolves which bundle and file a request URI points to.
+///
+/// Used by [`super::BundleProtocol`], see [`super::BundleProtocolBuilder::uri_resolver`].
 pub trait UriResolver: Send + Sync {
+  /// Resolves the bundle name, or `None` when the URI doesn't point to a bundle.
   fn resolve_bundle(&self, uri: &Uri) -> Option<String>;
+
+  /// Resolves the file path in the bundle (e.g. `/index.html`).
   fn resolve_path(&self, uri: &Uri) -> String;
+
+  /// Resolves the path routed by the app, used to match routing fallback rules.
+  ///
+  /// Defaults to the path of the URI.
+  fn resolve_route(&self, uri: &Uri) -> String {
+    uri.path().to_string()
+  }
+}
+
+impl<T: UriResolver + ?Sized> UriResolver for Arc<T> {
+  fn resolve_bundle(&self, uri: &Uri) -> Option<String> {
+    (**self).resolve_bundle(uri)
+  }
+
+  fn resolve_path(&self, uri: &Uri) -> String {
+    (**self).resolve_path(uri)
+  }
+
+  fn resolve_route(&self, uri: &Uri) -> String {
+    (**self).resolve_route(uri)
+  }
 }
 
-#[derive(Default)]
+/// Decodes a request path into a file path, resolving directories to `index.html`.
+fn to_file_path(path: &str) -> String {
+  let mut path = percent_encoding::percent_decode(path.as_bytes())
+    .decode_utf8_lossy()
+    .to_string();
+  if !path.starts_with('/') {
+    path.insert(0, '/');
+  }
+  if path.ends_with('/') {
+    path.push_str("index.html");
+    return path;
+  }
+  if let Some(last) = path.rsplit('/').next()
+    && !last.is_empty()
+    && !last.contains('.')
+  {
+    path.push_str("/index.html");
+  }
+  path
+}
+
+/// Resolves the bundle from the first label of the host.
+///
+/// ```text
+/// bundle://app/index.html     → bundle "app", file "/index.html"
+/// https://app.wvb/index.html  → bundle "app", file "/index.html"
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
 pub struct DefaultUriResolver;
 
 impl UriResolver for DefaultUriResolver {
@@ -17,18 +73,277 @@ impl UriResolver for DefaultUriResolver {
   }
 
   fn resolve_path(&self, uri: &Uri) -> String {
-    let mut path = percent_encoding::percent_decode(uri.path().as_bytes())
-      .decode_utf8_lossy()
-      .to_string();
-    if path.ends_with('/') {
-      path.push_str("index.html");
-      return path;
+    to_file_path(uri.path())
+  }
+}
+
+/// Resolves the bundle from the first segment of the path.
+///
+/// ```text
+/// bundle://localhost/app/index.html  → bundle "app", file "/index.html"
+/// ```
+#[derive(Debug, Default, Clone, Copy)]
+pub struct PathUriResolver;
+
+impl PathUriResolver {
+  fn split(uri: &Uri) -> (Option<&str>, &str) {
+    let path = uri.path().trim_start_matches('/');
+    match path.split_once('/') {
+      Some((bundle, _)) => (Some(bundle), &path[bundle.len()..]),
+      None if path.is_empty() => (None, "/"),
+      None => (Some(path), "/"),
     }
-    if let Some(last) = path.rsplit('/').next() {
-      if !last.is_empty() && !last.contains('.') {
-        path.push_str("/index.html");
-      }
+  }
+}
+
+impl UriResolver for PathUriResolver {
+  fn resolve_bundle(&self, uri: &Uri) -> Option<String> {
+    Self::split(uri).0.map(|x| {
+      percent_encoding::percent_decode(x.as_bytes())
+        .decode_utf8_lossy()
+        .to_string()
+    })
+  }
+
+  fn resolve_path(&self, uri: &Uri) -> String {
+    to_file_path(Self::split(uri).1)
+  }
+
+  fn resolve_route(&self, uri: &Uri) -> String {
+    Self::split(uri).1.to_string()
+  }
+}
+
+/// Resolves every request to a single bundle.
+///
+/// ```text
+/// bundle://localhost/index.html  → bundle "app", file "/index.html"
+/// ```
+#[derive(Debug, Clone)]
+pub struct SingleBundleUriResolver {
+  bundle: String,
+}
+
+impl SingleBundleUriResolver {
+  pub fn new(bundle: impl Into<String>) -> Self {
+    Self {
+      bundle: bundle.into(),
+    }
+  }
+}
+
+impl UriResolver for SingleBundleUriResolver {
+  fn resolve_bundle(&self, _uri: &Uri) -> Option<String> {
+    Some(self.bundle.clone())
+  }
+
+  fn resolve_path(&self, uri: &Uri) -> String {
+    to_file_path(uri.path())
+  }
+}
+
+/// Resolves the bundle by looking up the host in a mapping table.
+///
+/// Requests to hosts which are not in the table don't resolve to any bundle.
+///
+/// ```
+/// # #[cfg(feature = "protocol")]
+/// # {
+/// use wvb::protocol::MappingUriResolver;
+/// use std::collections::HashMap;
+///
+/// let resolver = MappingUriResolver::new(HashMap::from([
+///   ("app.example.com".to_string(), "app".to_string()),
+///   ("admin.example.com".to_string(), "admin".to_string()),
+/// ]));
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MappingUriResolver {
+  mapping: HashMap<String, String>,
+}
+
+impl MappingUriResolver {
+  /// Creates a new resolver with the given host to bundle name mapping.
+  pub fn new<T: Into<HashMap<String, String>>>(mapping: T) -> Self {
+    Self {
+      mapping: mapping.into(),
     }
-    path
+  }
+}
+
+impl UriResolver for MappingUriResolver {
+  fn resolve_bundle(&self, uri: &Uri) -> Option<String> {
+    uri.host().and_then(|x| self.mapping.get(x)).cloned()
+  }
+
+  fn resolve_path(&self, uri: &Uri) -> String {
+    to_file_path(uri.path())
+  }
+}
+
+/// Resolves the bundle from mount points on the path, with the longest matching prefix winning.
+///
+/// The mount prefix is stripped from the path, so each bundle is served as if it were at the
+/// root. Bundles mounted under a prefix should be built with that prefix as their base path,
+/// so references to their own assets resolve to the mount. Hosts are ignored.
+///
+/// ```text
+/// bundle://localhost/index.html                   → bundle "shell", file "/index.html"
+/// bundle://localhost/features/billing/app.js      → bundle "billing", file "/app.js"
+/// ```
+///
+/// ```
+/// # #[cfg(feature = "protocol")]
+/// # {
+/// use wvb::protocol::MountUriResolver;
+///
+/// let resolver = MountUriResolver::new()
+///   .mount("/", "shell")
+///   .mount("/features/billing", "billing");
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct MountUriResolver {
+  mounts: Vec<(String, String)>,
+}
+
+impl MountUriResolver {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Mount `bundle` at the path `prefix`.
+  #[must_use]
+  pub fn mount(mut self, prefix: impl Into<String>, bundle: impl Into<String>) -> Self {
+    let prefix = prefix.into().trim_end_matches('/').to_string();
+    self.mounts.retain(|(x, _)| *x != prefix);
+    self.mounts.push((prefix, bundle.into()));
+    // longest prefix wins
+    self.mounts.sort_by(|(a, _), (b, _)| b.len().cmp(&a.len()));
+    self
+  }
+
+  /// Mount points as `(prefix, bundle)`, longest prefix first.
+  pub fn mounts(&self) -> &[(String, String)] {
+    &self.mounts
+  }
+
+  fn find<'a>(&'a self, uri: &'a Uri) -> Option<(&'a str, &'a str)> {
+    let path = uri.path();
+    self.mounts.iter().find_map(|(prefix, bundle)| {
+      let rest = path.strip_prefix(prefix.as_str())?;
+      match rest {
+        "" => Some((bundle.as_str(), "/")),
+        _ if rest.starts_with('/') => Some((bundle.as_str(), rest)),
+        _ => None,
+      }
+    })
+  }
+}
+
+impl UriResolver for MountUriResolver {
+  fn resolve_bundle(&self, uri: &Uri) -> Option<String> {
+    self.find(uri).map(|(bundle, _)| bundle.to_string())
+  }
+
+  fn resolve_path(&self, uri: &Uri) -> String {
+    to_file_path(self.find(uri).map(|(_, x)| x).unwrap_or(uri.path()))
+  }
+
+  fn resolve_route(&self, uri: &Uri) -> String {
+    self
+      .find(uri)
+      .map(|(_, x)| x)
+      .unwrap_or(uri.path())
+      .to_string()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn uri(value: &str) -> Uri {
+    value.parse().unwrap()
+  }
+
+  #[test]
+  fn default_resolver() {
+    let resolver = DefaultUriResolver;
+    let u = uri("https://app.wvb/settings");
+    assert_eq!(resolver.resolve_bundle(&u), Some("app".to_string()));
+    assert_eq!(resolver.resolve_path(&u), "/settings/index.html");
+    assert_eq!(resolver.resolve_path(&uri("bundle://app/")), "/index.html");
+    assert_eq!(
+      resolver.resolve_path(&uri("bundle://app/a%20b.js")),
+      "/a b.js"
+    );
+  }
+
+  #[test]
+  fn path_resolver() {
+    let resolver = PathUriResolver;
+    let u = uri("bundle://localhost/app/assets/main.js");
+    assert_eq!(resolver.resolve_bundle(&u), Some("app".to_string()));
+    assert_eq!(resolver.resolve_path(&u), "/assets/main.js");
+    assert_eq!(resolver.resolve_route(&u), "/assets/main.js");
+
+    let u = uri("bundle://localhost/app");
+    assert_eq!(resolver.resolve_bundle(&u), Some("app".to_string()));
+    assert_eq!(resolver.resolve_path(&u), "/index.html");
+    assert_eq!(resolver.resolve_route(&u), "/");
+
+    let u = uri("bundle://localhost/app/settings/");
+    assert_eq!(resolver.resolve_path(&u), "/settings/index.html");
+    assert_eq!(resolver.resolve_route(&u), "/settings/");
+
+    assert_eq!(resolver.resolve_bundle(&uri("bundle://localhost/")), None);
+  }
+
+  #[test]
+  fn single_bundle_resolver() {
+    let resolver = SingleBundleUriResolver::new("app");
+    let u = uri("bundle://localhost/index.html");
+    assert_eq!(resolver.resolve_bundle(&u), Some("app".to_string()));
+    assert_eq!(resolver.resolve_path(&u), "/index.html");
+  }
+
+  #[test]
+  fn mapping_resolver() {
+    let resolver = MappingUriResolver::new(HashMap::from([(
+      "app.example.com".to_string(),
+      "app".to_string(),
+    )]));
+    assert_eq!(
+      resolver.resolve_bundle(&uri("https://app.example.com/")),
+      Some("app".to_string())
+    );
+    assert_eq!(
+      resolver.resolve_bundle(&uri("https://other.example.com/")),
+      None
+    );
+  }
+
+  #[test]
+  fn mount_resolver() {
+    let resolver = MountUriResolver::new()
+      .mount("/", "shell")
+      .mount("/features/billing/", "billing");
+    let u = uri("bundle://localhost/features/billing/assets/app.js");
+    assert_eq!(resolver.resolve_bundle(&u), Some("billing".to_string()));
+    assert_eq!(resolver.resolve_path(&u), "/assets/app.js");
+
+    let u = uri("bundle://localhost/features/billing");
+    assert_eq!(resolver.resolve_bundle(&u), Some("billing".to_string()));
+    assert_eq!(resolver.resolve_path(&u), "/index.html");
+    assert_eq!(resolver.resolve_route(&u), "/");
+
+    let u = uri("bundle://localhost/features/billing-v2/index.html");
+    assert_eq!(resolver.resolve_bundle(&u), Some("shell".to_string()));
+    assert_eq!(resolver.resolve_path(&u), "/features/billing-v2/index.html");
+
+    let resolver = MountUriResolver::new().mount("/app", "app");
+    assert_eq!(resolver.resolve_bundle(&uri("bundle://localhost/")), None);
   }
 }
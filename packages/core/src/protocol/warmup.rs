@@ -0,0 +1,335 @@
+use crate::protocol::cache::EntryCache;
+use crate::source::BundleSource;
+use crate::{BundleDescriptor, BundleMetadata};
+use bytes::Bytes;
+use std::collections::HashSet;
+use std::sync::Arc;
+use tokio::task::JoinSet;
+
+/// Maximum number of entries decompressed at once.
+const CONCURRENCY: usize = 8;
+
+/// Entries decompressed into the [`EntryCache`] right after a bundle is loaded, so the first
+/// paint doesn't read and decompress each entry on request.
+///
+/// HTML entries in the list are scanned for the scripts, stylesheets and preloads they
+/// reference (`<script src>` and `<link rel="stylesheet|modulepreload|preload" href>`), which
+/// are warmed up too.
+///
+/// Bundles can declare their own list in metadata with [`BundleMetadata::WARMUP`], which
+/// takes precedence over the warmup of the protocol.
+///
+/// # Example
+///
+/// ```
+/// # #[cfg(feature = "protocol")]
+/// # {
+/// use wvb::protocol::Warmup;
+///
+/// let warmup = Warmup::index_html().entry("/assets/fonts/inter.woff2");
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Warmup {
+  entries: Vec<String>,
+}
+
+impl Warmup {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Warmup of `/index.html` and the entries it references.
+  pub fn index_html() -> Self {
+    Self::new().entry("/index.html")
+  }
+
+  /// Add an entry to warm up.
+  pub fn entry(mut self, path: impl Into<String>) -> Self {
+    self.entries.push(path.into());
+    self
+  }
+
+  pub fn entries(&self) -> &[String] {
+    &self.entries
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entries.is_empty()
+  }
+
+  /// Reads the warmup declared in bundle metadata.
+  ///
+  /// Returns `None` when the metadata declares no entries.
+  pub fn from_metadata(metadata: &BundleMetadata) -> Option<Self> {
+    let entries = metadata.warmup();
+    if entries.is_empty() {
+      None
+    } else {
+      Some(Self { entries })
+    }
+  }
+}
+
+/// Decompresses the warmup entries of a bundle into the cache.
+///
+/// Returns the number of entries added to the cache.
+pub(crate) async fn warmup(
+  source: Arc<BundleSource>,
+  cache: Arc<EntryCache>,
+  bundle_name: String,
+  version: String,
+  descriptor: Arc<BundleDescriptor>,
+  warmup: Warmup,
+) -> crate::Result<usize> {
+  let index = descriptor.index();
+  let mut seen = HashSet::new();
+  let mut paths = warmup
+    .entries
+    .into_iter()
+    .filter(|x| index.contains_path(x) && seen.insert(x.clone()))
+    .collect::<Vec<_>>();
+  let mut warmed = 0;
+
+  // references are only known once HTML entries are read, so they are read first
+  let html = paths
+    .iter()
+    .filter(|x| {
+      index
+        .get_entry(x)
+        .is_some_and(|x| x.content_type().starts_with("text/html"))
+    })
+    .cloned()
+    .collect::<Vec<_>>();
+  for path in &html {
+    let data = match cache.peek(&bundle_name, &version, path) {
+      Some(x) => x,
+      None => {
+        warmed += 1;
+        read(&source, &cache, &bundle_name, &version, &descriptor, path).await?
+      }
+    };
+    for reference in html_references(&String::from_utf8_lossy(&data), path) {
+      if index.contains_path(&reference) && seen.insert(reference.clone()) {
+        paths.push(reference);
+      }
+    }
+  }
+
+  let mut tasks = JoinSet::new();
+  for path in paths {
+    if html.contains(&path) || cache.peek(&bundle_name, &version, &path).is_some() {
+      continue;
+    }
+    if tasks.len() >= CONCURRENCY
+      && let Some(result) = tasks.join_next().await
+    {
+      result.map_err(std::io::Error::other)??;
+    }
+    let (source, cache, bundle_name, version, descriptor) = (
+      source.clone(),
+      cache.clone(),
+      bundle_name.clone(),
+      version.clone(),
+      descriptor.clone(),
+    );
+    tasks.spawn(async move {
+      read(&source, &cache, &bundle_name, &version, &descriptor, &path)
+        .await
+        .map(|_| ())
+    });
+    warmed += 1;
+  }
+  while let Some(result) = tasks.join_next().await {
+    result.map_err(std::io::Error::other)??;
+  }
+  Ok(warmed)
+}
+
+async fn read(
+  source: &BundleSource,
+  cache: &EntryCache,
+  bundle_name: &str,
+  version: &str,
+  descriptor: &BundleDescriptor,
+  path: &str,
+) -> crate::Result<Bytes> {
+  let reader = source.reader_for(bundle_name, version).await?;
+  let data = Bytes::from(
+    descriptor
+      .async_get_data(reader, path)
+      .await?
+      .unwrap_or_default(),
+  );
+  cache.insert(bundle_name, version, path, data.clone());
+  Ok(data)
+}
+
+/// Returns the entry paths of the scripts, stylesheets and preloads referenced by an HTML
+/// document.
+fn html_references(html: &str, html_path: &str) -> Vec<String> {
+  // lowercasing ASCII keeps byte offsets, so positions are valid in both
+  let lower = html.to_ascii_lowercase();
+  let mut references = vec![];
+  let mut pos = 0;
+  while let Some(start) = lower[pos..].find('<') {
+    let start = pos + start + 1;
+    let end = match lower[start..].find('>') {
+      Some(x) => start + x,
+      None => break,
+    };
+    pos = end + 1;
+    let tag = &html[start..end];
+    let (name, attrs) = tag
+      .split_once(|c: char| c.is_ascii_whitespace())
+      .unwrap_or((tag, ""));
+    let url = if name.eq_ignore_ascii_case("script") {
+      attr(attrs, "src")
+    } else if name.eq_ignore_ascii_case("link")
+      && attr(attrs, "rel").is_some_and(|rel| {
+        rel.split_ascii_whitespace().any(|x| {
+          ["stylesheet", "modulepreload", "preload"]
+            .iter()
+            .any(|y| x.eq_ignore_ascii_case(y))
+        })
+      })
+    {
+      attr(attrs, "href")
+    } else {
+      None
+    };
+    if let Some(path) = url.and_then(|x| resolve_reference(html_path, x)) {
+      references.push(path);
+    }
+  }
+  references
+}
+
+/// Returns the value of an attribute in the attributes of a tag.
+fn attr<'a>(attrs: &'a str, name: &str) -> Option<&'a str> {
+  let mut rest = attrs;
+  loop {
+    rest = rest.trim_start();
+    if rest.is_empty() {
+      return None;
+    }
+    let key_end = rest
+      .find(|c: char| c == '=' || c == '/' || c.is_ascii_whitespace())
+      .unwrap_or(rest.len());
+    let (key, after) = rest.split_at(key_end);
+    let after = after.trim_start();
+    let (value, next) = match after.strip_prefix('=') {
+      Some(after) => {
+        let after = after.trim_start();
+        match after.chars().next() {
+          Some(quote @ ('"' | '\'')) => {
+            let inner = &after[1..];
+            let end = inner.find(quote).unwrap_or(inner.len());
+            (&inner[..end], &inner[(end + 1).min(inner.len())..])
+          }
+          _ => {
+            let end = after
+              .find(|c: char| c.is_ascii_whitespace())
+              .unwrap_or(after.len());
+            after.split_at(end)
+          }
+        }
+      }
+      None => ("", after),
+    };
+    if !key.is_empty() && key.eq_ignore_ascii_case(name) {
+      return Some(value);
+    }
+    // skip stray characters such as the `/` of self-closing tags
+    rest = if next.len() == rest.len() {
+      &rest[1..]
+    } else {
+      next
+    };
+  }
+}
+
+/// Resolves a URL referenced by an HTML entry into an entry path.
+///
+/// Returns `None` for URLs to other origins and data URLs.
+fn resolve_reference(html_path: &str, url: &str) -> Option<String> {
+  let url = url.trim();
+  let url = url.split(['?', '#']).next().unwrap_or_default();
+  if url.is_empty()
+    || url.starts_with("//")
+    || url.split('/').next().is_some_and(|x| x.contains(':'))
+  {
+    return None;
+  }
+  let url = percent_encoding::percent_decode(url.as_bytes()).decode_utf8_lossy();
+  let joined = match url.strip_prefix('/') {
+    Some(x) => x.to_string(),
+    None => {
+      let base = html_path.rsplit_once('/').map(|x| x.0).unwrap_or_default();
+      format!("{base}/{url}")
+    }
+  };
+  let mut segments: Vec<&str> = vec![];
+  for segment in joined.split('/') {
+    match segment {
+      "" | "." => {}
+      ".." => {
+        segments.pop();
+      }
+      _ => segments.push(segment),
+    }
+  }
+  Some(format!("/{}", segments.join("/")))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn references() {
+    let html = r#"<!doctype html>
+<html>
+  <head>
+    <script type="module" crossorigin src="/assets/index-abc.js"></script>
+    <link rel="modulepreload" href="./assets/vendor.js?v=1">
+    <LINK REL=stylesheet HREF=assets/index.css />
+    <link rel="icon" href="/favicon.ico">
+    <link rel="preload" as="font" href="../fonts/inter.woff2" crossorigin>
+    <script src="https://cdn.example.com/lib.js"></script>
+    <script>console.log("<inline>")</script>
+  </head>
+</html>"#;
+    assert_eq!(
+      html_references(html, "/docs/index.html"),
+      vec![
+        "/assets/index-abc.js",
+        "/docs/assets/vendor.js",
+        "/docs/assets/index.css",
+        "/fonts/inter.woff2",
+      ]
+    );
+  }
+
+  #[test]
+  fn resolve() {
+    assert_eq!(
+      resolve_reference("/index.html", "app%20main.js"),
+      Some("/app main.js".to_string())
+    );
+    assert_eq!(resolve_reference("/index.html", "data:text/css,a"), None);
+    assert_eq!(resolve_reference("/index.html", "//cdn.com/a.js"), None);
+    assert_eq!(resolve_reference("/index.html", "#top"), None);
+  }
+
+  #[test]
+  fn from_metadata() {
+    let mut metadata = BundleMetadata::new();
+    assert_eq!(Warmup::from_metadata(&metadata), None);
+    metadata.set_warmup(["/index.html", "/app.js"]);
+    assert_eq!(
+      Warmup::from_metadata(&metadata),
+      Some(Warmup::index_html().entry("/app.js"))
+    );
+  }
+}
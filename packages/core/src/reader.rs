@@ -1,3 +1,10 @@
+use crate::checksum::CHECKSUM_LEN;
+use crate::index::Index;
+use std::io::Read;
+
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncReadExt};
+
 pub trait Reader<T> {
   fn read(&mut self) -> crate::Result<T>;
 }
@@ -6,3 +13,145 @@ pub trait Reader<T> {
 pub trait AsyncReader<T> {
   fn read(&mut self) -> impl std::future::Future<Output = crate::Result<T>>;
 }
+
+/// Limits for reading bundles from untrusted sources, such as downloaded bundles.
+///
+/// Readers created with limits reject bundles whose index or metadata section is larger than
+/// the limits, and validate every index entry before any data is read: entries must lie
+/// within the data section without overlapping each other, and must not be larger than the
+/// limits, compressed or decompressed.
+///
+/// # Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use wvb::{BundleReader, ReadLimits};
+///
+/// let limits = ReadLimits::new().max_entry_size(16 * 1024 * 1024);
+/// let reader = BundleReader::new_with_limits(Cursor::new(vec![]), limits);
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ReadLimits {
+  /// Maximum size of the index section in bytes.
+  pub max_index_size: u32,
+  /// Maximum size of the metadata section in bytes.
+  pub max_metadata_size: u32,
+  /// Maximum compressed size of an entry in bytes.
+  pub max_entry_size: u64,
+  /// Maximum decompressed size of an entry in bytes.
+  pub max_decompressed_size: u64,
+}
+
+impl Default for ReadLimits {
+  fn default() -> Self {
+    Self {
+      max_index_size: 16 * 1024 * 1024,
+      max_metadata_size: 1024 * 1024,
+      max_entry_size: 256 * 1024 * 1024,
+      max_decompressed_size: 256 * 1024 * 1024,
+    }
+  }
+}
+
+impl ReadLimits {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn max_index_size(mut self, size: u32) -> Self {
+    self.max_index_size = size;
+    self
+  }
+
+  pub fn max_metadata_size(mut self, size: u32) -> Self {
+    self.max_metadata_size = size;
+    self
+  }
+
+  pub fn max_entry_size(mut self, size: u64) -> Self {
+    self.max_entry_size = size;
+    self
+  }
+
+  pub fn max_decompressed_size(mut self, size: u64) -> Self {
+    self.max_decompressed_size = size;
+    self
+  }
+
+  /// Validates the entries of an index against a data section of `data_len` bytes.
+  pub(crate) fn validate_index(&self, index: &Index, data_len: u64) -> crate::Result<()> {
+    let mut ranges = Vec::with_capacity(index.entries().len());
+    for (path, entry) in index.entries() {
+      check_limit("entry", entry.len(), Some(self.max_entry_size))?;
+      check_limit(
+        "decompressed entry",
+        entry.content_length(),
+        Some(self.max_decompressed_size),
+      )?;
+      // each entry is followed by the checksum of its data
+      let end = entry
+        .offset()
+        .checked_add(entry.len())
+        .and_then(|x| x.checked_add(CHECKSUM_LEN as u64));
+      match end {
+        Some(end) if end <= data_len => ranges.push((entry.offset(), end, path)),
+        _ => {
+          return Err(crate::Error::invalid_entry(format!(
+            "`{path}` is out of the data section"
+          )));
+        }
+      }
+    }
+    ranges.sort_unstable();
+    if let Some(pair) = ranges.windows(2).find(|x| x[1].0 < x[0].1) {
+      return Err(crate::Error::invalid_entry(format!(
+        "`{}` overlaps `{}`",
+        pair[1].2, pair[0].2
+      )));
+    }
+    Ok(())
+  }
+}
+
+/// Returns an error if `size` bytes of a section exceed the limit.
+pub(crate) fn check_limit(name: &str, size: u64, limit: Option<u64>) -> crate::Result<()> {
+  match limit {
+    Some(limit) if size > limit => Err(crate::Error::ReadLimitExceeded {
+      name: name.to_string(),
+      size,
+      limit,
+    }),
+    _ => Ok(()),
+  }
+}
+
+/// Initial capacity of buffers read by [`read_len`].
+const INITIAL_CAPACITY: u64 = 64 * 1024;
+
+/// Reads exactly `len` bytes.
+///
+/// Lengths read from a bundle can't be trusted, so the buffer grows with the data actually
+/// read instead of allocating `len` bytes upfront.
+pub(crate) fn read_len<R: Read>(r: R, len: u64) -> crate::Result<Vec<u8>> {
+  let mut buf = Vec::with_capacity(len.min(INITIAL_CAPACITY) as usize);
+  r.take(len).read_to_end(&mut buf)?;
+  if (buf.len() as u64) < len {
+    return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+  }
+  Ok(buf)
+}
+
+/// Asynchronously reads exactly `len` bytes, see [`read_len`].
+#[cfg(feature = "async")]
+pub(crate) async fn async_read_len<R: AsyncRead + Unpin>(r: R, len: u64) -> crate::Result<Vec<u8>> {
+  let mut buf = Vec::with_capacity(len.min(INITIAL_CAPACITY) as usize);
+  r.take(len).read_to_end(&mut buf).await?;
+  if (buf.len() as u64) < len {
+    return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+  }
+  Ok(buf)
+}
+
+/// Maximum memory claimed while decoding a section, so the length prefixes of untrusted
+/// sections can't trigger huge allocations.
+pub(crate) const DECODE_LIMIT: usize = 256 * 1024 * 1024;
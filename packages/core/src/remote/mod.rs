@@ -48,9 +48,22 @@
 //! - `Webview-Bundle-Version`: Version string
 //! - `Webview-Bundle-Integrity`: Optional integrity hash for verification
 //! - `Webview-Bundle-Signature`: Optional digital signature
+//! - `Webview-Bundle-Rollout-Percentage`: Optional percentage (0-100) of installs to roll out to
+//! - `Webview-Bundle-Min-App-Version`: Optional minimum host app version
+//! - `Webview-Bundle-Compatible-App-Version`: Optional semver range of compatible host app versions
+//! - `Webview-Bundle-Required-Capabilities`: Optional comma-separated native capabilities
+//!
+//! ## Server
+//!
+//! With the `remote-server` feature, [`RemoteServer`] implements the server side of the
+//! protocol on top of a bundle source directory, deciding deployed versions (optionally
+//! per release channel) from its `manifest.json`.
 
-mod http;
 mod remote;
+#[cfg(feature = "remote-server")]
+mod server;
 
-pub use http::*;
+pub use crate::http_config::HttpConfig;
 pub use remote::*;
+#[cfg(feature = "remote-server")]
+pub use server::*;
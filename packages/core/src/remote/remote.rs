@@ -1,5 +1,5 @@
 use crate::remote::HttpConfig;
-use crate::{Bundle, BundleReader, Reader};
+use crate::{Bundle, BundleReader, ReadLimits, Reader};
 use futures_util::StreamExt;
 use http::{StatusCode, header, uri::Uri};
 use serde::{Deserialize, Serialize};
@@ -31,6 +31,16 @@ pub struct RemoteBundleInfo {
   pub signature: Option<String>,
   /// Last modified date from the remote server.
   pub last_modified: Option<String>,
+  /// Percentage (0-100) of installs the bundle is rolled out to.
+  ///
+  /// `None` means the bundle is rolled out to every install.
+  pub rollout_percentage: Option<u8>,
+  /// Minimum host app version (semver) required to install the bundle.
+  pub min_app_version: Option<String>,
+  /// Semver range of host app versions the bundle is compatible with.
+  pub compatible_app_version: Option<String>,
+  /// Native capabilities the bundle requires from the host app.
+  pub required_capabilities: Vec<String>,
 }
 
 /// Error string representation for remote operations.
@@ -43,7 +53,7 @@ pub struct RemoteError {
 type OnDownload = dyn Fn(u64, u64, String) + Send + Sync + 'static;
 
 /// Configuration for remote operations.
-#[derive(Default, Clone)]
+#[derive(Clone)]
 #[non_exhaustive]
 pub struct RemoteConfig {
   /// Base URL of the remote server where bundles are hosted.
@@ -55,6 +65,21 @@ pub struct RemoteConfig {
   pub on_download: Option<Arc<OnDownload>>,
   /// Optional HTTP client configuration.
   pub http: Option<HttpConfig>,
+  /// Limits which downloaded bundles are validated against, unlimited when `None`.
+  ///
+  /// Defaults to [`ReadLimits::default`].
+  pub read_limits: Option<ReadLimits>,
+}
+
+impl Default for RemoteConfig {
+  fn default() -> Self {
+    Self {
+      endpoint: String::new(),
+      on_download: None,
+      http: None,
+      read_limits: Some(ReadLimits::default()),
+    }
+  }
 }
 
 #[derive(Default, Clone)]
@@ -76,6 +101,15 @@ impl RemoteBuilder {
     self
   }
 
+  /// Set the limits which downloaded bundles are validated against.
+  ///
+  /// Downloaded bundles are validated against [`ReadLimits::default`] unless set, pass `None`
+  /// to skip validation for trusted remotes.
+  pub fn read_limits(mut self, limits: impl Into<Option<ReadLimits>>) -> Self {
+    self.config.read_limits = limits.into();
+    self
+  }
+
   /// Set download progress callback.
   pub fn on_download<F>(mut self, on_download: F) -> Self
   where
@@ -209,6 +243,25 @@ impl Remote {
     let last_modified = get_header_value(headers, header::LAST_MODIFIED);
     let integrity = get_header_value(headers, "webview-bundle-integrity");
     let signature = get_header_value(headers, "webview-bundle-signature");
+    let rollout_percentage = get_header_value(headers, "webview-bundle-rollout-percentage")
+      .map(|x| match x.trim().parse::<u8>() {
+        Ok(percentage) if percentage <= 100 => Ok(percentage),
+        _ => Err(crate::Error::invalid_remote_bundle(
+          "\"webview-bundle-rollout-percentage\" header is not a percentage",
+        )),
+      })
+      .transpose()?;
+    let min_app_version = get_header_value(headers, "webview-bundle-min-app-version");
+    let compatible_app_version = get_header_value(headers, "webview-bundle-compatible-app-version");
+    let required_capabilities = get_header_value(headers, "webview-bundle-required-capabilities")
+      .map(|x| {
+        x.split(',')
+          .map(|x| x.trim())
+          .filter(|x| !x.is_empty())
+          .map(String::from)
+          .collect::<Vec<_>>()
+      })
+      .unwrap_or_default();
     Ok(RemoteBundleInfo {
       name,
       version,
@@ -216,6 +269,10 @@ impl Remote {
       integrity,
       signature,
       last_modified,
+      rollout_percentage,
+      min_app_version,
+      compatible_app_version,
+      required_capabilities,
     })
   }
 
@@ -258,7 +315,11 @@ impl Remote {
       }
     }
     let mut reader = Cursor::new(&data);
-    let bundle = Reader::<Bundle>::read(&mut BundleReader::new(&mut reader))?;
+    let mut reader = match self.config.read_limits {
+      Some(limits) => BundleReader::new_with_limits(&mut reader, limits),
+      None => BundleReader::new(&mut reader),
+    };
+    let bundle = Reader::<Bundle>::read(&mut reader)?;
     Ok((info, bundle, data))
   }
 }
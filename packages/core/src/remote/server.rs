@@ -0,0 +1,751 @@
+use crate::remote::ListRemoteBundleInfo;
+use crate::source::{BundleManifestData, BundleManifestEntry, BundleManifestMetadata};
+use crate::{EXTENSION, MANIFEST_FILENAME, MIME_TYPE};
+use http::{Method, Request, Response, StatusCode, header};
+use std::net::{SocketAddr, ToSocketAddrs};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+/// Delay before accepting connections again after accepting failed, so persistent errors such
+/// as running out of file descriptors don't keep workers busy.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_millis(100);
+
+/// Builder for creating a `RemoteServer`.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(feature = "remote-server")]
+/// # {
+/// use wvb::remote::RemoteServer;
+///
+/// let server = RemoteServer::builder()
+///     .dir("./bundles")
+///     .allow_other_versions(true)
+///     .build();
+/// # }
+/// ```
+#[derive(Debug, Default, Clone)]
+#[non_exhaustive]
+pub struct RemoteServerBuilder {
+  dir: PathBuf,
+  manifest_filepath: Option<PathBuf>,
+  allow_other_versions: bool,
+  workers: Option<usize>,
+}
+
+impl RemoteServerBuilder {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Set the directory to serve bundles from.
+  ///
+  /// The directory has the same layout as a `BundleSource` directory.
+  #[must_use]
+  pub fn dir(mut self, dir: impl Into<PathBuf>) -> Self {
+    self.dir = dir.into();
+    self
+  }
+
+  /// Set the manifest file path. Relative paths are resolved against the served directory.
+  pub fn manifest_filepath(mut self, filepath: impl Into<PathBuf>) -> Self {
+    self.manifest_filepath = Some(filepath.into());
+    self
+  }
+
+  /// Allow downloading versions other than the deployed one via `GET /bundles/{name}/{version}`.
+  pub fn allow_other_versions(mut self, allow: bool) -> Self {
+    self.allow_other_versions = allow;
+    self
+  }
+
+  /// Set the number of threads serving requests in [`RemoteServer::listen`].
+  ///
+  /// Defaults to the available parallelism.
+  pub fn workers(mut self, workers: usize) -> Self {
+    self.workers = Some(workers.max(1));
+    self
+  }
+
+  pub fn build(self) -> RemoteServer {
+    let dir = self.dir;
+    let manifest_filepath = self
+      .manifest_filepath
+      .map(|x| match x.is_absolute() {
+        true => x,
+        false => dir.join(x),
+      })
+      .unwrap_or_else(|| dir.join(MANIFEST_FILENAME));
+    let workers = self.workers.unwrap_or_else(|| {
+      std::thread::available_parallelism()
+        .map(|x| x.get())
+        .unwrap_or(4)
+    });
+    RemoteServer {
+      dir,
+      manifest_filepath,
+      allow_other_versions: self.allow_other_versions,
+      workers,
+    }
+  }
+}
+
+/// Server side of the remote bundle HTTP protocol.
+///
+/// `RemoteServer` serves a directory laid out like a `BundleSource`. The `manifest.json`
+/// of the directory decides which version of each bundle is deployed, optionally per
+/// release channel:
+///
+/// ```json
+/// {
+///   "manifestVersion": 1,
+///   "entries": {
+///     "app": {
+///       "versions": {
+///         "1.0.0": { "integrity": "..." },
+///         "1.1.0": { "integrity": "..." }
+///       },
+///       "currentVersion": "1.0.0",
+///       "channels": { "beta": "1.1.0" }
+///     }
+///   }
+/// }
+/// ```
+///
+/// The manifest is read on every request, so deployments can be changed by rewriting it
+/// without restarting the server.
+///
+/// Use [`RemoteServer::handle`] to plug the server into an existing HTTP stack, or
+/// [`RemoteServer::listen`] to run a standalone server.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(feature = "remote-server")]
+/// # {
+/// use wvb::remote::RemoteServer;
+///
+/// let server = RemoteServer::builder().dir("./bundles").build();
+/// let handle = server.listen("127.0.0.1:8080").unwrap();
+/// println!("Listening on {}", handle.url());
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct RemoteServer {
+  dir: PathBuf,
+  manifest_filepath: PathBuf,
+  allow_other_versions: bool,
+  workers: usize,
+}
+
+impl RemoteServer {
+  pub fn builder() -> RemoteServerBuilder {
+    RemoteServerBuilder::new()
+  }
+
+  /// Returns the directory bundles are served from.
+  pub fn dir(&self) -> &Path {
+    &self.dir
+  }
+
+  /// Handles a request of the remote bundle HTTP protocol.
+  ///
+  /// - `GET /bundles`
+  /// - `HEAD /bundles/{name}`
+  /// - `GET /bundles/{name}`
+  /// - `GET /bundles/{name}/{version}`
+  ///
+  /// The `channel` query parameter selects the deployed version of a release channel,
+  /// falling back to the current version when the channel has no deployment.
+  #[cfg_attr(feature = "tracing", tracing::instrument(
+    skip_all,
+    fields(request.method = request.method().to_string(), request.uri = request.uri().to_string()),
+    err(level = "error")
+  ))]
+  pub async fn handle(&self, request: Request<Vec<u8>>) -> crate::Result<Response<Vec<u8>>> {
+    let channel = get_query_value(request.uri(), "channel");
+    let segments = request
+      .uri()
+      .path()
+      .trim_matches('/')
+      .split('/')
+      .map(|x| urlencoding::decode(x).map(|x| x.into_owned()))
+      .collect::<Result<Vec<_>, _>>();
+    let segments = match segments {
+      Ok(x) => x,
+      Err(_) => return status_response(StatusCode::BAD_REQUEST),
+    };
+    if segments.first().map(|x| x.as_str()) != Some("bundles") {
+      return status_response(StatusCode::NOT_FOUND);
+    }
+    let method = request.method();
+    match &segments[1..] {
+      [] if method == Method::GET => self.list_bundles(channel.as_deref()).await,
+      [name] if method == Method::GET || method == Method::HEAD => {
+        let manifest = self.load_manifest().await?;
+        let version = manifest
+          .entries
+          .get(name)
+          .map(|entry| deployed_version(entry, channel.as_deref()).to_string());
+        match version {
+          Some(version) => {
+            self
+              .bundle_response(&manifest, name, &version, method == Method::HEAD)
+              .await
+          }
+          None => status_response(StatusCode::NOT_FOUND),
+        }
+      }
+      [name, version] if method == Method::GET || method == Method::HEAD => {
+        if !self.allow_other_versions {
+          return status_response(StatusCode::FORBIDDEN);
+        }
+        let manifest = self.load_manifest().await?;
+        self
+          .bundle_response(&manifest, name, version, method == Method::HEAD)
+          .await
+      }
+      [] | [_] | [_, _] => status_response(StatusCode::METHOD_NOT_ALLOWED),
+      _ => status_response(StatusCode::NOT_FOUND),
+    }
+  }
+
+  /// Starts listening on the given address and serves requests on a fixed pool of worker
+  /// threads, sharing one async runtime.
+  ///
+  /// The server stops when [`RemoteServerHandle::shutdown`] is called or the returned handle
+  /// is dropped.
+  pub fn listen(self, addr: impl ToSocketAddrs) -> crate::Result<RemoteServerHandle> {
+    let http_server = Arc::new(tiny_http::Server::http(addr).map_err(crate::Error::Generic)?);
+    let addr = http_server
+      .server_addr()
+      .to_ip()
+      .ok_or_else(|| crate::Error::generic("server is not listening on an ip address"))?;
+    let runtime = Arc::new(
+      tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .build()?,
+    );
+    let stopped = Arc::new(AtomicBool::new(false));
+    let server = Arc::new(self);
+    let threads = (0..server.workers)
+      .map(|_| {
+        let http_server = http_server.clone();
+        let runtime = runtime.clone();
+        let stopped = stopped.clone();
+        let server = server.clone();
+        std::thread::spawn(move || {
+          loop {
+            match http_server.recv() {
+              Ok(request) => serve_request(&server, &runtime, request),
+              // `recv` also fails when the server is unblocked on shutdown
+              Err(_) if stopped.load(Ordering::Acquire) => break,
+              Err(_e) => {
+                #[cfg(feature = "tracing")]
+                tracing::warn!(error = %_e, "fail to accept remote server connection");
+                std::thread::sleep(ACCEPT_ERROR_BACKOFF);
+              }
+            }
+          }
+        })
+      })
+      .collect();
+    Ok(RemoteServerHandle {
+      addr,
+      http_server,
+      stopped,
+      threads,
+    })
+  }
+
+  async fn list_bundles(&self, channel: Option<&str>) -> crate::Result<Response<Vec<u8>>> {
+    let manifest = self.load_manifest().await?;
+    let mut bundles = manifest
+      .entries
+      .iter()
+      .map(|(name, entry)| ListRemoteBundleInfo {
+        name: name.to_string(),
+        version: deployed_version(entry, channel).to_string(),
+      })
+      .collect::<Vec<_>>();
+    bundles.sort_by(|a, b| a.name.cmp(&b.name));
+    let body = serde_json::to_vec(&bundles)?;
+    let response = Response::builder()
+      .status(StatusCode::OK)
+      .header(header::CONTENT_TYPE, "application/json")
+      .header(header::CONTENT_LENGTH, body.len())
+      .body(body)?;
+    Ok(response)
+  }
+
+  async fn bundle_response(
+    &self,
+    manifest: &BundleManifestData,
+    bundle_name: &str,
+    version: &str,
+    head: bool,
+  ) -> crate::Result<Response<Vec<u8>>> {
+    let metadata = match manifest
+      .entries
+      .get(bundle_name)
+      .and_then(|entry| entry.versions.get(version))
+    {
+      Some(x) => x,
+      None => return status_response(StatusCode::NOT_FOUND),
+    };
+    let filepath = match self.get_filepath(bundle_name, version) {
+      Some(x) => x,
+      None => return status_response(StatusCode::NOT_FOUND),
+    };
+    let (content_length, body) = if head {
+      match tokio::fs::metadata(&filepath).await {
+        Ok(x) => (x.len(), vec![]),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+          return status_response(StatusCode::NOT_FOUND);
+        }
+        Err(e) => return Err(e.into()),
+      }
+    } else {
+      match tokio::fs::read(&filepath).await {
+        Ok(x) => (x.len() as u64, x),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+          return status_response(StatusCode::NOT_FOUND);
+        }
+        Err(e) => return Err(e.into()),
+      }
+    };
+    let response = Response::builder()
+      .status(StatusCode::OK)
+      .header(header::CONTENT_TYPE, MIME_TYPE)
+      .header(header::CONTENT_LENGTH, content_length)
+      .header("webview-bundle-name", bundle_name)
+      .header("webview-bundle-version", version);
+    let response = with_metadata_headers(response, metadata).body(body)?;
+    Ok(response)
+  }
+
+  async fn load_manifest(&self) -> crate::Result<BundleManifestData> {
+    match tokio::fs::read(&self.manifest_filepath).await {
+      Ok(raw) => Ok(serde_json::from_slice(&raw)?),
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Default::default()),
+      Err(e) => Err(e.into()),
+    }
+  }
+
+  fn get_filepath(&self, bundle_name: &str, version: &str) -> Option<PathBuf> {
+    if !is_safe_segment(bundle_name) || !is_safe_segment(version) {
+      return None;
+    }
+    let filename = format!("{bundle_name}_{version}.{EXTENSION}");
+    Some(self.dir.join(bundle_name).join(filename))
+  }
+}
+
+/// Handle of a running `RemoteServer`.
+///
+/// Dropping the handle stops the server.
+pub struct RemoteServerHandle {
+  addr: SocketAddr,
+  http_server: Arc<tiny_http::Server>,
+  stopped: Arc<AtomicBool>,
+  threads: Vec<JoinHandle<()>>,
+}
+
+impl RemoteServerHandle {
+  /// Returns the address the server is listening on.
+  pub fn addr(&self) -> SocketAddr {
+    self.addr
+  }
+
+  /// Returns the base url of the server, which can be used as a `Remote` endpoint.
+  pub fn url(&self) -> String {
+    format!("http://{}", self.addr)
+  }
+
+  /// Stops the server and waits for the worker threads to finish their current requests.
+  pub fn shutdown(mut self) {
+    self.stop();
+  }
+
+  fn stop(&mut self) {
+    self.stopped.store(true, Ordering::Release);
+    // each call unblocks a single worker waiting for a request
+    for _ in 0..self.threads.len() {
+      self.http_server.unblock();
+    }
+    for thread in self.threads.drain(..) {
+      let _ = thread.join();
+    }
+  }
+}
+
+impl Drop for RemoteServerHandle {
+  fn drop(&mut self) {
+    self.stop();
+  }
+}
+
+impl std::fmt::Debug for RemoteServerHandle {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    write!(f, "RemoteServerHandle {{ addr: {:?} }}", self.addr)
+  }
+}
+
+fn serve_request(
+  server: &RemoteServer,
+  runtime: &tokio::runtime::Runtime,
+  mut request: tiny_http::Request,
+) {
+  let response =
+    into_http_request(&mut request).and_then(|req| runtime.block_on(server.handle(req)));
+  let response = match response {
+    Ok(x) => x,
+    Err(e) => {
+      #[cfg(feature = "tracing")]
+      tracing::error!(error = %e, "fail to handle remote server request");
+      let body =
+        serde_json::to_vec(&serde_json::json!({ "message": e.to_string() })).unwrap_or_default();
+      Response::builder()
+        .status(StatusCode::INTERNAL_SERVER_ERROR)
+        .header(header::CONTENT_TYPE, "application/json")
+        .header(header::CONTENT_LENGTH, body.len())
+        .body(body)
+        .unwrap()
+    }
+  };
+  let _ = request.respond(into_tiny_response(response));
+}
+
+fn into_http_request(request: &mut tiny_http::Request) -> crate::Result<Request<Vec<u8>>> {
+  let mut body = vec![];
+  request.as_reader().read_to_end(&mut body)?;
+  let mut builder = Request::builder()
+    .method(request.method().as_str())
+    .uri(request.url());
+  for h in request.headers() {
+    builder = builder.header(h.field.as_str().as_str(), h.value.as_str());
+  }
+  Ok(builder.body(body)?)
+}
+
+fn into_tiny_response(
+  response: Response<Vec<u8>>,
+) -> tiny_http::Response<std::io::Cursor<Vec<u8>>> {
+  let status = response.status().as_u16();
+  let headers = response
+    .headers()
+    .iter()
+    .filter_map(|(name, value)| tiny_http::Header::from_bytes(name.as_str(), value.as_bytes()).ok())
+    .collect::<Vec<_>>();
+  let body = response.into_body();
+  let data_length = body.len();
+  let mut resp = tiny_http::Response::new(
+    status.into(),
+    vec![],
+    std::io::Cursor::new(body),
+    Some(data_length),
+    None,
+  );
+  // A `Content-Length` header overrides the data length, which keeps the length of
+  // `HEAD` responses.
+  for header in headers {
+    resp.add_header(header);
+  }
+  resp
+}
+
+fn deployed_version<'a>(entry: &'a BundleManifestEntry, channel: Option<&str>) -> &'a str {
+  channel
+    .and_then(|c| entry.channels.get(c))
+    .unwrap_or(&entry.current_version)
+}
+
+fn with_metadata_headers(
+  mut builder: http::response::Builder,
+  metadata: &BundleManifestMetadata,
+) -> http::response::Builder {
+  if let Some(etag) = &metadata.etag {
+    builder = builder.header(header::ETAG, etag);
+  }
+  if let Some(last_modified) = &metadata.last_modified {
+    builder = builder.header(header::LAST_MODIFIED, last_modified);
+  }
+  if let Some(integrity) = &metadata.integrity {
+    builder = builder.header("webview-bundle-integrity", integrity);
+  }
+  if let Some(signature) = &metadata.signature {
+    builder = builder.header("webview-bundle-signature", signature);
+  }
+  if let Some(rollout_percentage) = metadata.rollout_percentage {
+    builder = builder.header(
+      "webview-bundle-rollout-percentage",
+      u32::from(rollout_percentage),
+    );
+  }
+  if let Some(min_app_version) = &metadata.min_app_version {
+    builder = builder.header("webview-bundle-min-app-version", min_app_version);
+  }
+  if let Some(compatible_app_version) = &metadata.compatible_app_version {
+    builder = builder.header(
+      "webview-bundle-compatible-app-version",
+      compatible_app_version,
+    );
+  }
+  if !metadata.required_capabilities.is_empty() {
+    builder = builder.header(
+      "webview-bundle-required-capabilities",
+      metadata.required_capabilities.join(","),
+    );
+  }
+  builder
+}
+
+fn is_safe_segment(segment: &str) -> bool {
+  !segment.is_empty() && segment != "." && segment != ".." && !segment.contains(['/', '\\', '\0'])
+}
+
+fn get_query_value(uri: &http::Uri, key: &str) -> Option<String> {
+  uri.query()?.split('&').find_map(|pair| {
+    let (k, v) = pair.split_once('=').unwrap_or((pair, ""));
+    if k != key {
+      return None;
+    }
+    urlencoding::decode(v).ok().map(|x| x.into_owned())
+  })
+}
+
+fn status_response(status: StatusCode) -> crate::Result<Response<Vec<u8>>> {
+  let response = Response::builder().status(status).body(vec![])?;
+  Ok(response)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::remote::Remote;
+  use crate::testing::TempDir;
+  use crate::{Bundle, BundleEntry, BundleWriter, Writer};
+  use std::collections::HashMap;
+  use std::io::Cursor;
+
+  fn write_bundle(dir: &Path, name: &str, version: &str) {
+    let mut builder = Bundle::builder();
+    builder.insert_entry(
+      "/index.html",
+      BundleEntry::new(format!("<h1>{version}</h1>").as_bytes(), "text/html", None),
+    );
+    let bundle = builder.build().unwrap();
+    let mut data = vec![];
+    BundleWriter::new(Cursor::new(&mut data))
+      .write(&bundle)
+      .unwrap();
+    let filepath = dir.join(name).join(format!("{name}_{version}.wvb"));
+    std::fs::create_dir_all(filepath.parent().unwrap()).unwrap();
+    std::fs::write(filepath, data).unwrap();
+  }
+
+  fn setup() -> TempDir {
+    let temp_dir = TempDir::new();
+    write_bundle(temp_dir.dir(), "app", "1.0.0");
+    write_bundle(temp_dir.dir(), "app", "1.1.0");
+    let manifest = BundleManifestData {
+      manifest_version: Default::default(),
+      entries: HashMap::from([(
+        "app".to_string(),
+        BundleManifestEntry {
+          versions: HashMap::from([
+            (
+              "1.0.0".to_string(),
+              BundleManifestMetadata {
+                integrity: Some("sha256:abc".to_string()),
+                ..Default::default()
+              },
+            ),
+            ("1.1.0".to_string(), BundleManifestMetadata::default()),
+          ]),
+          current_version: "1.0.0".to_string(),
+          channels: HashMap::from([("beta".to_string(), "1.1.0".to_string())]),
+        },
+      )]),
+    };
+    std::fs::write(
+      temp_dir.dir().join(MANIFEST_FILENAME),
+      serde_json::to_vec(&manifest).unwrap(),
+    )
+    .unwrap();
+    temp_dir
+  }
+
+  fn get_remote(handle: &RemoteServerHandle) -> Remote {
+    Remote::builder().endpoint(handle.url()).build().unwrap()
+  }
+
+  #[tokio::test]
+  async fn list_bundles() {
+    let temp_dir = setup();
+    let handle = RemoteServer::builder()
+      .dir(temp_dir.dir())
+      .build()
+      .listen("127.0.0.1:0")
+      .unwrap();
+    let remote = get_remote(&handle);
+    let bundles = remote.list_bundles(None).await.unwrap();
+    assert_eq!(
+      bundles,
+      vec![ListRemoteBundleInfo {
+        name: "app".to_string(),
+        version: "1.0.0".to_string(),
+      }]
+    );
+    let bundles = remote
+      .list_bundles(Some(&"beta".to_string()))
+      .await
+      .unwrap();
+    assert_eq!(bundles[0].version, "1.1.0");
+    let bundles = remote
+      .list_bundles(Some(&"unknown".to_string()))
+      .await
+      .unwrap();
+    assert_eq!(bundles[0].version, "1.0.0");
+  }
+
+  #[tokio::test]
+  async fn get_current_info() {
+    let temp_dir = setup();
+    let handle = RemoteServer::builder()
+      .dir(temp_dir.dir())
+      .build()
+      .listen("127.0.0.1:0")
+      .unwrap();
+    let remote = get_remote(&handle);
+    let info = remote.get_current_info("app", None).await.unwrap();
+    assert_eq!(info.name, "app");
+    assert_eq!(info.version, "1.0.0");
+    assert_eq!(info.integrity.as_deref(), Some("sha256:abc"));
+    let info = remote
+      .get_current_info("app", Some(&"beta".to_string()))
+      .await
+      .unwrap();
+    assert_eq!(info.version, "1.1.0");
+    let err = remote
+      .get_current_info("not_found", None)
+      .await
+      .unwrap_err();
+    assert!(matches!(err, crate::Error::RemoteBundleNotFound));
+  }
+
+  #[tokio::test]
+  async fn download() {
+    let temp_dir = setup();
+    let handle = RemoteServer::builder()
+      .dir(temp_dir.dir())
+      .build()
+      .listen("127.0.0.1:0")
+      .unwrap();
+    let remote = get_remote(&handle);
+    let (info, bundle, _) = remote.download("app", None).await.unwrap();
+    assert_eq!(info.version, "1.0.0");
+    assert_eq!(
+      bundle.get_data("/index.html").unwrap().unwrap(),
+      b"<h1>1.0.0</h1>"
+    );
+    let (info, bundle, _) = remote
+      .download("app", Some(&"beta".to_string()))
+      .await
+      .unwrap();
+    assert_eq!(info.version, "1.1.0");
+    assert_eq!(
+      bundle.get_data("/index.html").unwrap().unwrap(),
+      b"<h1>1.1.0</h1>"
+    );
+
+    // downloads are validated against the default limits, unless opted out
+    assert_eq!(
+      crate::remote::RemoteConfig::default().read_limits,
+      Some(crate::ReadLimits::default())
+    );
+    let remote = Remote::builder()
+      .endpoint(handle.url())
+      .read_limits(crate::ReadLimits::new().max_index_size(10))
+      .build()
+      .unwrap();
+    let err = remote.download("app", None).await.unwrap_err();
+    assert!(matches!(err, crate::Error::ReadLimitExceeded { .. }));
+    let remote = Remote::builder()
+      .endpoint(handle.url())
+      .read_limits(None)
+      .build()
+      .unwrap();
+    assert!(remote.download("app", None).await.is_ok());
+  }
+
+  #[tokio::test]
+  async fn download_version() {
+    let temp_dir = setup();
+    let handle = RemoteServer::builder()
+      .dir(temp_dir.dir())
+      .build()
+      .listen("127.0.0.1:0")
+      .unwrap();
+    let remote = get_remote(&handle);
+    let err = remote.download_version("app", "1.1.0").await.unwrap_err();
+    assert!(matches!(err, crate::Error::RemoteForbidden));
+
+    let handle = RemoteServer::builder()
+      .dir(temp_dir.dir())
+      .allow_other_versions(true)
+      .build()
+      .listen("127.0.0.1:0")
+      .unwrap();
+    let remote = get_remote(&handle);
+    let (info, _, _) = remote.download_version("app", "1.1.0").await.unwrap();
+    assert_eq!(info.version, "1.1.0");
+    let err = remote.download_version("app", "2.0.0").await.unwrap_err();
+    assert!(matches!(err, crate::Error::RemoteBundleNotFound));
+  }
+
+  #[tokio::test]
+  async fn shutdown() {
+    let temp_dir = setup();
+    let handle = RemoteServer::builder()
+      .dir(temp_dir.dir())
+      .workers(2)
+      .build()
+      .listen("127.0.0.1:0")
+      .unwrap();
+    let remote = get_remote(&handle);
+    let requests = (0..4).map(|_| remote.list_bundles(None));
+    for result in futures_util::future::join_all(requests).await {
+      assert_eq!(result.unwrap().len(), 1);
+    }
+    let url = handle.url();
+    handle.shutdown();
+    // a new client, as pooled connections would wait for a response until timing out
+    let remote = Remote::builder().endpoint(url).build().unwrap();
+    assert!(remote.list_bundles(None).await.is_err());
+  }
+
+  #[tokio::test]
+  async fn reject_path_traversal() {
+    let temp_dir = setup();
+    let server = RemoteServer::builder()
+      .dir(temp_dir.dir())
+      .allow_other_versions(true)
+      .build();
+    let resp = server
+      .handle(
+        Request::builder()
+          .uri("http://localhost/bundles/app/..%2F..%2Fsecret")
+          .method("GET")
+          .body(vec![])
+          .unwrap(),
+      )
+      .await
+      .unwrap();
+    assert_eq!(resp.status(), 404);
+  }
+}
@@ -0,0 +1,208 @@
+use crate::BundleMetadata;
+use crate::source::BundleManifestMetadata;
+use semver::{Version, VersionReq};
+use std::collections::HashSet;
+use std::fmt;
+
+/// Description of the native host app which loads bundles.
+///
+/// Bundle versions declaring compatibility constraints are checked against the host
+/// before they can become current.
+///
+/// # Example
+///
+/// ```no_run
+/// # #[cfg(feature = "source")]
+/// # {
+/// use wvb::source::HostInfo;
+///
+/// let host = HostInfo::new()
+///     .version(semver::Version::new(1, 2, 0))
+///     .capability("fs")
+///     .capability("notification");
+/// # }
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct HostInfo {
+  version: Option<Version>,
+  capabilities: HashSet<String>,
+}
+
+impl HostInfo {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Set the version of the host app.
+  #[must_use]
+  pub fn version(mut self, version: Version) -> Self {
+    self.version = Some(version);
+    self
+  }
+
+  /// Add a native capability provided by the host app (e.g. a Tauri command).
+  #[must_use]
+  pub fn capability(mut self, capability: impl Into<String>) -> Self {
+    self.capabilities.insert(capability.into());
+    self
+  }
+
+  /// Add native capabilities provided by the host app.
+  #[must_use]
+  pub fn capabilities<I, S>(mut self, capabilities: I) -> Self
+  where
+    I: IntoIterator<Item = S>,
+    S: Into<String>,
+  {
+    self
+      .capabilities
+      .extend(capabilities.into_iter().map(Into::into));
+    self
+  }
+
+  pub fn get_version(&self) -> Option<&Version> {
+    self.version.as_ref()
+  }
+
+  pub fn has_capability(&self, capability: &str) -> bool {
+    self.capabilities.contains(capability)
+  }
+}
+
+/// Compatibility constraints of a bundle version on the host app.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct BundleCompatibility {
+  /// Semver range of host app versions the bundle is compatible with.
+  pub app_version: Option<VersionReq>,
+  /// Native capabilities the bundle requires from the host app.
+  pub capabilities: Vec<String>,
+}
+
+impl BundleCompatibility {
+  /// Parse constraints from their string representation.
+  pub fn parse(app_version: Option<&str>, capabilities: &[String]) -> crate::Result<Self> {
+    let app_version = app_version
+      .map(VersionReq::parse)
+      .transpose()
+      .map_err(crate::Error::generic)?;
+    Ok(Self {
+      app_version,
+      capabilities: capabilities.to_vec(),
+    })
+  }
+
+  /// Returns `true` if the bundle has no constraints.
+  pub fn is_empty(&self) -> bool {
+    self.app_version.is_none() && self.capabilities.is_empty()
+  }
+
+  /// Check constraints against the host app.
+  ///
+  /// The app version constraint is skipped when the host version is unknown.
+  pub fn check(&self, host: &HostInfo) -> Result<(), Incompatibility> {
+    if let (Some(req), Some(version)) = (&self.app_version, &host.version)
+      && !req.matches(version)
+    {
+      return Err(Incompatibility::AppVersion {
+        required: req.clone(),
+        actual: version.clone(),
+      });
+    }
+    let missing = self
+      .capabilities
+      .iter()
+      .filter(|x| !host.has_capability(x))
+      .cloned()
+      .collect::<Vec<_>>();
+    if !missing.is_empty() {
+      return Err(Incompatibility::MissingCapabilities(missing));
+    }
+    Ok(())
+  }
+}
+
+impl TryFrom<&BundleManifestMetadata> for BundleCompatibility {
+  type Error = crate::Error;
+
+  fn try_from(value: &BundleManifestMetadata) -> Result<Self, Self::Error> {
+    Self::parse(
+      value.compatible_app_version.as_deref(),
+      &value.required_capabilities,
+    )
+  }
+}
+
+impl TryFrom<&BundleMetadata> for BundleCompatibility {
+  type Error = crate::Error;
+
+  fn try_from(value: &BundleMetadata) -> Result<Self, Self::Error> {
+    Self::parse(
+      value.compatible_app_version(),
+      &value.required_capabilities(),
+    )
+  }
+}
+
+/// Reason a bundle version is not compatible with the host app.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Incompatibility {
+  /// Host app version is out of the supported range.
+  AppVersion {
+    required: VersionReq,
+    actual: Version,
+  },
+  /// Host app does not provide the required capabilities.
+  MissingCapabilities(Vec<String>),
+}
+
+impl fmt::Display for Incompatibility {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::AppVersion { required, actual } => {
+        write!(f, "app version {actual} does not match \"{required}\"")
+      }
+      Self::MissingCapabilities(capabilities) => {
+        write!(f, "missing capabilities: {}", capabilities.join(", "))
+      }
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn check() {
+    let host = HostInfo::new()
+      .version(Version::new(1, 2, 0))
+      .capabilities(["fs", "notification"]);
+    let compat = BundleCompatibility::parse(Some(">=1.1, <2"), &["fs".to_string()]).unwrap();
+    assert!(compat.check(&host).is_ok());
+    let compat = BundleCompatibility::parse(Some(">=1.3"), &[]).unwrap();
+    assert!(matches!(
+      compat.check(&host),
+      Err(Incompatibility::AppVersion { .. })
+    ));
+    let compat =
+      BundleCompatibility::parse(None, &["fs".to_string(), "clipboard".to_string()]).unwrap();
+    assert_eq!(
+      compat.check(&host),
+      Err(Incompatibility::MissingCapabilities(vec![
+        "clipboard".to_string()
+      ]))
+    );
+    assert!(BundleCompatibility::default().check(&host).is_ok());
+  }
+
+  #[test]
+  fn unknown_host_version() {
+    let compat = BundleCompatibility::parse(Some(">=2"), &[]).unwrap();
+    assert!(compat.check(&HostInfo::new()).is_ok());
+  }
+
+  #[test]
+  fn invalid_app_version() {
+    assert!(BundleCompatibility::parse(Some("not a range"), &[]).is_err());
+  }
+}
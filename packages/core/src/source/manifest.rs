@@ -19,6 +19,18 @@ pub struct BundleManifestMetadata {
   pub integrity: Option<String>,
   pub signature: Option<String>,
   pub last_modified: Option<String>,
+  /// Percentage (0-100) of installs the version is rolled out to.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub rollout_percentage: Option<u8>,
+  /// Minimum host app version required to install the version.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub min_app_version: Option<String>,
+  /// Semver range of host app versions the version is compatible with.
+  #[serde(default, skip_serializing_if = "Option::is_none")]
+  pub compatible_app_version: Option<String>,
+  /// Native capabilities the version requires from the host app.
+  #[serde(default, skip_serializing_if = "Vec::is_empty")]
+  pub required_capabilities: Vec<String>,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
@@ -26,6 +38,11 @@ pub struct BundleManifestMetadata {
 pub struct BundleManifestEntry {
   pub versions: HashMap<String, BundleManifestMetadata>,
   pub current_version: String,
+  /// Current version for each release channel, keyed by channel name.
+  ///
+  /// Only used when the directory is served by a remote server.
+  #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+  pub channels: HashMap<String, String>,
 }
 
 #[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
@@ -36,7 +53,8 @@ pub struct BundleManifestData {
   pub entries: HashMap<String, BundleManifestEntry>,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 #[non_exhaustive]
 pub struct ListBundleManifestItem {
   pub name: String,
@@ -191,6 +209,7 @@ impl BundleManifest<ReadWrite> {
       .or_insert_with(|| BundleManifestEntry {
         versions: HashMap::from([(version.to_string(), metadata.clone())]),
         current_version: version.to_string(),
+        channels: HashMap::new(),
       });
     Ok(inserted)
   }
@@ -347,6 +366,10 @@ mod tests {
       integrity: None,
       signature: None,
       last_modified: None,
+      rollout_percentage: None,
+      min_app_version: None,
+      compatible_app_version: None,
+      required_capabilities: vec![],
     };
     let inserted = manifest
       .insert_entry("app", "1.2.0", metadata.clone())
@@ -375,6 +398,10 @@ mod tests {
       integrity: None,
       signature: None,
       last_modified: None,
+      rollout_percentage: None,
+      min_app_version: None,
+      compatible_app_version: None,
+      required_capabilities: vec![],
     };
     let inserted = manifest
       .insert_entry("vite", "1.0.0", metadata.clone())
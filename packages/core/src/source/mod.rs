@@ -65,10 +65,21 @@
 //!   }
 //! }
 //! ```
+//!
+//! ## Compatibility
+//!
+//! A version can declare the host app versions (`compatibleAppVersion`, a semver range) and
+//! native capabilities (`requiredCapabilities`) it needs, in its manifest entry or in the
+//! metadata section of the bundle file (see `BundleMetadata::COMPATIBLE_APP_VERSION`). When
+//! the source is built with a [`HostInfo`], incompatible remote versions never become current:
+//! `write_remote_bundle` and `update_version` fail with `Error::BundleIncompatible`, and
+//! `load_version` falls back to the builtin version.
 
+mod compatibility;
 mod manifest;
 mod source;
 mod utils;
 
+pub use compatibility::*;
 pub use manifest::*;
 pub use source::*;
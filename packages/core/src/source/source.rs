@@ -1,14 +1,15 @@
 use crate::source::{
-  BundleManifest, BundleManifestMetadata, ListBundleManifestItem, ReadOnly, ReadWrite, utils,
+  BundleCompatibility, BundleManifest, BundleManifestMetadata, HostInfo, ListBundleManifestItem,
+  ReadOnly, ReadWrite, utils,
 };
 use crate::{
   AsyncBundleReader, AsyncBundleWriter, AsyncReader, AsyncWriter, Bundle, BundleDescriptor,
-  EXTENSION, MANIFEST_FILENAME,
+  BundleMetadata, EXTENSION, MANIFEST_FILENAME, VerifyOptions,
 };
 use dashmap::DashMap;
 use serde::{Deserialize, Serialize};
 use std::path::{Path, PathBuf};
-use std::sync::Arc;
+use std::sync::{Arc, RwLock};
 use tokio::fs::File;
 use tokio::sync::OnceCell;
 
@@ -25,7 +26,8 @@ pub enum BundleSourceKind {
 /// Bundle version with source kind information.
 ///
 /// This indicates which source (builtin or remote) provides a bundle version.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
 pub struct BundleSourceVersion {
   /// The source kind (builtin or remote)
   pub kind: BundleSourceKind,
@@ -72,6 +74,8 @@ pub struct BundleSourceBuilder {
   builtin_manifest_filepath: Option<PathBuf>,
   remote_dir: PathBuf,
   remote_manifest_filepath: Option<PathBuf>,
+  host: Option<HostInfo>,
+  verify_data: Option<VerifyOptions>,
 }
 
 impl BundleSourceBuilder {
@@ -101,6 +105,22 @@ impl BundleSourceBuilder {
     self
   }
 
+  /// Set the host app information used to check compatibility of remote versions.
+  pub fn host(mut self, host: HostInfo) -> Self {
+    self.host = Some(host);
+    self
+  }
+
+  /// Check entry data against its checksum when read, see
+  /// [`BundleDescriptor::set_verify_data`].
+  ///
+  /// Applies to every bundle and descriptor read from the source, including entries streamed
+  /// by `BundleProtocol`.
+  pub fn verify_data(mut self, options: VerifyOptions) -> Self {
+    self.verify_data = Some(options);
+    self
+  }
+
   pub fn build(self) -> BundleSource {
     let builtin_dir = self.builtin_dir;
     let builtin_manifest_filepath = self
@@ -117,25 +137,56 @@ impl BundleSourceBuilder {
       builtin_manifest: BundleManifest::new(&builtin_manifest_filepath, ReadOnly),
       remote_dir,
       remote_manifest: BundleManifest::new(&remote_manifest_filepath, ReadWrite),
+      host: self.host,
+      verify_data: self.verify_data,
       descriptors: DashMap::default(),
+      compatibility: DashMap::default(),
+      unload_listeners: RwLock::default(),
     }
   }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Serialize, Debug, Clone)]
+#[serde(rename_all = "camelCase")]
 #[non_exhaustive]
 pub struct ListBundleItem {
   pub kind: BundleSourceKind,
+  #[serde(flatten)]
   pub item: ListBundleManifestItem,
 }
 
-#[derive(Debug)]
+/// Listener called with the bundle name when a descriptor is unloaded.
+type UnloadListener = Box<dyn Fn(&str) + Send + Sync>;
+
+/// Descriptor with the version it was read from.
+type VersionedDescriptor = (String, Arc<BundleDescriptor>);
+
 pub struct BundleSource {
   builtin_dir: PathBuf,
   builtin_manifest: BundleManifest<ReadOnly>,
   remote_dir: PathBuf,
   remote_manifest: BundleManifest<ReadWrite>,
-  descriptors: DashMap<String, Arc<OnceCell<Arc<BundleDescriptor>>>>,
+  host: Option<HostInfo>,
+  verify_data: Option<VerifyOptions>,
+  descriptors: DashMap<String, Arc<OnceCell<VersionedDescriptor>>>,
+  /// Compatibility of remote versions with the host app, keyed by bundle name and version,
+  /// holding the reason of incompatible versions.
+  compatibility: DashMap<(String, String), Option<String>>,
+  unload_listeners: RwLock<Vec<UnloadListener>>,
+}
+
+impl std::fmt::Debug for BundleSource {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("BundleSource")
+      .field("builtin_dir", &self.builtin_dir)
+      .field("builtin_manifest", &self.builtin_manifest)
+      .field("remote_dir", &self.remote_dir)
+      .field("remote_manifest", &self.remote_manifest)
+      .field("host", &self.host)
+      .field("verify_data", &self.verify_data)
+      .field("descriptors", &self.descriptors)
+      .finish_non_exhaustive()
+  }
 }
 
 impl BundleSource {
@@ -143,6 +194,21 @@ impl BundleSource {
     BundleSourceBuilder::new()
   }
 
+  /// Returns the directory of builtin bundles.
+  pub fn builtin_dir(&self) -> &Path {
+    &self.builtin_dir
+  }
+
+  /// Returns the directory of remote bundles.
+  pub fn remote_dir(&self) -> &Path {
+    &self.remote_dir
+  }
+
+  /// Returns the host app information, if configured.
+  pub fn host(&self) -> Option<&HostInfo> {
+    self.host.as_ref()
+  }
+
   pub async fn list_bundles(&self) -> crate::Result<Vec<ListBundleItem>> {
     let (builtin_entries, remote_entries) = tokio::try_join!(
       self.builtin_manifest.list_entries(),
@@ -169,11 +235,27 @@ impl BundleSource {
     &self,
     bundle_name: &str,
   ) -> crate::Result<Option<BundleSourceVersion>> {
-    match self
+    let remote_version = match self
       .remote_manifest
       .load_current_version(bundle_name)
       .await?
     {
+      Some(ver) => match self.check_compatibility(bundle_name, &ver).await {
+        Ok(()) => Some(ver),
+        Err(crate::Error::BundleIncompatible { .. }) => {
+          #[cfg(feature = "tracing")]
+          tracing::warn!(
+            bundle_name,
+            version = ver,
+            "remote version is incompatible with the host app, fallback to builtin version"
+          );
+          None
+        }
+        Err(e) => return Err(e),
+      },
+      None => None,
+    };
+    match remote_version {
       Some(ver) => Ok(Some(BundleSourceVersion::remote(ver))),
       None => {
         // fallback to builtin version
@@ -187,50 +269,89 @@ impl BundleSource {
     }
   }
 
+  /// Switches the current version of a bundle, unloading its descriptor.
   pub async fn update_version(&self, bundle_name: &str, version: &str) -> crate::Result<()> {
+    self.check_compatibility(bundle_name, version).await?;
     self
       .remote_manifest
       .update_current_version(bundle_name, version)
-      .await
+      .await?;
+    self.unload_descriptor(bundle_name);
+    Ok(())
   }
 
   pub async fn filepath(&self, bundle_name: &str) -> crate::Result<PathBuf> {
-    let ver = self
-      .load_version(bundle_name)
-      .await?
-      .ok_or(crate::Error::BundleNotFound)?;
-    let filepath = match &ver.kind {
-      BundleSourceKind::Builtin => self.get_builtin_filepath(bundle_name, &ver.version),
-      BundleSourceKind::Remote => self.get_remote_filepath(bundle_name, &ver.version),
-    };
+    let (_, filepath) = self.resolve(bundle_name).await?;
     Ok(filepath)
   }
 
   pub async fn reader(&self, bundle_name: &str) -> crate::Result<File> {
-    let filepath = self.filepath(bundle_name).await?;
-    let file = File::open(filepath).await.map_err(|e| {
-      if e.kind() == std::io::ErrorKind::NotFound {
-        return crate::Error::BundleNotFound;
-      }
-      crate::Error::from(e)
-    })?;
+    let (_, file) = self.open(bundle_name).await?;
     Ok(file)
   }
 
+  /// Opens the file of a version of a bundle, regardless of the current version.
+  ///
+  /// Reads of a loaded descriptor should use its version, since the current version may be
+  /// switched in the meantime. The remote directory is looked up first, like for the current
+  /// version.
+  pub async fn reader_for(&self, bundle_name: &str, version: &str) -> crate::Result<File> {
+    for filepath in [
+      self.get_remote_filepath(bundle_name, version),
+      self.get_builtin_filepath(bundle_name, version),
+    ] {
+      match File::open(filepath).await {
+        Ok(file) => return Ok(file),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.into()),
+      }
+    }
+    Err(crate::Error::BundleNotFound)
+  }
+
+  /// Reads the current version of a bundle.
+  ///
+  /// Fails with [`crate::Error::BundleMetadataMismatch`] when the metadata section of the
+  /// bundle file does not match its manifest entry.
   pub async fn fetch(&self, bundle_name: &str) -> crate::Result<Bundle> {
-    let mut file = self.reader(bundle_name).await?;
-    let bundle = AsyncReader::<Bundle>::read(&mut AsyncBundleReader::new(&mut file)).await?;
+    let (version, mut file) = self.open(bundle_name).await?;
+    let mut bundle = AsyncReader::<Bundle>::read(&mut AsyncBundleReader::new(&mut file)).await?;
+    check_metadata(bundle_name, &version, bundle.metadata())?;
+    bundle.set_verify_data(self.verify_data);
     Ok(bundle)
   }
 
+  /// Reads the descriptor of the current version of a bundle.
+  ///
+  /// Fails with [`crate::Error::BundleMetadataMismatch`] when the metadata section of the
+  /// bundle file does not match its manifest entry.
   pub async fn fetch_descriptor(&self, bundle_name: &str) -> crate::Result<BundleDescriptor> {
-    let mut file = self.reader(bundle_name).await?;
-    let manifest =
+    let (_, descriptor) = self.fetch_versioned_descriptor(bundle_name).await?;
+    Ok(descriptor)
+  }
+
+  async fn fetch_versioned_descriptor(
+    &self,
+    bundle_name: &str,
+  ) -> crate::Result<(String, BundleDescriptor)> {
+    let (version, mut file) = self.open(bundle_name).await?;
+    let mut manifest =
       AsyncReader::<BundleDescriptor>::read(&mut AsyncBundleReader::new(&mut file)).await?;
-    Ok(manifest)
+    check_metadata(bundle_name, &version, manifest.metadata())?;
+    manifest.set_verify_data(self.verify_data);
+    Ok((version, manifest))
   }
 
   pub async fn load_descriptor(&self, bundle_name: &str) -> crate::Result<Arc<BundleDescriptor>> {
+    let (_, descriptor) = self.load_versioned_descriptor(bundle_name).await?;
+    Ok(descriptor)
+  }
+
+  /// Loads the descriptor of a bundle, along with the version it was read from.
+  pub async fn load_versioned_descriptor(
+    &self,
+    bundle_name: &str,
+  ) -> crate::Result<VersionedDescriptor> {
     if let Some(entry) = self.descriptors.get(bundle_name) {
       if let Some(m) = entry.get() {
         return Ok(m.clone());
@@ -242,16 +363,44 @@ impl BundleSource {
     };
     let descriptor = descriptor_cell
       .get_or_try_init(|| async {
-        let d = self.fetch_descriptor(bundle_name).await?;
-        Ok::<Arc<BundleDescriptor>, crate::Error>(Arc::new(d))
+        let (version, d) = self.fetch_versioned_descriptor(bundle_name).await?;
+        Ok::<_, crate::Error>((version, Arc::new(d)))
       })
       .await?
       .clone();
     Ok(descriptor)
   }
 
+  /// Unloads the cached descriptor of a bundle, so the next load reads the current version.
+  ///
+  /// Cached compatibility checks of its versions are dropped too.
+  ///
+  /// Listeners registered with [`BundleSource::on_unload_descriptor`] are called even when
+  /// no descriptor was loaded.
   pub fn unload_descriptor(&self, bundle_name: &str) -> bool {
-    self.descriptors.remove(bundle_name).is_some()
+    let unloaded = self.descriptors.remove(bundle_name).is_some();
+    self
+      .compatibility
+      .retain(|(name, _), _| name != bundle_name);
+    for listener in self.unload_listeners.read().unwrap().iter() {
+      listener(bundle_name);
+    }
+    unloaded
+  }
+
+  /// Registers a listener called with the bundle name whenever a descriptor is unloaded,
+  /// including when the version of the bundle is switched.
+  ///
+  /// Used to invalidate data derived from a bundle, such as cached entries.
+  pub fn on_unload_descriptor<F>(&self, listener: F)
+  where
+    F: Fn(&str) + Send + Sync + 'static,
+  {
+    self
+      .unload_listeners
+      .write()
+      .unwrap()
+      .push(Box::new(listener));
   }
 
   pub async fn write_remote_bundle(
@@ -261,6 +410,11 @@ impl BundleSource {
     bundle: &Bundle,
     metadata: BundleManifestMetadata,
   ) -> crate::Result<()> {
+    check_metadata(bundle_name, version, bundle.metadata())?;
+    if let Some(host) = &self.host {
+      let compat = BundleCompatibility::try_from(bundle.metadata());
+      check_host(bundle_name, version, compat, host)?;
+    }
     let filepath = self.get_remote_filepath(bundle_name, version);
     if let Some(parent) = filepath.parent() {
       let _ = tokio::fs::create_dir_all(parent).await;
@@ -271,9 +425,110 @@ impl BundleSource {
       .remote_manifest
       .insert_entry(bundle_name, version, metadata)
       .await?;
+    self
+      .compatibility
+      .remove(&(bundle_name.to_string(), version.to_string()));
     Ok(())
   }
 
+  /// Check that a remote version is compatible with the host app.
+  ///
+  /// Constraints are read from both the manifest entry and the metadata section of the
+  /// bundle file. Always succeeds when the source has no host information.
+  ///
+  /// Results are cached until the bundle is unloaded with
+  /// [`BundleSource::unload_descriptor`] or the version is written again.
+  pub async fn check_compatibility(&self, bundle_name: &str, version: &str) -> crate::Result<()> {
+    let host = match &self.host {
+      Some(x) => x,
+      None => return Ok(()),
+    };
+    let key = (bundle_name.to_string(), version.to_string());
+    let reason = match self.compatibility.get(&key).map(|x| x.clone()) {
+      Some(x) => x,
+      None => {
+        let reason = match self.read_compatibility(bundle_name, version, host).await {
+          // the bundle file is not written yet, so the check is not complete
+          Ok(false) => return Ok(()),
+          Ok(true) => None,
+          Err(crate::Error::BundleIncompatible { reason, .. }) => Some(reason),
+          Err(e) => return Err(e),
+        };
+        self.compatibility.insert(key, reason.clone());
+        reason
+      }
+    };
+    match reason {
+      Some(reason) => Err(crate::Error::bundle_incompatible(
+        bundle_name,
+        version,
+        reason,
+      )),
+      None => Ok(()),
+    }
+  }
+
+  /// Checks the constraints of a remote version, returning whether its bundle file was
+  /// checked too.
+  async fn read_compatibility(
+    &self,
+    bundle_name: &str,
+    version: &str,
+    host: &HostInfo,
+  ) -> crate::Result<bool> {
+    if let Some(metadata) = self
+      .remote_manifest
+      .load_metadata(bundle_name, version)
+      .await?
+    {
+      check_host(
+        bundle_name,
+        version,
+        BundleCompatibility::try_from(&metadata),
+        host,
+      )?;
+    }
+    let filepath = self.get_remote_filepath(bundle_name, version);
+    let mut file = match File::open(&filepath).await {
+      Ok(x) => x,
+      Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+      Err(e) => return Err(e.into()),
+    };
+    let mut reader = AsyncBundleReader::new(&mut file);
+    let header = reader.read_header().await?;
+    let metadata = reader.read_metadata(header).await?;
+    check_host(
+      bundle_name,
+      version,
+      BundleCompatibility::try_from(&metadata),
+      host,
+    )?;
+    Ok(true)
+  }
+
+  async fn resolve(&self, bundle_name: &str) -> crate::Result<(String, PathBuf)> {
+    let ver = self
+      .load_version(bundle_name)
+      .await?
+      .ok_or(crate::Error::BundleNotFound)?;
+    let filepath = match &ver.kind {
+      BundleSourceKind::Builtin => self.get_builtin_filepath(bundle_name, &ver.version),
+      BundleSourceKind::Remote => self.get_remote_filepath(bundle_name, &ver.version),
+    };
+    Ok((ver.version, filepath))
+  }
+
+  async fn open(&self, bundle_name: &str) -> crate::Result<(String, File)> {
+    let (version, filepath) = self.resolve(bundle_name).await?;
+    let file = File::open(filepath).await.map_err(|e| {
+      if e.kind() == std::io::ErrorKind::NotFound {
+        return crate::Error::BundleNotFound;
+      }
+      crate::Error::from(e)
+    })?;
+    Ok((version, file))
+  }
+
   fn get_builtin_filepath(&self, bundle_name: &str, version: &str) -> PathBuf {
     self.get_filepath(&self.builtin_dir, bundle_name, version)
   }
@@ -289,10 +544,52 @@ impl BundleSource {
   }
 }
 
+/// Check compatibility constraints of a bundle version against the host app.
+fn check_host(
+  bundle_name: &str,
+  version: &str,
+  compat: crate::Result<BundleCompatibility>,
+  host: &HostInfo,
+) -> crate::Result<()> {
+  compat
+    .map_err(|e| crate::Error::bundle_incompatible(bundle_name, version, e))?
+    .check(host)
+    .map_err(|e| crate::Error::bundle_incompatible(bundle_name, version, e))
+}
+
+/// Check the metadata section of a bundle file against its manifest entry.
+///
+/// Keys missing from the metadata are not checked, so bundles without metadata always pass.
+fn check_metadata(
+  bundle_name: &str,
+  version: &str,
+  metadata: &BundleMetadata,
+) -> crate::Result<()> {
+  if let Some(name) = metadata.name()
+    && name != bundle_name
+  {
+    return Err(crate::Error::bundle_metadata_mismatch(
+      bundle_name,
+      version,
+      format!("bundle file is named \"{name}\""),
+    ));
+  }
+  if let Some(ver) = metadata.version()
+    && ver != version
+  {
+    return Err(crate::Error::bundle_metadata_mismatch(
+      bundle_name,
+      version,
+      format!("bundle file has version \"{ver}\""),
+    ));
+  }
+  Ok(())
+}
+
 #[cfg(test)]
 mod tests {
   use super::*;
-  use crate::testing::Fixtures;
+  use crate::testing::{Fixtures, MockBundle, MockSource};
 
   #[tokio::test]
   async fn fetch() {
@@ -407,6 +704,68 @@ mod tests {
     assert!(!Arc::ptr_eq(&m3, &m4));
   }
 
+  #[tokio::test]
+  async fn unload_listener() {
+    use std::sync::Mutex;
+
+    let mut mock = MockSource::new();
+    mock
+      .add_builtin_bundle(MockBundle::new("app", "1.0.0"))
+      .set_builtin_current_version("app", "1.0.0")
+      .add_remote_bundle(MockBundle::new("app", "1.1.0"))
+      .add_remote_bundle(MockBundle::new("app", "1.2.0"))
+      .set_remote_current_version("app", "1.1.0");
+    let source = mock.get_source();
+    let unloaded = Arc::new(Mutex::new(Vec::new()));
+    let u = unloaded.clone();
+    source.on_unload_descriptor(move |name| u.lock().unwrap().push(name.to_string()));
+
+    let (version, _) = source.load_versioned_descriptor("app").await.unwrap();
+    assert_eq!(version, "1.1.0");
+    assert!(source.unload_descriptor("app"));
+    assert!(!source.unload_descriptor("other"));
+    // switching the version unloads the descriptor
+    source.load_descriptor("app").await.unwrap();
+    source.update_version("app", "1.2.0").await.unwrap();
+    let (version, _) = source.load_versioned_descriptor("app").await.unwrap();
+    assert_eq!(version, "1.2.0");
+    assert_eq!(*unloaded.lock().unwrap(), ["app", "other", "app"]);
+  }
+
+  #[tokio::test]
+  async fn reader_for() {
+    use crate::BundleEntry;
+
+    let entry = |data: &[u8]| BundleEntry::new(data, "text/html", None);
+    let mut mock = MockSource::new();
+    mock
+      .add_builtin_bundle(
+        MockBundle::new("app", "1.0.0").with_entry("/index.html", entry(b"1.0.0")),
+      )
+      .set_builtin_current_version("app", "1.0.0")
+      .add_remote_bundle(MockBundle::new("app", "1.1.0").with_entry("/index.html", entry(b"1.1.0")))
+      .add_remote_bundle(MockBundle::new("app", "1.2.0").with_entry("/index.html", entry(b"1.2.0")))
+      .set_remote_current_version("app", "1.1.0");
+    let source = mock.get_source();
+
+    let (version, descriptor) = source.load_versioned_descriptor("app").await.unwrap();
+    source.update_version("app", "1.2.0").await.unwrap();
+    // the loaded descriptor is still read from its own version
+    let reader = source.reader_for("app", &version).await.unwrap();
+    let data = descriptor.async_get_data(reader, "/index.html").await;
+    assert_eq!(data.unwrap().unwrap(), b"1.1.0");
+    // versions missing from the remote directory are read from the builtin directory
+    let mut reader = source.reader_for("app", "1.0.0").await.unwrap();
+    let bundle = AsyncReader::<Bundle>::read(&mut AsyncBundleReader::new(&mut reader))
+      .await
+      .unwrap();
+    assert_eq!(bundle.get_data("/index.html").unwrap().unwrap(), b"1.0.0");
+    assert!(matches!(
+      source.reader_for("app", "0.1.0").await,
+      Err(crate::Error::BundleNotFound)
+    ));
+  }
+
   #[tokio::test]
   async fn load_and_unload_concurrently() {
     use std::sync::Arc;
@@ -489,4 +848,126 @@ mod tests {
       assert!(Arc::ptr_eq(&after_jobs[0], m));
     }
   }
+
+  #[tokio::test]
+  async fn incompatible_remote_version() {
+    let mut mock = MockSource::new();
+    mock
+      .add_builtin_bundle(MockBundle::new("app", "1.0.0"))
+      .set_builtin_current_version("app", "1.0.0")
+      .add_remote_bundle(MockBundle::new("app", "1.1.0").with_compatible_app_version(">=2"))
+      .add_remote_bundle(MockBundle::new("app", "1.2.0").with_required_capability("fs"))
+      .set_remote_current_version("app", "1.1.0");
+
+    let source = mock.get_source();
+    let version = source.load_version("app").await.unwrap().unwrap();
+    assert_eq!(version, BundleSourceVersion::remote("1.1.0".to_string()));
+
+    let host = HostInfo::new().version(semver::Version::new(1, 5, 0));
+    let source = mock.get_source_with_host(host.clone());
+    let version = source.load_version("app").await.unwrap().unwrap();
+    assert_eq!(version, BundleSourceVersion::builtin("1.0.0".to_string()));
+    let err = source.update_version("app", "1.2.0").await.unwrap_err();
+    assert!(matches!(err, crate::Error::BundleIncompatible { .. }));
+
+    let source = mock.get_source_with_host(host.capability("fs"));
+    source.update_version("app", "1.2.0").await.unwrap();
+    let version = source.load_version("app").await.unwrap().unwrap();
+    assert_eq!(version, BundleSourceVersion::remote("1.2.0".to_string()));
+  }
+
+  #[tokio::test]
+  async fn incompatible_bundle_metadata() {
+    let mut metadata = BundleMetadata::new();
+    metadata
+      .set_compatible_app_version(">=2")
+      .set_required_capabilities(["fs"]);
+    let mut mock = MockSource::new();
+    mock
+      .add_builtin_bundle(MockBundle::new("app", "1.0.0"))
+      .set_builtin_current_version("app", "1.0.0")
+      .add_remote_bundle(MockBundle::new("app", "1.1.0").with_bundle_metadata(metadata.clone()))
+      .set_remote_current_version("app", "1.1.0");
+
+    let host = HostInfo::new().version(semver::Version::new(1, 5, 0));
+    let source = mock.get_source_with_host(host.clone());
+    let version = source.load_version("app").await.unwrap().unwrap();
+    assert_eq!(version, BundleSourceVersion::builtin("1.0.0".to_string()));
+    let err = source.update_version("app", "1.1.0").await.unwrap_err();
+    assert!(matches!(err, crate::Error::BundleIncompatible { .. }));
+    let bundle = MockBundle::new("app", "1.2.0")
+      .with_bundle_metadata(metadata)
+      .bundle();
+    let err = source
+      .write_remote_bundle("app", "1.2.0", &bundle, Default::default())
+      .await
+      .unwrap_err();
+    assert!(matches!(err, crate::Error::BundleIncompatible { .. }));
+
+    let host = HostInfo::new()
+      .version(semver::Version::new(2, 0, 0))
+      .capability("fs");
+    let source = mock.get_source_with_host(host);
+    let version = source.load_version("app").await.unwrap().unwrap();
+    assert_eq!(version, BundleSourceVersion::remote("1.1.0".to_string()));
+    source
+      .write_remote_bundle("app", "1.2.0", &bundle, Default::default())
+      .await
+      .unwrap();
+  }
+
+  #[tokio::test]
+  async fn cache_compatibility() {
+    let mut mock = MockSource::new();
+    mock
+      .add_builtin_bundle(MockBundle::new("app", "1.0.0"))
+      .set_builtin_current_version("app", "1.0.0")
+      .add_remote_bundle(MockBundle::new("app", "1.1.0"))
+      .set_remote_current_version("app", "1.1.0");
+    let source = mock.get_source_with_host(HostInfo::new().version(semver::Version::new(1, 0, 0)));
+    let version = source.load_version("app").await.unwrap().unwrap();
+    assert_eq!(version, BundleSourceVersion::remote("1.1.0".to_string()));
+
+    // the bundle file is not read again until the bundle is unloaded
+    let mut metadata = BundleMetadata::new();
+    metadata.set_compatible_app_version(">=2");
+    mock.add_remote_bundle(MockBundle::new("app", "1.1.0").with_bundle_metadata(metadata));
+    let version = source.load_version("app").await.unwrap().unwrap();
+    assert_eq!(version, BundleSourceVersion::remote("1.1.0".to_string()));
+    source.unload_descriptor("app");
+    let version = source.load_version("app").await.unwrap().unwrap();
+    assert_eq!(version, BundleSourceVersion::builtin("1.0.0".to_string()));
+  }
+
+  #[tokio::test]
+  async fn metadata_mismatch() {
+    let mut metadata = BundleMetadata::new();
+    metadata.set_name("app").set_version("1.0.0");
+    let mut mock = MockSource::new();
+    mock
+      .add_builtin_bundle(MockBundle::new("app", "0.1.0"))
+      .set_builtin_current_version("app", "0.1.0")
+      .add_remote_bundle(MockBundle::new("app", "1.0.0").with_bundle_metadata(metadata.clone()))
+      .add_remote_bundle(MockBundle::new("app", "1.1.0").with_bundle_metadata(metadata.clone()))
+      .set_remote_current_version("app", "1.0.0");
+
+    let source = mock.get_source();
+    let descriptor = source.fetch_descriptor("app").await.unwrap();
+    assert_eq!(descriptor.metadata(), &metadata);
+
+    source.update_version("app", "1.1.0").await.unwrap();
+    let err = source.fetch_descriptor("app").await.unwrap_err();
+    assert!(matches!(err, crate::Error::BundleMetadataMismatch { .. }));
+    let err = source.fetch("app").await.unwrap_err();
+    assert!(matches!(err, crate::Error::BundleMetadataMismatch { .. }));
+
+    let bundle = MockBundle::new("other", "1.0.0")
+      .with_bundle_metadata(metadata)
+      .bundle();
+    let err = source
+      .write_remote_bundle("other", "1.0.0", &bundle, Default::default())
+      .await
+      .unwrap_err();
+    assert!(matches!(err, crate::Error::BundleMetadataMismatch { .. }));
+  }
 }
@@ -1,17 +1,18 @@
-use crate::remote::Remote;
 use crate::source::{
-  BundleManifestData, BundleManifestEntry, BundleManifestMetadata, BundleSource,
+  BundleManifestData, BundleManifestEntry, BundleManifestMetadata, BundleSource, HostInfo,
 };
 use crate::testing::TempDir;
-use crate::{Bundle, BundleEntry, BundleWriter, Writer};
-use httpmock::{HttpMockRequest, HttpMockResponse, MockExt, MockServer};
-use serde::Serialize;
+use crate::{Bundle, BundleEntry, BundleMetadata, BundleWriter, Writer};
 use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::hash::{Hash, Hasher};
 use std::io::Cursor;
 use std::path::PathBuf;
-use std::sync::{Arc, Mutex};
+
+#[cfg(feature = "remote")]
+mod remote;
+#[cfg(feature = "remote")]
+pub use remote::*;
 
 #[derive(Debug, Clone)]
 pub struct MockBundle {
@@ -21,6 +22,11 @@ pub struct MockBundle {
   integrity: Option<String>,
   signature: Option<String>,
   last_modified: Option<String>,
+  rollout_percentage: Option<u8>,
+  min_app_version: Option<String>,
+  compatible_app_version: Option<String>,
+  required_capabilities: Vec<String>,
+  bundle_metadata: BundleMetadata,
   entries: HashMap<String, BundleEntry>,
 }
 
@@ -54,6 +60,11 @@ impl MockBundle {
       integrity: None,
       signature: None,
       last_modified: None,
+      rollout_percentage: None,
+      min_app_version: None,
+      compatible_app_version: None,
+      required_capabilities: vec![],
+      bundle_metadata: BundleMetadata::default(),
       entries: HashMap::new(),
     }
   }
@@ -98,9 +109,58 @@ impl MockBundle {
     if let Some(last_modified) = &self.last_modified {
       headers.push(("last-modified".to_owned(), last_modified.to_owned()));
     }
+    if let Some(rollout_percentage) = &self.rollout_percentage {
+      headers.push((
+        "webview-bundle-rollout-percentage".to_owned(),
+        rollout_percentage.to_string(),
+      ));
+    }
+    if let Some(min_app_version) = &self.min_app_version {
+      headers.push((
+        "webview-bundle-min-app-version".to_owned(),
+        min_app_version.to_owned(),
+      ));
+    }
+    if let Some(compatible_app_version) = &self.compatible_app_version {
+      headers.push((
+        "webview-bundle-compatible-app-version".to_owned(),
+        compatible_app_version.to_owned(),
+      ));
+    }
+    if !self.required_capabilities.is_empty() {
+      headers.push((
+        "webview-bundle-required-capabilities".to_owned(),
+        self.required_capabilities.join(","),
+      ));
+    }
     headers
   }
 
+  pub fn with_rollout_percentage(mut self, percentage: u8) -> Self {
+    self.rollout_percentage = Some(percentage);
+    self
+  }
+
+  pub fn with_min_app_version(mut self, version: impl Into<String>) -> Self {
+    self.min_app_version = Some(version.into());
+    self
+  }
+
+  pub fn with_compatible_app_version(mut self, version: impl Into<String>) -> Self {
+    self.compatible_app_version = Some(version.into());
+    self
+  }
+
+  pub fn with_required_capability(mut self, capability: impl Into<String>) -> Self {
+    self.required_capabilities.push(capability.into());
+    self
+  }
+
+  pub fn with_bundle_metadata(mut self, metadata: BundleMetadata) -> Self {
+    self.bundle_metadata = metadata;
+    self
+  }
+
   pub fn with_entry(mut self, path: impl Into<String>, entry: BundleEntry) -> Self {
     self.entries.insert(path.into(), entry);
     self
@@ -116,6 +176,7 @@ impl MockBundle {
     for (path, entry) in self.entries.iter() {
       builder.insert_entry(path, entry.clone());
     }
+    builder.set_metadata(self.bundle_metadata.clone());
     builder.build().unwrap()
   }
 
@@ -135,6 +196,10 @@ impl MockBundle {
       integrity: self.integrity.to_owned(),
       signature: self.signature.to_owned(),
       last_modified: self.last_modified.to_owned(),
+      rollout_percentage: self.rollout_percentage,
+      min_app_version: self.min_app_version.to_owned(),
+      compatible_app_version: self.compatible_app_version.to_owned(),
+      required_capabilities: self.required_capabilities.to_owned(),
     }
   }
 
@@ -203,7 +268,7 @@ impl MockBundleCollection {
 
   pub fn remove_all(&mut self, name: impl Into<String>) -> &mut Self {
     let name = name.into();
-    self.bundles.retain(|x| x.name() != &name);
+    self.bundles.retain(|x| x.name() != name);
     self
   }
 
@@ -248,6 +313,14 @@ impl MockSource {
       .build()
   }
 
+  pub fn get_source_with_host(&self, host: HostInfo) -> BundleSource {
+    BundleSource::builder()
+      .builtin_dir(&self.builtin_dir)
+      .remote_dir(&self.remote_dir)
+      .host(host)
+      .build()
+  }
+
   pub fn add_builtin_bundle(&mut self, bundle: MockBundle) -> &mut Self {
     let filepath = self.builtin_dir.join(bundle.name()).join(format!(
       "{}_{}.wvb",
@@ -303,6 +376,7 @@ impl MockSource {
       bundle.version()
     ));
     fs::create_dir_all(filepath.parent().unwrap()).unwrap();
+    fs::write(filepath, bundle.bundle_data()).unwrap();
     self.remote_bundles.add(bundle);
     self.sync_remote_manifest();
     self
@@ -413,501 +487,9 @@ impl MockSource {
         .or_insert_with(|| BundleManifestEntry {
           versions: HashMap::from([(bundle.version().to_string(), bundle.metadata())]),
           current_version: bundle.version().to_string(),
+          channels: HashMap::new(),
         });
     }
     Some(manifest)
   }
 }
-
-#[derive(Debug, Hash, Eq, PartialEq, Clone)]
-pub enum MockRemoteEndpoint {
-  ListBundles,
-  GetCurrentInfo,
-  Download,
-  DownloadVersion,
-}
-
-pub struct MockRemote {
-  server: MockServer,
-  mocks: HashMap<MockRemoteEndpoint, usize>,
-  allow_other_versions: bool,
-  bundles: Arc<Mutex<MockBundleCollection>>,
-  current_versions: Arc<Mutex<CurrentVersions>>,
-  channel_bundles: Arc<Mutex<HashMap<String, MockBundleCollection>>>,
-  channel_current_versions: Arc<Mutex<HashMap<String, CurrentVersions>>>,
-}
-
-impl MockRemote {
-  pub fn new() -> Self {
-    let server = MockServer::start();
-    let mut instance = Self {
-      server,
-      mocks: HashMap::new(),
-      allow_other_versions: false,
-      bundles: Arc::new(Mutex::new(MockBundleCollection::new())),
-      current_versions: Arc::new(Mutex::new(CurrentVersions::new())),
-      channel_bundles: Default::default(),
-      channel_current_versions: Default::default(),
-    };
-    instance.init();
-    instance
-  }
-
-  pub fn get_remote(&self) -> Remote {
-    Remote::builder()
-      .endpoint(self.server_url())
-      .build()
-      .unwrap()
-  }
-
-  pub fn server_url(&self) -> String {
-    format!("http://{}:{}", self.server.host(), self.server.port())
-  }
-
-  pub fn allow_other_versions(&mut self, allow: bool) -> &mut Self {
-    self.allow_other_versions = allow;
-    self
-  }
-
-  pub fn add_bundle(&mut self, bundle: MockBundle) -> &mut Self {
-    {
-      let mut collection = self.bundles.lock().unwrap();
-      collection.add(bundle);
-    }
-    self
-  }
-
-  pub fn remove_bundle(
-    &mut self,
-    name: impl Into<String>,
-    version: impl Into<String>,
-  ) -> &mut Self {
-    let name = name.into();
-    let version = version.into();
-    {
-      let mut collection = self.bundles.lock().unwrap();
-      collection.remove(&name, &version);
-    }
-    {
-      let mut cv = self.current_versions.lock().unwrap();
-      cv.unset_if_current(&name, &version);
-    }
-    self
-  }
-
-  pub fn remove_bundle_all(&mut self, name: impl Into<String>) -> &mut Self {
-    let name = name.into();
-    {
-      let mut collection = self.bundles.lock().unwrap();
-      collection.remove_all(&name);
-    }
-    {
-      let mut cv = self.current_versions.lock().unwrap();
-      cv.unset(&name);
-    }
-    self
-  }
-
-  pub fn add_channel_bundle(
-    &mut self,
-    channel: impl Into<String>,
-    bundle: MockBundle,
-  ) -> &mut Self {
-    let channel = channel.into();
-    {
-      let mut collection = self.channel_bundles.lock().unwrap();
-      collection
-        .entry(channel)
-        .and_modify(|x| {
-          x.add(bundle.clone());
-        })
-        .or_insert_with(move || {
-          let mut collection = MockBundleCollection::new();
-          collection.add(bundle);
-          collection
-        });
-    }
-    self
-  }
-
-  pub fn remove_channel_bundle(
-    &mut self,
-    channel: impl Into<String>,
-    name: impl Into<String>,
-    version: impl Into<String>,
-  ) -> &mut Self {
-    let channel = channel.into();
-    let name = name.into();
-    let version = version.into();
-    {
-      let mut collection = self.channel_bundles.lock().unwrap();
-      if let Some(x) = collection.get_mut(&channel) {
-        x.remove(&name, &version);
-      }
-    }
-    {
-      let mut cv = self.channel_current_versions.lock().unwrap();
-      if let Some(x) = cv.get_mut(&channel) {
-        x.unset_if_current(&name, &version);
-      }
-    }
-    self
-  }
-
-  pub fn remove_channel_bundle_all(
-    &mut self,
-    channel: impl Into<String>,
-    name: impl Into<String>,
-  ) -> &mut Self {
-    let channel = channel.into();
-    let name = name.into();
-    {
-      let mut collection = self.channel_bundles.lock().unwrap();
-      if let Some(x) = collection.get_mut(&channel) {
-        x.remove_all(&name);
-      }
-    }
-    {
-      let mut cv = self.channel_current_versions.lock().unwrap();
-      if let Some(x) = cv.get_mut(&channel) {
-        x.unset(&name);
-      }
-    }
-    self
-  }
-
-  pub fn set_bundle_current_version(
-    &mut self,
-    name: impl Into<String>,
-    version: impl Into<String>,
-  ) -> &mut Self {
-    {
-      let mut cv = self.current_versions.lock().unwrap();
-      cv.set(name, version);
-    }
-    self
-  }
-
-  pub fn set_channel_bundle_current_version(
-    &mut self,
-    channel: impl Into<String>,
-    name: impl Into<String>,
-    version: impl Into<String>,
-  ) -> &mut Self {
-    let channel = channel.into();
-    let name = name.into();
-    let version = version.into();
-    {
-      let mut cv = self.channel_current_versions.lock().unwrap();
-      cv.entry(channel)
-        .and_modify(|x| {
-          x.set(&name, &version);
-        })
-        .or_insert_with(|| {
-          let mut cv = CurrentVersions::new();
-          cv.set(&name, &version);
-          cv
-        });
-    }
-    self
-  }
-
-  fn init(&mut self) {
-    let bundles = Arc::clone(&self.bundles);
-    let current_versions = Arc::clone(&self.current_versions);
-    let channel_bundles = Arc::clone(&self.channel_bundles);
-    let channel_current_versions = Arc::clone(&self.channel_current_versions);
-
-    let get_bundle = Arc::new(
-      move |bundle_name: String,
-            version: Option<String>,
-            channel: Option<String>|
-            -> Option<MockBundle> {
-        let version = version.or_else(|| match &channel {
-          Some(c) => {
-            let ccv = channel_current_versions.lock().unwrap();
-            ccv
-              .get(c)
-              .and_then(|x| x.versions.get(&bundle_name))
-              .cloned()
-          }
-          None => {
-            let cv = current_versions.lock().unwrap();
-            cv.versions.get(&bundle_name).cloned()
-          }
-        })?;
-
-        let target: MockBundle = (bundle_name, version).into();
-        let bundle = match &channel {
-          Some(c) => {
-            let cb = channel_bundles.lock().unwrap();
-            cb.get(c).and_then(|x| x.bundles.get(&target)).cloned()
-          }
-          None => {
-            let b = bundles.lock().unwrap();
-            b.bundles.get(&target).cloned()
-          }
-        }?;
-        Some(bundle)
-      },
-    );
-
-    let list_bundles = self.server.mock(|when, then| {
-      #[derive(Serialize)]
-      struct ResponseItem {
-        name: String,
-        version: String,
-      }
-
-      let bundles = Arc::clone(&self.bundles);
-      let channel_bundles = Arc::clone(&self.channel_bundles);
-
-      when.method("GET").path("/bundles");
-      then.respond_with(move |req| {
-        let channel = get_channel(req);
-        let resp = match channel {
-          Some(c) => {
-            let cb = channel_bundles.lock().unwrap();
-            cb.get(&c)
-              .map(|x| x.bundles.iter())
-              .unwrap_or_default()
-              .map(|x| ResponseItem {
-                name: x.name().to_owned(),
-                version: x.version().to_owned(),
-              })
-              .collect::<Vec<_>>()
-          }
-          None => {
-            let b = bundles.lock().unwrap();
-            b.bundles
-              .iter()
-              .map(|x| ResponseItem {
-                name: x.name().to_owned(),
-                version: x.version().to_owned(),
-              })
-              .collect::<Vec<_>>()
-          }
-        };
-        HttpMockResponse::builder()
-          .status(200)
-          .header("content-type", "application/json")
-          .body(serde_json::to_string(&resp).unwrap())
-          .build()
-      });
-    });
-
-    let get_current_info = self.server.mock(|when, then| {
-      let gb = Arc::clone(&get_bundle);
-      when.method("HEAD").path_matches(r"^/bundles/([^/]+)$");
-      then.respond_with(move |req| {
-        let channel = get_channel(req);
-        let bundle_name = get_bundle_name(req);
-        let bundle = if let Some(b) = gb(bundle_name, None, channel) {
-          b
-        } else {
-          return HttpMockResponse::builder().status(404).build();
-        };
-        HttpMockResponse::builder()
-          .status(204)
-          .headers(bundle.remote_headers())
-          .build()
-      });
-    });
-
-    let download = self.server.mock(|when, then| {
-      let gb = Arc::clone(&get_bundle);
-      when.method("GET").path_matches(r"^/bundles/([^/]+)$");
-      then.respond_with(move |req| {
-        let channel = get_channel(req);
-        let bundle_name = get_bundle_name(req);
-        let bundle = if let Some(b) = gb(bundle_name, None, channel) {
-          b
-        } else {
-          return HttpMockResponse::builder().status(404).build();
-        };
-        HttpMockResponse::builder()
-          .status(200)
-          .headers(bundle.remote_headers())
-          .header("content-type", "application/webview-bundle")
-          .body(bundle.bundle_data())
-          .build()
-      });
-    });
-
-    let download_version = self.server.mock(|when, then| {
-      let gb = Arc::clone(&get_bundle);
-      let allow_other_versions = self.allow_other_versions;
-
-      when
-        .method("GET")
-        .path_matches(r"^/bundles/([^/]+)/([^/]+)$");
-      then.respond_with(move |req| {
-        if !allow_other_versions {
-          return HttpMockResponse::builder().status(403).build();
-        }
-        let channel = get_channel(req);
-        let bundle_name = get_bundle_name(req);
-        let version = get_version(req);
-        let bundle = if let Some(b) = gb(bundle_name, Some(version), channel) {
-          b
-        } else {
-          return HttpMockResponse::builder().status(404).build();
-        };
-        HttpMockResponse::builder()
-          .status(200)
-          .headers(bundle.remote_headers())
-          .header("content-type", "application/webview-bundle")
-          .body(bundle.bundle_data())
-          .build()
-      });
-    });
-
-    self
-      .mocks
-      .insert(MockRemoteEndpoint::ListBundles, list_bundles.id());
-    self
-      .mocks
-      .insert(MockRemoteEndpoint::GetCurrentInfo, get_current_info.id());
-    self
-      .mocks
-      .insert(MockRemoteEndpoint::Download, download.id());
-    self
-      .mocks
-      .insert(MockRemoteEndpoint::DownloadVersion, download_version.id());
-  }
-}
-
-fn get_channel(req: &HttpMockRequest) -> Option<String> {
-  req.query_params_map().get("channel").cloned()
-}
-
-fn get_bundle_name(req: &HttpMockRequest) -> String {
-  // /bundles/:bundle_name -> Some(bundle_name)
-  // /bundles/:bundle_name/:version -> Some(bundle_name)
-  req
-    .uri()
-    .path()
-    .split('/')
-    .nth(2)
-    .map(String::from)
-    .unwrap()
-}
-
-fn get_version(req: &HttpMockRequest) -> String {
-  // /bundles/:bundle_name -> None
-  // /bundles/:bundle_name/:version -> Some(version)
-  req
-    .uri()
-    .path()
-    .split('/')
-    .nth(3)
-    .map(String::from)
-    .unwrap()
-}
-
-pub struct MockSystem {
-  source: MockSource,
-  remote: MockRemote,
-}
-
-impl MockSystem {
-  pub fn new() -> Self {
-    Self {
-      source: MockSource::new(),
-      remote: MockRemote::new(),
-    }
-  }
-
-  pub fn source(&self) -> &MockSource {
-    &self.source
-  }
-
-  pub fn source_mut(&mut self) -> &mut MockSource {
-    &mut self.source
-  }
-
-  pub fn remote(&self) -> &MockRemote {
-    &self.remote
-  }
-
-  pub fn remote_mut(&mut self) -> &mut MockRemote {
-    &mut self.remote
-  }
-}
-
-#[cfg(test)]
-mod tests {
-  use super::*;
-  use crate::protocol::{BundleProtocol, Protocol};
-  use crate::remote::ListRemoteBundleInfo;
-  use crate::updater::Updater;
-  use http::Request;
-
-  #[tokio::test]
-  async fn smoke() {
-    let mut system = MockSystem::new();
-    system
-      .source_mut()
-      .add_builtin_bundle(MockBundle::new("app", "1.0.0").with_entry(
-        "/index.html",
-        BundleEntry::new(b"<h1>1.0.0</h1>", "text/html", None),
-      ))
-      .set_builtin_current_version("app", "1.0.0");
-    system
-      .remote_mut()
-      .add_bundle(MockBundle::new("app", "1.1.0").with_entry(
-        "/index.html",
-        BundleEntry::new(b"<h1>1.1.0</h1>", "text/html", None),
-      ))
-      .set_bundle_current_version("app", "1.1.0");
-
-    let source = Arc::new(system.source().get_source());
-    let protocol = BundleProtocol::new(source.clone());
-    let resp = protocol
-      .handle(
-        Request::builder()
-          .uri("https://app.wvb")
-          .method("GET")
-          .body(vec![])
-          .unwrap(),
-      )
-      .await
-      .unwrap();
-    let html = str::from_utf8(resp.body()).unwrap();
-    assert_eq!(html, "<h1>1.0.0</h1>");
-
-    let remote = Arc::new(system.remote().get_remote());
-
-    let updater = Updater::new(source.clone(), remote.clone(), None);
-    let remotes = updater.list_remotes().await.unwrap();
-    assert_eq!(remotes.len(), 1);
-    assert_eq!(
-      remotes,
-      vec![ListRemoteBundleInfo {
-        name: "app".to_string(),
-        version: "1.1.0".to_string(),
-      }]
-    );
-    let update_info = updater.get_update("app").await.unwrap();
-    assert_eq!(update_info.name, "app");
-    assert_eq!(update_info.version, "1.1.0");
-    assert_eq!(update_info.local_version.unwrap(), "1.0.0");
-    assert!(update_info.is_available);
-
-    updater.download_update("app", None).await.unwrap();
-    source.update_version("app", "1.1.0").await.unwrap();
-
-    let resp = protocol
-      .handle(
-        Request::builder()
-          .uri("https://app.wvb")
-          .method("GET")
-          .body(vec![])
-          .unwrap(),
-      )
-      .await
-      .unwrap();
-    let html = str::from_utf8(resp.body()).unwrap();
-    assert_eq!(html, "<h1>1.1.0</h1>");
-  }
-}
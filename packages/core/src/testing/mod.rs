@@ -1,12 +1,11 @@
-#![cfg(test)]
 #![allow(dead_code, unused_imports, unused_variables)]
 
 mod fixtures;
-#[cfg(all(feature = "source", feature = "remote"))]
+#[cfg(feature = "source")]
 mod mock;
 mod temp;
 
 pub use fixtures::*;
-#[cfg(all(feature = "source", feature = "remote"))]
+#[cfg(feature = "source")]
 pub use mock::*;
 pub use temp::*;
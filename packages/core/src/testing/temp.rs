@@ -41,7 +41,7 @@ impl TempDir {
   fn next_dir_for_today() -> PathBuf {
     let now = OffsetDateTime::now_local().unwrap_or_else(|_| OffsetDateTime::now_utc());
     let today = now
-      .format(&format_description::parse("[year][month][day]").unwrap())
+      .format(&format_description::parse_borrowed::<2>("[year][month][day]").unwrap())
       .unwrap();
     let date_dir = base_dir().join(&today);
     fs::create_dir_all(&date_dir).unwrap();
@@ -1,11 +1,18 @@
+use crate::checksum::make_checksum;
 #[cfg(feature = "integrity")]
 use crate::integrity::{IntegrityChecker, IntegrityPolicy};
 use crate::remote::{ListRemoteBundleInfo, Remote, RemoteBundleInfo};
 #[cfg(feature = "signature")]
 use crate::signature::SignatureVerifier;
-use crate::source::{BundleManifestMetadata, BundleSource};
+use crate::source::{BundleCompatibility, BundleManifestMetadata, BundleSource};
 use serde::{Deserialize, Serialize};
+use std::hash::{BuildHasher, Hasher, RandomState};
 use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::OnceCell;
+
+/// Name of the file storing the install id, relative to the remote directory.
+pub const INSTALL_ID_FILENAME: &str = ".install_id";
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 #[serde(rename_all = "camelCase")]
@@ -18,6 +25,12 @@ pub struct BundleUpdateInfo {
   pub integrity: Option<String>,
   pub signature: Option<String>,
   pub last_modified: Option<String>,
+  pub rollout_percentage: Option<u8>,
+  pub min_app_version: Option<String>,
+  pub compatible_app_version: Option<String>,
+  pub required_capabilities: Vec<String>,
+  /// Whether the version is compatible with the host app of the source.
+  pub is_compatible: bool,
 }
 
 impl From<&BundleUpdateInfo> for RemoteBundleInfo {
@@ -29,6 +42,10 @@ impl From<&BundleUpdateInfo> for RemoteBundleInfo {
       integrity: value.integrity.clone(),
       signature: value.signature.clone(),
       last_modified: value.last_modified.clone(),
+      rollout_percentage: value.rollout_percentage,
+      min_app_version: value.min_app_version.clone(),
+      compatible_app_version: value.compatible_app_version.clone(),
+      required_capabilities: value.required_capabilities.clone(),
     }
   }
 }
@@ -40,6 +57,10 @@ impl From<&RemoteBundleInfo> for BundleManifestMetadata {
       integrity: value.integrity.clone(),
       signature: value.signature.clone(),
       last_modified: value.last_modified.clone(),
+      rollout_percentage: value.rollout_percentage,
+      min_app_version: value.min_app_version.clone(),
+      compatible_app_version: value.compatible_app_version.clone(),
+      required_capabilities: value.required_capabilities.clone(),
     }
   }
 }
@@ -48,6 +69,8 @@ impl From<&RemoteBundleInfo> for BundleManifestMetadata {
 #[non_exhaustive]
 pub struct UpdaterConfig {
   pub(crate) channel: Option<String>,
+  pub(crate) app_version: Option<String>,
+  pub(crate) install_id: Option<String>,
   #[cfg(feature = "integrity")]
   pub(crate) integrity_checker: IntegrityChecker,
   #[cfg(feature = "integrity")]
@@ -66,6 +89,24 @@ impl UpdaterConfig {
     self
   }
 
+  /// Set the version (semver) of the host app.
+  ///
+  /// Updates requiring a newer app version are not reported as available. Defaults to the
+  /// version of the source host information.
+  pub fn app_version(mut self, version: impl Into<String>) -> Self {
+    self.app_version = Some(version.into());
+    self
+  }
+
+  /// Set the id used to place this install in rollout buckets.
+  ///
+  /// When not set, a random id is generated once and persisted in the remote directory
+  /// of the source.
+  pub fn install_id(mut self, id: impl Into<String>) -> Self {
+    self.install_id = Some(id.into());
+    self
+  }
+
   #[cfg(feature = "integrity")]
   pub fn integrity_checker(mut self, checker: IntegrityChecker) -> Self {
     self.integrity_checker = checker;
@@ -89,6 +130,7 @@ pub struct Updater {
   source: Arc<BundleSource>,
   remote: Arc<Remote>,
   config: UpdaterConfig,
+  install_id: OnceCell<String>,
 }
 
 impl Updater {
@@ -101,9 +143,44 @@ impl Updater {
       source,
       remote,
       config: config.unwrap_or_default(),
+      install_id: OnceCell::new(),
     }
   }
 
+  /// Returns the stable id of this install.
+  pub async fn install_id(&self) -> crate::Result<&str> {
+    let id = self
+      .install_id
+      .get_or_try_init(|| async {
+        if let Some(id) = &self.config.install_id {
+          return Ok(id.to_string());
+        }
+        let filepath = self.source.remote_dir().join(INSTALL_ID_FILENAME);
+        match tokio::fs::read_to_string(&filepath).await {
+          Ok(id) if !id.trim().is_empty() => return Ok(id.trim().to_string()),
+          Ok(_) => {}
+          Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+          Err(e) => return Err(crate::Error::from(e)),
+        }
+        let id = generate_install_id();
+        tokio::fs::create_dir_all(self.source.remote_dir()).await?;
+        tokio::fs::write(&filepath, &id).await?;
+        Ok(id)
+      })
+      .await?;
+    Ok(id)
+  }
+
+  /// Returns the rollout bucket (0-99) of this install for the bundle.
+  ///
+  /// The bucket only depends on the install id and the bundle name, so an install
+  /// which received a version at 5% rollout keeps receiving it at 50%.
+  pub async fn rollout_bucket(&self, bundle_name: &str) -> crate::Result<u8> {
+    let install_id = self.install_id().await?;
+    let key = format!("{install_id}:{bundle_name}");
+    Ok((make_checksum(0, key.as_bytes()) % 100) as u8)
+  }
+
   pub async fn list_remotes(&self) -> crate::Result<Vec<ListRemoteBundleInfo>> {
     self.remote.list_bundles(self.config.channel.as_ref()).await
   }
@@ -120,11 +197,18 @@ impl Updater {
     Ok(info)
   }
 
+  /// Downloads a bundle version and writes it to the remote directory of the source.
+  ///
+  /// Without an explicit `version`, the version deployed to the channel is downloaded, and
+  /// it must be compatible with the host app and eligible for this install (rollout
+  /// percentage and min app version), as reported by [`Updater::get_update`].
   pub async fn download_update(
     &self,
     bundle_name: impl Into<String>,
     version: Option<String>,
   ) -> crate::Result<RemoteBundleInfo> {
+    let check_eligible = version.is_none();
+    #[cfg_attr(not(feature = "integrity"), allow(unused_variables))]
     let (info, bundle, data) = match version {
       Some(ver) => {
         self
@@ -139,6 +223,10 @@ impl Updater {
           .await
       }
     }?;
+    if check_eligible {
+      self.check_compatible(&info)?;
+      self.check_eligible(&info).await?;
+    }
     #[cfg(feature = "integrity")]
     {
       match self.config.integrity_policy {
@@ -192,11 +280,13 @@ impl Updater {
 
   async fn to_update_info(&self, info: RemoteBundleInfo) -> crate::Result<BundleUpdateInfo> {
     let local_version = self.source.load_version(&info.name).await?;
-    let is_available = if let Some(ref local_ver) = local_version {
+    let is_newer = if let Some(ref local_ver) = local_version {
       local_ver.version != info.version
     } else {
       true
     };
+    let is_compatible = self.is_compatible(&info)?;
+    let is_available = is_newer && is_compatible && self.is_eligible(&info).await?;
     Ok(BundleUpdateInfo {
       name: info.name,
       version: info.version,
@@ -206,6 +296,280 @@ impl Updater {
       integrity: info.integrity.clone(),
       signature: info.signature.clone(),
       last_modified: info.last_modified.clone(),
+      rollout_percentage: info.rollout_percentage,
+      min_app_version: info.min_app_version.clone(),
+      compatible_app_version: info.compatible_app_version.clone(),
+      required_capabilities: info.required_capabilities.clone(),
+      is_compatible,
     })
   }
+
+  fn is_compatible(&self, info: &RemoteBundleInfo) -> crate::Result<bool> {
+    match self.check_compatible(info) {
+      Ok(()) => Ok(true),
+      Err(crate::Error::BundleIncompatible { .. }) => Ok(false),
+      Err(e) => Err(e),
+    }
+  }
+
+  fn check_compatible(&self, info: &RemoteBundleInfo) -> crate::Result<()> {
+    let host = match self.source.host() {
+      Some(x) => x,
+      None => return Ok(()),
+    };
+    let compat = BundleCompatibility::parse(
+      info.compatible_app_version.as_deref(),
+      &info.required_capabilities,
+    )
+    .map_err(|_| {
+      crate::Error::invalid_remote_bundle(format!(
+        "invalid compatible app version: {}",
+        info.compatible_app_version.as_deref().unwrap_or_default()
+      ))
+    })?;
+    compat
+      .check(host)
+      .map_err(|e| crate::Error::bundle_incompatible(&info.name, &info.version, e))
+  }
+
+  async fn is_eligible(&self, info: &RemoteBundleInfo) -> crate::Result<bool> {
+    match self.check_eligible(info).await {
+      Ok(()) => Ok(true),
+      Err(crate::Error::UpdateNotEligible { .. }) => Ok(false),
+      Err(e) => Err(e),
+    }
+  }
+
+  async fn check_eligible(&self, info: &RemoteBundleInfo) -> crate::Result<()> {
+    if let Some(min_app_version) = &info.min_app_version
+      && let Some(app_version) = self.app_version()?
+    {
+      let min_app_version = semver::Version::parse(min_app_version).map_err(|_| {
+        crate::Error::invalid_remote_bundle(format!("invalid min app version: {min_app_version}"))
+      })?;
+      if app_version < min_app_version {
+        return Err(crate::Error::update_not_eligible(
+          &info.name,
+          &info.version,
+          format!("app version {app_version} is lower than {min_app_version}"),
+        ));
+      }
+    }
+    if let Some(percentage) = info.rollout_percentage {
+      let bucket = self.rollout_bucket(&info.name).await?;
+      if bucket >= percentage {
+        return Err(crate::Error::update_not_eligible(
+          &info.name,
+          &info.version,
+          format!("install is out of the {percentage}% rollout"),
+        ));
+      }
+    }
+    Ok(())
+  }
+
+  fn app_version(&self) -> crate::Result<Option<semver::Version>> {
+    match &self.config.app_version {
+      Some(x) => Ok(Some(
+        semver::Version::parse(x).map_err(crate::Error::generic)?,
+      )),
+      None => Ok(self.source.host().and_then(|x| x.get_version()).cloned()),
+    }
+  }
+}
+
+fn generate_install_id() -> String {
+  let nanos = SystemTime::now()
+    .duration_since(UNIX_EPOCH)
+    .map(|x| x.as_nanos())
+    .unwrap_or_default();
+  let mut id = String::with_capacity(32);
+  for i in 0..2u8 {
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u128(nanos);
+    hasher.write_u32(std::process::id());
+    hasher.write_u8(i);
+    id.push_str(&format!("{:016x}", hasher.finish()));
+  }
+  id
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::source::HostInfo;
+  use crate::testing::{MockBundle, MockSystem};
+
+  fn updater(system: &MockSystem, config: UpdaterConfig) -> Updater {
+    let source = Arc::new(system.source().get_source());
+    let remote = Arc::new(system.remote().get_remote());
+    Updater::new(source, remote, Some(config))
+  }
+
+  #[tokio::test]
+  async fn install_id_is_persisted() {
+    let system = MockSystem::new();
+    let install_id = updater(&system, UpdaterConfig::new())
+      .install_id()
+      .await
+      .unwrap()
+      .to_string();
+    assert_eq!(install_id.len(), 32);
+    assert_eq!(
+      updater(&system, UpdaterConfig::new())
+        .install_id()
+        .await
+        .unwrap(),
+      install_id
+    );
+    assert_eq!(
+      updater(&system, UpdaterConfig::new().install_id("custom"))
+        .install_id()
+        .await
+        .unwrap(),
+      "custom"
+    );
+  }
+
+  #[tokio::test]
+  async fn rollout_percentage() {
+    let mut system = MockSystem::new();
+    system
+      .remote_mut()
+      .add_bundle(MockBundle::new("app", "1.1.0").with_rollout_percentage(50))
+      .set_bundle_current_version("app", "1.1.0");
+    let mut available = 0;
+    for i in 0..100 {
+      let updater = updater(
+        &system,
+        UpdaterConfig::new().install_id(format!("install-{i}")),
+      );
+      let bucket = updater.rollout_bucket("app").await.unwrap();
+      assert!(bucket < 100);
+      let info = updater.get_update("app").await.unwrap();
+      assert_eq!(info.rollout_percentage, Some(50));
+      assert_eq!(info.is_available, bucket < 50);
+      if info.is_available {
+        available += 1;
+      }
+    }
+    assert!(available > 20 && available < 80);
+  }
+
+  #[tokio::test]
+  async fn rollout_is_stable_when_percentage_grows() {
+    let mut system = MockSystem::new();
+    system
+      .remote_mut()
+      .add_bundle(MockBundle::new("app", "1.1.0").with_rollout_percentage(5))
+      .set_bundle_current_version("app", "1.1.0");
+    let mut rolled_out = vec![];
+    for i in 0..100 {
+      let updater = updater(
+        &system,
+        UpdaterConfig::new().install_id(format!("install-{i}")),
+      );
+      if updater.get_update("app").await.unwrap().is_available {
+        rolled_out.push(i);
+      }
+    }
+    assert!(!rolled_out.is_empty());
+    system
+      .remote_mut()
+      .add_bundle(MockBundle::new("app", "1.1.0").with_rollout_percentage(50));
+    for i in rolled_out {
+      let updater = updater(
+        &system,
+        UpdaterConfig::new().install_id(format!("install-{i}")),
+      );
+      assert!(updater.get_update("app").await.unwrap().is_available);
+    }
+  }
+
+  #[tokio::test]
+  async fn min_app_version() {
+    let mut system = MockSystem::new();
+    system
+      .remote_mut()
+      .add_bundle(MockBundle::new("app", "1.1.0").with_min_app_version("2.0.0"))
+      .set_bundle_current_version("app", "1.1.0");
+    let info = updater(&system, UpdaterConfig::new().app_version("1.9.0"))
+      .get_update("app")
+      .await
+      .unwrap();
+    assert_eq!(info.min_app_version.as_deref(), Some("2.0.0"));
+    assert!(!info.is_available);
+    let info = updater(&system, UpdaterConfig::new().app_version("2.0.0"))
+      .get_update("app")
+      .await
+      .unwrap();
+    assert!(info.is_available);
+    let info = updater(&system, UpdaterConfig::new())
+      .get_update("app")
+      .await
+      .unwrap();
+    assert!(info.is_available);
+  }
+
+  #[tokio::test]
+  async fn download_update_requires_eligibility() {
+    let mut system = MockSystem::new();
+    system
+      .remote_mut()
+      .add_bundle(MockBundle::new("app", "1.1.0").with_min_app_version("2.0.0"))
+      .set_bundle_current_version("app", "1.1.0")
+      .allow_other_versions(true);
+    let updater = updater(&system, UpdaterConfig::new().app_version("1.9.0"));
+    let err = updater.download_update("app", None).await.unwrap_err();
+    assert!(matches!(err, crate::Error::UpdateNotEligible { .. }));
+    let filepath = updater.source.remote_dir().join("app/app_1.1.0.wvb");
+    assert!(!filepath.exists());
+    // explicitly requested versions are not checked
+    let info = updater
+      .download_update("app", Some("1.1.0".to_string()))
+      .await
+      .unwrap();
+    assert_eq!(info.version, "1.1.0");
+
+    let updater = self::updater(&system, UpdaterConfig::new().app_version("2.0.0"));
+    updater.download_update("app", None).await.unwrap();
+  }
+
+  #[tokio::test]
+  async fn incompatible_update() {
+    let mut system = MockSystem::new();
+    system
+      .remote_mut()
+      .add_bundle(
+        MockBundle::new("app", "1.1.0")
+          .with_compatible_app_version("^2")
+          .with_required_capability("fs"),
+      )
+      .set_bundle_current_version("app", "1.1.0");
+    let remote = Arc::new(system.remote().get_remote());
+
+    let host = HostInfo::new()
+      .version(semver::Version::new(1, 0, 0))
+      .capability("fs");
+    let source = Arc::new(system.source().get_source_with_host(host));
+    let info = Updater::new(source, remote.clone(), None)
+      .get_update("app")
+      .await
+      .unwrap();
+    assert_eq!(info.compatible_app_version.as_deref(), Some("^2"));
+    assert_eq!(info.required_capabilities, vec!["fs".to_string()]);
+    assert!(!info.is_compatible);
+    assert!(!info.is_available);
+
+    let host = HostInfo::new()
+      .version(semver::Version::new(2, 1, 0))
+      .capability("fs");
+    let source = Arc::new(system.source().get_source_with_host(host));
+    let info = Updater::new(source, remote, None)
+      .get_update("app")
+      .await
+      .unwrap();
+    assert!(info.is_compatible);
+    assert!(info.is_available);
+  }
 }
@@ -0,0 +1,316 @@
+use crate::bundle::BundleDataReader;
+use crate::header::{HeaderReader, HeaderReaderOptions};
+use crate::index::IndexReader;
+use crate::metadata::{MetadataReader, MetadataReaderOptions};
+use crate::reader::Reader;
+use crate::{Bundle, BundleDescriptor};
+use std::fmt;
+use std::io::{Cursor, ErrorKind, Read, Seek};
+
+#[cfg(feature = "async")]
+use crate::bundle::AsyncBundleDataReader;
+#[cfg(feature = "async")]
+use crate::{AsyncHeaderReader, AsyncIndexReader, AsyncMetadataReader, AsyncReader};
+#[cfg(feature = "async")]
+use tokio::io::{AsyncRead, AsyncSeek};
+
+/// Options for verifying the checksums of a bundle.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct VerifyOptions {
+  /// Seed of entry checksums, as set with [`crate::BundleBuilderOptions::data_checksum_seed`].
+  pub data_checksum_seed: u32,
+}
+
+impl VerifyOptions {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn data_checksum_seed(mut self, seed: u32) -> Self {
+    self.data_checksum_seed = seed;
+    self
+  }
+}
+
+/// Part of a bundle which doesn't match its checksum.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyFailure {
+  Header,
+  Index,
+  Metadata,
+  /// Entry at the path, whose data doesn't match its checksum or lies outside the bundle.
+  Entry(String),
+}
+
+impl fmt::Display for VerifyFailure {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self {
+      Self::Header => write!(f, "header"),
+      Self::Index => write!(f, "index"),
+      Self::Metadata => write!(f, "metadata"),
+      Self::Entry(path) => write!(f, "entry `{path}`"),
+    }
+  }
+}
+
+/// Records a failure for errors caused by corrupted data, and returns other errors.
+fn check(
+  result: crate::Result<()>,
+  failure: impl FnOnce() -> VerifyFailure,
+  failures: &mut Vec<VerifyFailure>,
+) -> crate::Result<()> {
+  match result {
+    Ok(()) => Ok(()),
+    Err(
+      crate::Error::InvalidHeaderChecksum
+      | crate::Error::InvalidIndexChecksum
+      | crate::Error::InvalidMetadataChecksum
+      | crate::Error::ChecksumMismatch
+      | crate::Error::InvalidEntry(_),
+    ) => {
+      failures.push(failure());
+      Ok(())
+    }
+    Err(crate::Error::Io(e)) if e.kind() == ErrorKind::UnexpectedEof => {
+      failures.push(failure());
+      Ok(())
+    }
+    Err(e) => Err(e),
+  }
+}
+
+fn into_result(failures: Vec<VerifyFailure>) -> crate::Result<()> {
+  if failures.is_empty() {
+    Ok(())
+  } else {
+    Err(crate::Error::BundleVerifyFailed(failures))
+  }
+}
+
+/// Returns entry paths in a stable order, so failures are reported consistently.
+fn sorted_paths(descriptor: &BundleDescriptor) -> Vec<&String> {
+  let mut paths = descriptor.index.entries().keys().collect::<Vec<_>>();
+  paths.sort();
+  paths
+}
+
+impl BundleDescriptor {
+  /// Checks the header, index, metadata and every entry of the bundle against their
+  /// checksums.
+  ///
+  /// Every part which doesn't match is reported in [`crate::Error::BundleVerifyFailed`].
+  ///
+  /// # Arguments
+  ///
+  /// * `reader` - A reader positioned at the start of the bundle file
+  pub fn verify<R: Read + Seek>(&self, reader: R) -> crate::Result<()> {
+    self.verify_with_options(reader, VerifyOptions::default())
+  }
+
+  pub fn verify_with_options<R: Read + Seek>(
+    &self,
+    mut reader: R,
+    options: VerifyOptions,
+  ) -> crate::Result<()> {
+    let mut failures = vec![];
+    let header_options = HeaderReaderOptions::new().verify_checksum(true);
+    let result = HeaderReader::new_with_options(&mut reader, header_options)
+      .read()
+      .map(|_| ());
+    check(result, || VerifyFailure::Header, &mut failures)?;
+    let result = IndexReader::new(&mut reader, self.header).verify();
+    check(result, || VerifyFailure::Index, &mut failures)?;
+    let metadata_options = MetadataReaderOptions::new().verify_checksum(true);
+    let result = MetadataReader::new_with_options(&mut reader, self.header, metadata_options)
+      .read()
+      .map(|_| ());
+    check(result, || VerifyFailure::Metadata, &mut failures)?;
+    let mut data = BundleDataReader::new(&mut reader, self.header.index_end_offset());
+    for path in sorted_paths(self) {
+      let entry = self.index.get_entry(path).unwrap();
+      let result = data.verify_entry(entry, &options);
+      check(result, || VerifyFailure::Entry(path.clone()), &mut failures)?;
+    }
+    into_result(failures)
+  }
+
+  /// Asynchronously checks the bundle against its checksums, see [`BundleDescriptor::verify`].
+  #[cfg(feature = "async")]
+  pub async fn async_verify<R: AsyncRead + AsyncSeek + Unpin>(
+    &self,
+    reader: R,
+  ) -> crate::Result<()> {
+    self
+      .async_verify_with_options(reader, VerifyOptions::default())
+      .await
+  }
+
+  #[cfg(feature = "async")]
+  pub async fn async_verify_with_options<R: AsyncRead + AsyncSeek + Unpin>(
+    &self,
+    mut reader: R,
+    options: VerifyOptions,
+  ) -> crate::Result<()> {
+    let mut failures = vec![];
+    let header_options = HeaderReaderOptions::new().verify_checksum(true);
+    let result = AsyncHeaderReader::new_with_options(&mut reader, header_options)
+      .read()
+      .await
+      .map(|_| ());
+    check(result, || VerifyFailure::Header, &mut failures)?;
+    let result = AsyncIndexReader::new(&mut reader, self.header)
+      .verify()
+      .await;
+    check(result, || VerifyFailure::Index, &mut failures)?;
+    let metadata_options = MetadataReaderOptions::new().verify_checksum(true);
+    let result = AsyncMetadataReader::new_with_options(&mut reader, self.header, metadata_options)
+      .read()
+      .await
+      .map(|_| ());
+    check(result, || VerifyFailure::Metadata, &mut failures)?;
+    let mut data = AsyncBundleDataReader::new(&mut reader, self.header.index_end_offset());
+    for path in sorted_paths(self) {
+      let entry = self.index.get_entry(path).unwrap();
+      let result = data.verify_entry(entry, &options).await;
+      check(result, || VerifyFailure::Entry(path.clone()), &mut failures)?;
+    }
+    into_result(failures)
+  }
+}
+
+impl Bundle {
+  /// Checks every entry of the bundle against its checksum.
+  ///
+  /// The header and index of a bundle in memory are encoded when it's written, so only entries
+  /// are checked. Use [`BundleDescriptor::verify`] to check a bundle file.
+  pub fn verify(&self) -> crate::Result<()> {
+    self.verify_with_options(VerifyOptions::default())
+  }
+
+  pub fn verify_with_options(&self, options: VerifyOptions) -> crate::Result<()> {
+    let mut failures = vec![];
+    let mut data = BundleDataReader::new(Cursor::new(&self.data), 0);
+    for path in sorted_paths(&self.descriptor) {
+      let entry = self.descriptor.index.get_entry(path).unwrap();
+      let result = data.verify_entry(entry, &options);
+      check(result, || VerifyFailure::Entry(path.clone()), &mut failures)?;
+    }
+    into_result(failures)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{BundleBuilderOptions, BundleEntry, BundleReader, BundleWriter, Writer};
+
+  fn bundle() -> Bundle {
+    let mut builder = Bundle::builder();
+    builder.insert_entry(
+      "/index.html",
+      BundleEntry::new(b"<h1>Hello</h1>", "text/html", None),
+    );
+    builder.insert_entry(
+      "/index.js",
+      BundleEntry::new(b"console.log('hello');", "text/javascript", None),
+    );
+    builder.metadata_mut().set_name("app");
+    builder.build().unwrap()
+  }
+
+  fn write(bundle: &Bundle) -> Vec<u8> {
+    let mut data = vec![];
+    BundleWriter::new(Cursor::new(&mut data))
+      .write(bundle)
+      .unwrap();
+    data
+  }
+
+  fn failures(result: crate::Result<()>) -> Vec<VerifyFailure> {
+    match result {
+      Err(crate::Error::BundleVerifyFailed(failures)) => failures,
+      x => panic!("unexpected result: {x:?}"),
+    }
+  }
+
+  #[test]
+  fn verify() {
+    let bundle = bundle();
+    bundle.verify().unwrap();
+    let data = write(&bundle);
+    let descriptor: BundleDescriptor = BundleReader::new(Cursor::new(&data)).read().unwrap();
+    descriptor.verify(Cursor::new(&data)).unwrap();
+  }
+
+  #[test]
+  fn report_every_failure() {
+    let bundle = bundle();
+    let mut data = write(&bundle);
+    let descriptor: BundleDescriptor = BundleReader::new(Cursor::new(&data)).read().unwrap();
+    let base = descriptor.header.index_end_offset() as usize;
+    for path in ["/index.html", "/index.js"] {
+      let entry = descriptor.index.get_entry(path).unwrap();
+      data[base + entry.offset() as usize] ^= 0xff;
+    }
+    let index_offset = descriptor.header.index_offset() as usize;
+    data[index_offset] ^= 0xff;
+    assert_eq!(
+      failures(descriptor.verify(Cursor::new(&data))),
+      vec![
+        VerifyFailure::Index,
+        VerifyFailure::Entry("/index.html".to_string()),
+        VerifyFailure::Entry("/index.js".to_string()),
+      ]
+    );
+
+    // entries past the end of the file
+    let data = &data[..base + 1];
+    assert_eq!(
+      failures(descriptor.verify(Cursor::new(data))),
+      vec![
+        VerifyFailure::Index,
+        VerifyFailure::Entry("/index.html".to_string()),
+        VerifyFailure::Entry("/index.js".to_string()),
+      ]
+    );
+  }
+
+  #[test]
+  fn verify_data() {
+    let bundle = bundle();
+    let mut data = write(&bundle);
+    let mut descriptor: BundleDescriptor = BundleReader::new(Cursor::new(&data)).read().unwrap();
+    let entry = descriptor.index.get_entry("/index.js").unwrap();
+    let offset = descriptor.header.index_end_offset() + entry.offset() + entry.len();
+    data[offset as usize] ^= 0xff;
+    // the data itself is intact, only its checksum is not
+    assert!(descriptor.get_data(Cursor::new(&data), "/index.js").is_ok());
+    descriptor.set_verify_data(Some(VerifyOptions::new()));
+    assert!(matches!(
+      descriptor.get_data(Cursor::new(&data), "/index.js"),
+      Err(crate::Error::ChecksumMismatch)
+    ));
+    assert!(
+      descriptor
+        .get_data(Cursor::new(&data), "/index.html")
+        .is_ok()
+    );
+  }
+
+  #[test]
+  fn data_checksum_seed() {
+    let mut options = BundleBuilderOptions::new();
+    options.data_checksum_seed(42);
+    let mut builder = crate::BundleBuilder::new_with_options(options);
+    builder.insert_entry("/a.txt", BundleEntry::new(b"a", "text/plain", None));
+    let mut bundle = builder.build().unwrap();
+    assert_eq!(
+      failures(bundle.verify()),
+      vec![VerifyFailure::Entry("/a.txt".to_string())]
+    );
+    let options = VerifyOptions::new().data_checksum_seed(42);
+    bundle.verify_with_options(options).unwrap();
+    bundle.set_verify_data(Some(options));
+    assert_eq!(bundle.get_data("/a.txt").unwrap().unwrap(), b"a");
+  }
+}
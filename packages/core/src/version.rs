@@ -3,25 +3,45 @@ use std::fmt::{Display, Formatter};
 pub(crate) const VERSION_LEN: usize = 1;
 
 /// Version fo Webview Bundle.
-#[derive(Debug, PartialEq, Eq, Copy, Clone, Default)]
+///
+/// `V1` remains the default so written bundles stay readable by existing clients.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Copy, Clone, Default)]
 pub enum Version {
   /// Version 1
   #[default]
   V1,
+  /// Version 2
+  ///
+  /// Stores the metadata size in the header and the metadata section right after the header,
+  /// so metadata can be read without seeking to the end of the file.
+  V2,
 }
 
 impl Version {
+  /// The newest format version.
+  pub const LATEST: Version = Version::V2;
+
+  /// All format versions, from oldest to newest.
+  pub const ALL: [Version; 2] = [Version::V1, Version::V2];
+
   pub const fn bytes(&self) -> [u8; VERSION_LEN] {
     match self {
       Version::V1 => [0x01],
+      Version::V2 => [0x02],
     }
   }
+
+  /// Parses a version from its byte representation.
+  pub fn from_bytes(bytes: [u8; VERSION_LEN]) -> Option<Version> {
+    Self::ALL.into_iter().find(|x| x.bytes() == bytes)
+  }
 }
 
 impl Display for Version {
   fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
     let s = match self {
       Self::V1 => "v1",
+      Self::V2 => "v2",
     };
     f.write_str(s)
   }
@@ -39,10 +59,20 @@ mod tests {
   #[test]
   fn bytes() {
     assert_eq!(Version::V1.bytes(), [0x01]);
+    assert_eq!(Version::V2.bytes(), [0x02]);
+  }
+
+  #[test]
+  fn from_bytes() {
+    assert_eq!(Version::from_bytes([0x01]), Some(Version::V1));
+    assert_eq!(Version::from_bytes([0x02]), Some(Version::V2));
+    assert_eq!(Version::from_bytes([0x03]), None);
+    assert!(Version::V1 < Version::LATEST);
   }
 
   #[test]
   fn display() {
     assert_eq!(format!("{}", Version::V1), "v1");
+    assert_eq!(format!("{}", Version::V2), "v2");
   }
 }
@@ -52,6 +52,16 @@ describe('bundle', () => {
     expect(bundle.descriptor().header().version()).toEqual('v1');
   });
 
+  it('v2', () => {
+    const builder = new BundleBuilder('v2');
+    builder.insertEntry('/index.js', INDEX_JS_BUF);
+    builder.setMetadata('name', 'app');
+    const bundle = builder.build();
+    expect(bundle.descriptor().header().version()).toEqual('v2');
+    expect(bundle.descriptor().metadata()).toEqual({ name: 'app' });
+    expect(bundle.getData('/index.js')).toEqual(Buffer.from(INDEX_JS_BUF));
+  });
+
   it('default version', () => {
     const builder = new BundleBuilder();
     const bundle = builder.build();
@@ -84,6 +94,21 @@ describe('bundle', () => {
       foo: 'bar',
     });
   });
+
+  it('detect content type', () => {
+    const png = Buffer.from([0x89, 0x50, 0x4e, 0x47, 0x0d, 0x0a, 0x1a, 0x0a]);
+    const builder = new BundleBuilder();
+    builder.insertEntry('/index.js', INDEX_JS_BUF);
+    builder.insertEntry('/logo.json', png);
+    builder.insertEntry('/icon.svg', Buffer.from('<?xml version="1.0"?><svg></svg>'));
+    builder.insertEntry('/LICENSE', Buffer.from('MIT License'));
+    const index = builder.build().descriptor().index();
+    expect(index.getEntry('/index.js')?.contentType).toEqual('text/javascript');
+    // sniffed data takes precedence over the extension, except for svg
+    expect(index.getEntry('/logo.json')?.contentType).toEqual('image/png');
+    expect(index.getEntry('/icon.svg')?.contentType).toEqual('image/svg+xml');
+    expect(index.getEntry('/LICENSE')?.contentType).toEqual('application/octet-stream');
+  });
 });
 
 describe('read/write', () => {
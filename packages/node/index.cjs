@@ -514,6 +514,7 @@ module.exports.BundleDescriptor = nativeBinding.BundleDescriptor
 module.exports.BundleProtocol = nativeBinding.BundleProtocol
 module.exports.BundleSource = nativeBinding.BundleSource
 module.exports.Header = nativeBinding.Header
+module.exports.HttpResponseStream = nativeBinding.HttpResponseStream
 module.exports.Index = nativeBinding.Index
 module.exports.LocalProtocol = nativeBinding.LocalProtocol
 module.exports.Remote = nativeBinding.Remote
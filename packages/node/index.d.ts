@@ -55,6 +55,18 @@ export declare class Bundle {
    * @returns {number | null} xxHash-32 checksum or null if not found
    */
   getDataChecksum(path: string): number | null
+  /**
+   * Checks every entry of the bundle against its checksum.
+   *
+   * @throws {Error} If the data of any entry doesn't match its checksum
+   *
+   * @example
+   * ```typescript
+   * const bundle = await readBundle("app.wvb");
+   * bundle.verify();
+   * ```
+   */
+  verify(): void
 }
 
 /**
@@ -146,6 +158,35 @@ export declare class BundleBuilder {
    * ```
    */
   removeEntry(path: string): boolean
+  /**
+   * Returns the bundle metadata set on the builder.
+   *
+   * @returns {Record<string, string>} Bundle metadata key/values
+   */
+  metadata(): Record<string, string>
+  /**
+   * Sets a bundle metadata value.
+   *
+   * Well-known keys are `name`, `version`, `builtAt`, `gitCommit`,
+   * `compatibleAppVersion` and `requiredCapabilities`.
+   *
+   * @param {string} key - Metadata key
+   * @param {string} value - Metadata value
+   *
+   * @example
+   * ```typescript
+   * builder.setMetadata("name", "app");
+   * builder.setMetadata("version", "1.0.0");
+   * ```
+   */
+  setMetadata(key: string, value: string): void
+  /**
+   * Removes a bundle metadata value.
+   *
+   * @param {string} key - Metadata key
+   * @returns {boolean} True if the value was removed, false if not found
+   */
+  removeMetadata(key: string): boolean
   /**
    * Checks if a file path exists in the builder.
    *
@@ -205,13 +246,27 @@ export declare class BundleDescriptor {
    * @returns {Index} Bundle index with file metadata
    */
   index(): Index
+  /**
+   * Returns the bundle metadata (e.g. name, version, gitCommit).
+   *
+   * Bundles written without metadata return an empty object.
+   *
+   * @returns {Record<string, string>} Bundle metadata key/values
+   *
+   * @example
+   * ```typescript
+   * const metadata = descriptor.metadata();
+   * console.log(metadata.name, metadata.version);
+   * ```
+   */
+  metadata(): Record<string, string>
 }
 
 /**
  * Protocol handler for serving files from bundle sources.
  *
  * Serves web resources from `.wvb` bundle files, supporting:
- * - GET and HEAD HTTP methods
+ * - GET and HEAD HTTP methods, and OPTIONS for CORS preflight requests
  * - HTTP Range requests for streaming
  * - Content-Type and custom HTTP headers
  *
@@ -243,9 +298,55 @@ export declare class BundleProtocol {
    *   remoteDir: "./remote"
    * });
    * const protocol = new BundleProtocol(source);
+   *
+   * // Serves "bundle://localhost/app/index.html" from bundle "app"
+   * const pathProtocol = new BundleProtocol(source, {
+   *   uriResolver: { kind: "path" }
+   * });
+   *
+   * // Serves "/features/billing" from bundle "billing", and other paths from
+   * // bundle "shell", looking up entries in "shell-patch" first
+   * const mountProtocol = new BundleProtocol(source, {
+   *   uriResolver: {
+   *     kind: "mount",
+   *     mounts: { "/": "shell", "/features/billing": "billing" }
+   *   },
+   *   overlays: { shell: ["shell-patch"] }
+   * });
    * ```
    */
-  constructor(source: BundleSource)
+  constructor(source: BundleSource, options?: BundleProtocolOptions | undefined | null)
+  /**
+   * Returns statistics of the entry cache, or `null` when caching is disabled.
+   *
+   * @returns {EntryCacheStats | null} Cache statistics
+   *
+   * @example
+   * ```typescript
+   * const protocol = new BundleProtocol(source, { cacheSize: 32 * 1024 * 1024 });
+   * const stats = protocol.cacheStats();
+   * console.log(`hits: ${stats?.hits}, misses: ${stats?.misses}`);
+   * ```
+   */
+  cacheStats(): EntryCacheStats | null
+  /**
+   * Warms up the entries of a bundle into the cache, returning the number of entries added.
+   *
+   * Bundles are warmed up in the background when loaded, this waits for it instead.
+   *
+   * @param {string} bundleName - Name of the bundle to warm up
+   * @returns {Promise<number>} Number of entries added to the cache
+   *
+   * @example
+   * ```typescript
+   * const protocol = new BundleProtocol(source, {
+   *   cacheSize: 32 * 1024 * 1024,
+   *   warmup: ["/index.html"]
+   * });
+   * await protocol.warmup("app");
+   * ```
+   */
+  warmup(bundleName: string): Promise<number>
   /**
    * Handles an HTTP request and returns a response.
    *
@@ -277,6 +378,27 @@ export declare class BundleProtocol {
    * ```
    */
   handle(method: HttpMethod, uri: string, headers?: Record<string, string> | undefined | null): Promise<HttpResponse>
+  /**
+   * Handles an HTTP request and returns a response with a streamed body.
+   *
+   * Entries are decompressed while the body is read, and range requests stop
+   * decompressing at the end of the range.
+   *
+   * @param {HttpMethod} method - HTTP method (GET or HEAD)
+   * @param {string} uri - Request URI (e.g., "bundle://app/index.html")
+   * @param {Record<string, string>} [headers] - Optional request headers
+   * @returns {Promise<HttpResponseStream>} HTTP response
+   *
+   * @example
+   * ```typescript
+   * const response = await protocol.handleStream("GET", "bundle://app/video.mp4");
+   * let chunk;
+   * while ((chunk = await response.read()) != null) {
+   *   stream.write(chunk);
+   * }
+   * ```
+   */
+  handleStream(method: HttpMethod, uri: string, headers?: Record<string, string> | undefined | null): Promise<HttpResponseStream>
 }
 
 /**
@@ -476,6 +598,42 @@ export declare class Header {
   indexSize(): number
 }
 
+/**
+ * HTTP response whose body is read in chunks.
+ *
+ * Bundle entries are decompressed while the body is read, so large files (e.g. videos)
+ * are not held in memory at once.
+ *
+ * @example
+ * ```typescript
+ * const response = await protocol.handleStream("GET", "bundle://app/video.mp4");
+ * let chunk;
+ * while ((chunk = await response.read()) != null) {
+ *   stream.write(chunk);
+ * }
+ * ```
+ */
+export declare class HttpResponseStream {
+  /**
+   * Gets the HTTP status code.
+   *
+   * @returns {number} Status code
+   */
+  get status(): number
+  /**
+   * Gets the response headers.
+   *
+   * @returns {Record<string, string>} Response headers
+   */
+  get headers(): Record<string, string>
+  /**
+   * Reads the next chunk of the body.
+   *
+   * @returns {Promise<Buffer | null>} Next chunk, or `null` at the end of the body
+   */
+  read(): Promise<Buffer | null>
+}
+
 /**
  * Bundle index mapping file paths to their metadata.
  *
@@ -533,7 +691,8 @@ export declare class Index {
  * Protocol handler that proxies requests to localhost servers.
  *
  * Forwards requests to local development servers for hot-reloading workflows.
- * Features response caching and 304 Not Modified support.
+ * Request headers are forwarded and responses are cached and revalidated with
+ * conditional requests.
  *
  * @example
  * ```typescript
@@ -551,6 +710,7 @@ export declare class LocalProtocol {
    * Creates a new local protocol handler.
    *
    * @param {Record<string, string>} hosts - Map of custom hosts to localhost URLs
+   * @param {HttpOptions} [http] - HTTP client configuration, shared by all requests
    *
    * @example
    * ```typescript
@@ -560,7 +720,7 @@ export declare class LocalProtocol {
    * });
    * ```
    */
-  constructor(hosts: Record<string, string>)
+  constructor(hosts: Record<string, string>, http?: HttpOptions | undefined | null)
   /**
    * Handles an HTTP request by proxying to localhost.
    *
@@ -592,6 +752,26 @@ export declare class LocalProtocol {
    * ```
    */
   handle(method: HttpMethod, uri: string, headers?: Record<string, string> | undefined | null): Promise<HttpResponse>
+  /**
+   * Handles an HTTP request and returns a response with a streamed body.
+   *
+   * Responses are cached for revalidation, so the body is read as a single chunk.
+   *
+   * @param {HttpMethod} method - HTTP method
+   * @param {string} uri - Request URI (e.g., "app://myapp/api/data")
+   * @param {Record<string, string>} [headers] - Optional request headers
+   * @returns {Promise<HttpResponseStream>} HTTP response
+   *
+   * @example
+   * ```typescript
+   * const response = await protocol.handleStream("GET", "app://myapp/video.mp4");
+   * let chunk;
+   * while ((chunk = await response.read()) != null) {
+   *   stream.write(chunk);
+   * }
+   * ```
+   */
+  handleStream(method: HttpMethod, uri: string, headers?: Record<string, string> | undefined | null): Promise<HttpResponseStream>
 }
 
 /**
@@ -806,6 +986,9 @@ export declare class Updater {
    * Downloads the specified bundle version (or the latest if not specified),
    * verifies integrity and signature if configured, and installs it to the remote directory.
    *
+   * Without a version, the latest version must be compatible with the host app and eligible
+   * for this install (rollout percentage and min app version), otherwise this throws.
+   *
    * @param {string} bundleName - Name of the bundle to download
    * @param {string} [version] - Specific version to download (defaults to latest)
    * @returns {Promise<RemoteBundleInfo>} Information about the downloaded bundle
@@ -893,12 +1076,20 @@ export interface BundleManifestEntry {
  * @property {string} [integrity] - SHA3 integrity hash for verification
  * @property {string} [signature] - Digital signature for authentication
  * @property {string} [lastModified] - HTTP Last-Modified timestamp
+ * @property {number} [rolloutPercentage] - Percentage (0-100) of installs the version is rolled out to
+ * @property {string} [minAppVersion] - Minimum host app version required
+ * @property {string} [compatibleAppVersion] - Semver range of compatible host app versions
+ * @property {string[]} [requiredCapabilities] - Native capabilities required from the host app
  */
 export interface BundleManifestMetadata {
   etag?: string
   integrity?: string
   signature?: string
   lastModified?: string
+  rolloutPercentage?: number
+  minAppVersion?: string
+  compatibleAppVersion?: string
+  requiredCapabilities?: Array<string>
 }
 
 /**
@@ -910,6 +1101,34 @@ export declare enum BundleManifestVersion {
   V1 = 1
 }
 
+/**
+ * Bundle protocol options.
+ *
+ * @property {UriResolverOptions} [uriResolver] - How request URIs are resolved into bundle names
+ * @property {string} [headerRules] - Response header rules applied to every bundle, in the
+ * format of the `headers` bundle metadata
+ * @property {Record<string, string[]>} [overlays] - Map of bundle names to bundles overlaid on
+ * them, looked up in order before the bundle itself
+ * @property {number} [cacheSize] - Maximum size in bytes of decompressed entries cached in
+ * memory, caching is disabled when omitted
+ * @property {string[]} [warmup] - Entry paths decompressed into the cache when a bundle is
+ * loaded, along with the scripts and stylesheets referenced by HTML entries. Bundles can
+ * declare their own in the `warmup` metadata. Requires `cacheSize`
+ * @property {CorsOptions} [cors] - CORS policy, so pages of other schemes or hosts can fetch
+ * from bundles
+ * @property {number} [maxRangeSize] - Maximum size in bytes of the ranges sent in a response to
+ * a range request, defaults to 1000 KiB
+ */
+export interface BundleProtocolOptions {
+  uriResolver?: UriResolverOptions
+  headerRules?: string
+  overlays?: Record<string, Array<string>>
+  cacheSize?: number
+  warmup?: Array<string>
+  cors?: CorsOptions
+  maxRangeSize?: number
+}
+
 /**
  * Configuration for creating a bundle source.
  *
@@ -917,6 +1136,8 @@ export declare enum BundleManifestVersion {
  * @property {string} remoteDir - Directory containing remote bundles
  * @property {string} [builtinManifestFilepath] - Custom manifest path for builtin
  * @property {string} [remoteManifestFilepath] - Custom manifest path for remote
+ * @property {string} [hostAppVersion] - Host app version, checked against bundle compatibility
+ * @property {string[]} [hostCapabilities] - Native capabilities provided by the host app
  *
  * @example
  * ```typescript
@@ -932,6 +1153,8 @@ export interface BundleSourceConfig {
   remoteDir: string
   builtinManifestFilepath?: string
   remoteManifestFilepath?: string
+  hostAppVersion?: string
+  hostCapabilities?: Array<string>
 }
 
 /**
@@ -968,6 +1191,11 @@ export interface BundleSourceVersion {
  * @property {string} [integrity] - Integrity hash (e.g., "sha384-...")
  * @property {string} [signature] - Digital signature
  * @property {string} [lastModified] - Last modified timestamp
+ * @property {number} [rolloutPercentage] - Percentage (0-100) of installs the update is rolled out to
+ * @property {string} [minAppVersion] - Minimum host app version required
+ * @property {string} [compatibleAppVersion] - Semver range of compatible host app versions
+ * @property {string[]} requiredCapabilities - Native capabilities required from the host app
+ * @property {boolean} isCompatible - Whether the update is compatible with the host app
  *
  * @example
  * ```typescript
@@ -987,6 +1215,50 @@ export interface BundleUpdateInfo {
   integrity?: string
   signature?: string
   lastModified?: string
+  rolloutPercentage?: number
+  minAppVersion?: string
+  compatibleAppVersion?: string
+  requiredCapabilities: Array<string>
+  isCompatible: boolean
+}
+
+/**
+ * CORS policy options.
+ *
+ * @property {string[]} [origins] - Allowed origins such as `https://tauri.localhost`, or `*` to
+ * allow every origin
+ * @property {string[]} [schemes] - Schemes whose every origin is allowed, such as `tauri`
+ * @property {HttpMethod[]} [methods] - Methods allowed by preflight requests, defaults to GET
+ * and HEAD. Methods other than GET and HEAD are ignored, as bundles don't serve them
+ * @property {string[]} [headers] - Request headers allowed by preflight requests, or `*` to
+ * allow every header
+ * @property {string[]} [exposeHeaders] - Response headers exposed to scripts
+ * @property {boolean} [credentials] - Whether requests with credentials are allowed
+ * @property {number} [maxAge] - How long in seconds preflight results can be cached
+ */
+export interface CorsOptions {
+  origins?: Array<string>
+  schemes?: Array<string>
+  methods?: Array<HttpMethod>
+  headers?: Array<string>
+  exposeHeaders?: Array<string>
+  credentials?: boolean
+  maxAge?: number
+}
+
+/**
+ * Statistics of the entry cache of a bundle protocol.
+ *
+ * @property {number} hits - Number of lookups served from the cache
+ * @property {number} misses - Number of lookups not found in the cache
+ * @property {number} entries - Number of cached entries
+ * @property {number} size - Total size of cached entries in bytes
+ */
+export interface EntryCacheStats {
+  hits: number
+  misses: number
+  entries: number
+  size: number
 }
 
 export type HttpMethod =  'get'|
@@ -1157,6 +1429,10 @@ export declare function readBundleFromBuffer(buffer: Buffer): Bundle
  * @property {string} [integrity] - SHA3 integrity hash
  * @property {string} [signature] - Digital signature
  * @property {string} [lastModified] - Last-Modified timestamp
+ * @property {number} [rolloutPercentage] - Percentage (0-100) of installs the bundle is rolled out to
+ * @property {string} [minAppVersion] - Minimum host app version required
+ * @property {string} [compatibleAppVersion] - Semver range of compatible host app versions
+ * @property {string[]} requiredCapabilities - Native capabilities required from the host app
  */
 export interface RemoteBundleInfo {
   name: string
@@ -1165,6 +1441,10 @@ export interface RemoteBundleInfo {
   integrity?: string
   signature?: string
   lastModified?: string
+  rolloutPercentage?: number
+  minAppVersion?: string
+  compatibleAppVersion?: string
+  requiredCapabilities: Array<string>
 }
 
 /**
@@ -1291,6 +1571,8 @@ export interface SignatureVerifyingKeyOptions {
  * Configuration options for the updater.
  *
  * @property {string} [channel] - Update channel (e.g., "stable", "beta")
+ * @property {string} [appVersion] - Host app version, compared against `minAppVersion` of updates (defaults to the source host app version)
+ * @property {string} [installId] - Stable id used for staged rollouts (generated and persisted when omitted)
  * @property {IntegrityPolicy} [integrityPolicy] - Policy for integrity verification
  * @property {Function} [integrityChecker] - Custom integrity verification function
  * @property {SignatureVerifierOptions | Function} [signatureVerifier] - Signature verification config or custom function
@@ -1327,11 +1609,43 @@ export interface SignatureVerifyingKeyOptions {
  */
 export interface UpdaterOptions {
   channel?: string
+  appVersion?: string
+  installId?: string
   integrityPolicy?: IntegrityPolicy
   integrityChecker?: (data: Uint8Array, integrity: string) => Promise<boolean>
   signatureVerifier?: SignatureVerifierOptions | ((data: Uint8Array, signature: string) => Promise<boolean>)
 }
 
+/**
+ * How request URIs are resolved into bundle names.
+ *
+ * - `host`: bundle from the first label of the host (`bundle://app/index.html`)
+ * - `path`: bundle from the first path segment (`bundle://localhost/app/index.html`)
+ * - `singleBundle`: every request is served from the `bundle` option
+ * - `mapping`: bundle looked up from the host in the `mapping` option
+ * - `mount`: bundle mounted at the longest matching path prefix in the `mounts` option
+ */
+export type UriResolverKind =  'host'|
+'path'|
+'singleBundle'|
+'mapping'|
+'mount';
+
+/**
+ * URI resolver options.
+ *
+ * @property {UriResolverKind} kind - How the bundle name is resolved
+ * @property {string} [bundle] - Bundle name, required for `singleBundle`
+ * @property {Record<string, string>} [mapping] - Map of hosts to bundle names, required for `mapping`
+ * @property {Record<string, string>} [mounts] - Map of path prefixes to bundle names, required for `mount`
+ */
+export interface UriResolverOptions {
+  kind: UriResolverKind
+  bundle?: string
+  mapping?: Record<string, string>
+  mounts?: Record<string, string>
+}
+
 /**
  * Format of the public key used for signature verification.
  *
@@ -1377,13 +1691,15 @@ export type VerifyingKeyFormat = /** SubjectPublicKeyInfo DER format (binary) */
 /** Raw key bytes (Ed25519 only| 32 bytes) */
 'raw';
 
-export type Version =  'v1';
+export type Version =  'v1'|
+'v2';
 
 /**
  * Writes a bundle to a file asynchronously.
  *
  * @param {Bundle} bundle - Bundle to write
  * @param {string} filepath - Destination file path
+ * @param {Version} [version] - Target format version (defaults to the bundle's version)
  * @returns {Promise<number>} Number of bytes written
  * @throws {Error} If the file cannot be written
  *
@@ -1393,14 +1709,18 @@ export type Version =  'v1';
  * builder.insertEntry("/index.html", Buffer.from("<html></html>"));
  * const bundle = builder.build();
  * await writeBundle(bundle, "output.wvb");
+ *
+ * // Migrate a bundle to the newest format
+ * await writeBundle(await readBundle("app.wvb"), "app.v2.wvb", "v2");
  * ```
  */
-export declare function writeBundle(bundle: Bundle, filepath: string): Promise<bigint>
+export declare function writeBundle(bundle: Bundle, filepath: string, version?: Version | undefined | null): Promise<bigint>
 
 /**
  * Writes a bundle to a buffer synchronously.
  *
  * @param {Bundle} bundle - Bundle to write
+ * @param {Version} [version] - Target format version (defaults to the bundle's version)
  * @returns {Buffer} Bundle data as a buffer
  *
  * @example
@@ -1410,4 +1730,4 @@ export declare function writeBundle(bundle: Bundle, filepath: string): Promise<b
  * console.log(`Bundle size: ${buffer.length} bytes`);
  * ```
  */
-export declare function writeBundleIntoBuffer(bundle: Bundle): Buffer
+export declare function writeBundleIntoBuffer(bundle: Bundle, version?: Version | undefined | null): Buffer
@@ -508,13 +508,14 @@ if (!nativeBinding) {
   throw new Error(`Failed to load native binding`)
 }
 
-const { Bundle, BundleBuilder, BundleDescriptor, BundleProtocol, BundleSource, Header, Index, LocalProtocol, Remote, Updater, BundleManifestVersion, BundleSourceKind, HttpMethod, IntegrityAlgorithm, IntegrityPolicy, readBundle, readBundleFromBuffer, SignatureAlgorithm, VerifyingKeyFormat, Version, writeBundle, writeBundleIntoBuffer } = nativeBinding
+const { Bundle, BundleBuilder, BundleDescriptor, BundleProtocol, BundleSource, Header, HttpResponseStream, Index, LocalProtocol, Remote, Updater, BundleManifestVersion, BundleSourceKind, HttpMethod, IntegrityAlgorithm, IntegrityPolicy, readBundle, readBundleFromBuffer, SignatureAlgorithm, VerifyingKeyFormat, Version, writeBundle, writeBundleIntoBuffer } = nativeBinding
 export { Bundle }
 export { BundleBuilder }
 export { BundleDescriptor }
 export { BundleProtocol }
 export { BundleSource }
 export { Header }
+export { HttpResponseStream }
 export { Index }
 export { LocalProtocol }
 export { Remote }
@@ -1,5 +1,4 @@
 use crate::http::HttpHeaders;
-use crate::mime::MimeType;
 use crate::version::Version;
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
@@ -213,6 +212,28 @@ impl BundleDescriptor {
     let inner = this.share_with(env, |manifest| Ok(manifest.inner.index()))?;
     Ok(Index { inner })
   }
+
+  /// Returns the bundle metadata (e.g. name, version, gitCommit).
+  ///
+  /// Bundles written without metadata return an empty object.
+  ///
+  /// @returns {Record<string, string>} Bundle metadata key/values
+  ///
+  /// @example
+  /// ```typescript
+  /// const metadata = descriptor.metadata();
+  /// console.log(metadata.name, metadata.version);
+  /// ```
+  #[napi]
+  pub fn metadata(&self) -> HashMap<String, String> {
+    self
+      .inner
+      .metadata()
+      .entries()
+      .iter()
+      .map(|(k, v)| (k.to_string(), v.to_string()))
+      .collect()
+  }
 }
 
 /// A complete bundle including metadata and file data.
@@ -284,6 +305,21 @@ impl Bundle {
     let checksum = self.inner.get_data_checksum(&path)?;
     Ok(checksum)
   }
+
+  /// Checks every entry of the bundle against its checksum.
+  ///
+  /// @throws {Error} If the data of any entry doesn't match its checksum
+  ///
+  /// @example
+  /// ```typescript
+  /// const bundle = await readBundle("app.wvb");
+  /// bundle.verify();
+  /// ```
+  #[napi]
+  pub fn verify(&self) -> crate::Result<()> {
+    self.inner.verify()?;
+    Ok(())
+  }
 }
 
 /// Reads a bundle from a buffer synchronously.
@@ -329,6 +365,7 @@ pub async fn read_bundle(filepath: String) -> crate::Result<Bundle> {
 ///
 /// @param {Bundle} bundle - Bundle to write
 /// @param {string} filepath - Destination file path
+/// @param {Version} [version] - Target format version (defaults to the bundle's version)
 /// @returns {Promise<number>} Number of bytes written
 /// @throws {Error} If the file cannot be written
 ///
@@ -338,21 +375,31 @@ pub async fn read_bundle(filepath: String) -> crate::Result<Bundle> {
 /// builder.insertEntry("/index.html", Buffer.from("<html></html>"));
 /// const bundle = builder.build();
 /// await writeBundle(bundle, "output.wvb");
+///
+/// // Migrate a bundle to the newest format
+/// await writeBundle(await readBundle("app.wvb"), "app.v2.wvb", "v2");
 /// ```
 #[napi]
-pub async fn write_bundle(bundle: &Bundle, filepath: String) -> crate::Result<usize> {
+pub async fn write_bundle(
+  bundle: &Bundle,
+  filepath: String,
+  version: Option<Version>,
+) -> crate::Result<usize> {
   let mut file = fs::File::create(&filepath)
     .await
     .map_err(|e| crate::Error::Core(wvb::Error::from(e)))?;
-  let size =
-    AsyncWriter::<wvb::Bundle>::write(&mut AsyncBundleWriter::new(&mut file), &bundle.inner)
-      .await?;
+  let mut writer = match version {
+    Some(v) => AsyncBundleWriter::new_with_version(&mut file, v.into()),
+    None => AsyncBundleWriter::new(&mut file),
+  };
+  let size = AsyncWriter::<wvb::Bundle>::write(&mut writer, &bundle.inner).await?;
   Ok(size)
 }
 
 /// Writes a bundle to a buffer synchronously.
 ///
 /// @param {Bundle} bundle - Bundle to write
+/// @param {Version} [version] - Target format version (defaults to the bundle's version)
 /// @returns {Buffer} Bundle data as a buffer
 ///
 /// @example
@@ -362,9 +409,16 @@ pub async fn write_bundle(bundle: &Bundle, filepath: String) -> crate::Result<us
 /// console.log(`Bundle size: ${buffer.length} bytes`);
 /// ```
 #[napi]
-pub fn write_bundle_into_buffer(bundle: &Bundle) -> crate::Result<Buffer> {
+pub fn write_bundle_into_buffer(
+  bundle: &Bundle,
+  version: Option<Version>,
+) -> crate::Result<Buffer> {
   let mut buf = vec![];
-  Writer::<wvb::Bundle>::write(&mut BundleWriter::new(&mut buf), &bundle.inner)?;
+  let mut writer = match version {
+    Some(v) => BundleWriter::new_with_version(&mut buf, v.into()),
+    None => BundleWriter::new(&mut buf),
+  };
+  Writer::<wvb::Bundle>::write(&mut writer, &bundle.inner)?;
   Ok(buf.into())
 }
 
@@ -452,7 +506,6 @@ impl From<BuildIndexOptions> for IndexWriterOptions {
 /// ```
 #[napi]
 pub struct BundleBuilder {
-  pub(crate) version: Version,
   pub(crate) inner: wvb::BundleBuilder,
 }
 
@@ -468,18 +521,17 @@ impl BundleBuilder {
   /// ```
   #[napi(constructor)]
   pub fn new(version: Option<Version>) -> BundleBuilder {
-    Self {
-      version: version.unwrap_or(Version::V1),
-      inner: wvb::BundleBuilder::new(),
-    }
+    let mut inner = wvb::BundleBuilder::new();
+    inner.set_version(version.unwrap_or(Version::V1).into());
+    Self { inner }
   }
 
   /// Gets the bundle format version.
   ///
   /// @returns {Version} Bundle format version
   #[napi(getter)]
-  pub fn version(&self) -> &Version {
-    &self.version
+  pub fn version(&self) -> Version {
+    Version::from(self.inner.version())
   }
 
   /// Returns all entry paths currently in the builder.
@@ -533,10 +585,8 @@ impl BundleBuilder {
     } else {
       None
     };
-    let content_type = content_type.unwrap_or_else(|| {
-      let mime = MimeType::parse_with_fallback(data.as_ref(), &path, MimeType::OctetStream);
-      mime.to_string()
-    });
+    let content_type =
+      content_type.unwrap_or_else(|| wvb::mime::content_type(&path, data.as_ref()));
     Ok(
       self
         .inner
@@ -559,6 +609,47 @@ impl BundleBuilder {
     self.inner.remove_entry(&path).is_some()
   }
 
+  /// Returns the bundle metadata set on the builder.
+  ///
+  /// @returns {Record<string, string>} Bundle metadata key/values
+  #[napi]
+  pub fn metadata(&self) -> HashMap<String, String> {
+    self
+      .inner
+      .metadata()
+      .entries()
+      .iter()
+      .map(|(k, v)| (k.to_string(), v.to_string()))
+      .collect()
+  }
+
+  /// Sets a bundle metadata value.
+  ///
+  /// Well-known keys are `name`, `version`, `builtAt`, `gitCommit`,
+  /// `compatibleAppVersion` and `requiredCapabilities`.
+  ///
+  /// @param {string} key - Metadata key
+  /// @param {string} value - Metadata value
+  ///
+  /// @example
+  /// ```typescript
+  /// builder.setMetadata("name", "app");
+  /// builder.setMetadata("version", "1.0.0");
+  /// ```
+  #[napi]
+  pub fn set_metadata(&mut self, key: String, value: String) {
+    self.inner.metadata_mut().insert(key, value);
+  }
+
+  /// Removes a bundle metadata value.
+  ///
+  /// @param {string} key - Metadata key
+  /// @returns {boolean} True if the value was removed, false if not found
+  #[napi]
+  pub fn remove_metadata(&mut self, key: String) -> bool {
+    self.inner.metadata_mut().remove(&key).is_some()
+  }
+
   /// Checks if a file path exists in the builder.
   ///
   /// @param {string} path - File path to check
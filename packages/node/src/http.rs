@@ -1,10 +1,14 @@
-use napi::bindgen_prelude::Buffer;
+use futures_util::StreamExt;
+use napi::bindgen_prelude::{AsyncBlock, AsyncBlockBuilder, Buffer, Env};
 use napi_derive::napi;
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::ops::Deref;
+use std::sync::Arc;
+use tokio::sync::Mutex;
 use wvb::http;
 use wvb::http::HeaderMap;
+use wvb::protocol::{BodyStream, StreamProtocolResponse};
 
 #[napi(string_enum = "lowercase")]
 pub enum HttpMethod {
@@ -97,6 +101,75 @@ impl From<http::Response<Cow<'static, [u8]>>> for HttpResponse {
   }
 }
 
+/// HTTP response whose body is read in chunks.
+///
+/// Bundle entries are decompressed while the body is read, so large files (e.g. videos)
+/// are not held in memory at once.
+///
+/// @example
+/// ```typescript
+/// const response = await protocol.handleStream("GET", "bundle://app/video.mp4");
+/// let chunk;
+/// while ((chunk = await response.read()) != null) {
+///   stream.write(chunk);
+/// }
+/// ```
+#[napi]
+pub struct HttpResponseStream {
+  status: u16,
+  headers: HashMap<String, String>,
+  body: Arc<Mutex<BodyStream>>,
+}
+
+#[napi]
+impl HttpResponseStream {
+  /// Gets the HTTP status code.
+  ///
+  /// @returns {number} Status code
+  #[napi(getter)]
+  pub fn status(&self) -> u16 {
+    self.status
+  }
+
+  /// Gets the response headers.
+  ///
+  /// @returns {Record<string, string>} Response headers
+  #[napi(getter)]
+  pub fn headers(&self) -> HashMap<String, String> {
+    self.headers.clone()
+  }
+
+  /// Reads the next chunk of the body.
+  ///
+  /// @returns {Promise<Buffer | null>} Next chunk, or `null` at the end of the body
+  #[napi(ts_return_type = "Promise<Buffer | null>")]
+  pub fn read(&self, env: Env) -> napi::Result<AsyncBlock<Option<Buffer>>> {
+    let body = self.body.clone();
+    AsyncBlockBuilder::new(async move {
+      match body.lock().await.next().await {
+        Some(chunk) => chunk
+          .map(|x| Some(Buffer::from(x.as_ref())))
+          .map_err(crate::Error::Core)
+          .map_err(|e| e.into()),
+        None => Ok(None),
+      }
+    })
+    .build(&env)
+  }
+}
+
+impl From<StreamProtocolResponse> for HttpResponseStream {
+  fn from(value: StreamProtocolResponse) -> Self {
+    let status = value.status().as_u16();
+    let headers = HttpHeaders::from(value.headers()).0;
+    HttpResponseStream {
+      status,
+      headers,
+      body: Arc::new(Mutex::new(value.into_body())),
+    }
+  }
+}
+
 pub(crate) fn request(
   method: HttpMethod,
   uri: String,
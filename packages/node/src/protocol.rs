@@ -1,18 +1,204 @@
 use crate::http::HttpMethod;
 use crate::http::HttpResponse;
+use crate::http::HttpResponseStream;
 use crate::http::request;
+use crate::remote::HttpOptions;
 use crate::source::BundleSource;
 use napi::bindgen_prelude::*;
 use napi_derive::napi;
 use std::collections::HashMap;
 use std::sync::Arc;
-use wvb::protocol;
-use wvb::protocol::Protocol;
+use wvb::protocol::{Protocol, StreamProtocol};
+use wvb::remote::HttpConfig;
+use wvb::{http, protocol};
+
+/// How request URIs are resolved into bundle names.
+///
+/// - `host`: bundle from the first label of the host (`bundle://app/index.html`)
+/// - `path`: bundle from the first path segment (`bundle://localhost/app/index.html`)
+/// - `singleBundle`: every request is served from the `bundle` option
+/// - `mapping`: bundle looked up from the host in the `mapping` option
+/// - `mount`: bundle mounted at the longest matching path prefix in the `mounts` option
+#[napi(string_enum = "camelCase")]
+pub enum UriResolverKind {
+  Host,
+  Path,
+  SingleBundle,
+  Mapping,
+  Mount,
+}
+
+/// URI resolver options.
+///
+/// @property {UriResolverKind} kind - How the bundle name is resolved
+/// @property {string} [bundle] - Bundle name, required for `singleBundle`
+/// @property {Record<string, string>} [mapping] - Map of hosts to bundle names, required for `mapping`
+/// @property {Record<string, string>} [mounts] - Map of path prefixes to bundle names, required for `mount`
+#[napi(object)]
+pub struct UriResolverOptions {
+  pub kind: UriResolverKind,
+  pub bundle: Option<String>,
+  pub mapping: Option<HashMap<String, String>>,
+  pub mounts: Option<HashMap<String, String>>,
+}
+
+impl UriResolverOptions {
+  fn into_builder(
+    self,
+    builder: protocol::BundleProtocolBuilder,
+  ) -> crate::Result<protocol::BundleProtocolBuilder> {
+    let builder = match self.kind {
+      UriResolverKind::Host => builder.uri_resolver(protocol::DefaultUriResolver),
+      UriResolverKind::Path => builder.uri_resolver(protocol::PathUriResolver),
+      UriResolverKind::SingleBundle => {
+        let bundle = self.bundle.ok_or_else(|| {
+          Error::new(
+            Status::InvalidArg,
+            "`bundle` is required for the singleBundle uri resolver",
+          )
+        })?;
+        builder.uri_resolver(protocol::SingleBundleUriResolver::new(bundle))
+      }
+      UriResolverKind::Mapping => {
+        let mapping = self.mapping.ok_or_else(|| {
+          Error::new(
+            Status::InvalidArg,
+            "`mapping` is required for the mapping uri resolver",
+          )
+        })?;
+        builder.uri_resolver(protocol::MappingUriResolver::new(mapping))
+      }
+      UriResolverKind::Mount => {
+        let mounts = self.mounts.ok_or_else(|| {
+          Error::new(
+            Status::InvalidArg,
+            "`mounts` is required for the mount uri resolver",
+          )
+        })?;
+        let resolver = mounts.into_iter().fold(
+          protocol::MountUriResolver::new(),
+          |resolver, (prefix, bundle)| resolver.mount(prefix, bundle),
+        );
+        builder.uri_resolver(resolver)
+      }
+    };
+    Ok(builder)
+  }
+}
+
+/// CORS policy options.
+///
+/// @property {string[]} [origins] - Allowed origins such as `https://tauri.localhost`, or `*` to
+/// allow every origin
+/// @property {string[]} [schemes] - Schemes whose every origin is allowed, such as `tauri`
+/// @property {HttpMethod[]} [methods] - Methods allowed by preflight requests, defaults to GET
+/// and HEAD. Methods other than GET and HEAD are ignored, as bundles don't serve them
+/// @property {string[]} [headers] - Request headers allowed by preflight requests, or `*` to
+/// allow every header
+/// @property {string[]} [exposeHeaders] - Response headers exposed to scripts
+/// @property {boolean} [credentials] - Whether requests with credentials are allowed
+/// @property {number} [maxAge] - How long in seconds preflight results can be cached
+#[napi(object)]
+pub struct CorsOptions {
+  pub origins: Option<Vec<String>>,
+  pub schemes: Option<Vec<String>>,
+  pub methods: Option<Vec<HttpMethod>>,
+  pub headers: Option<Vec<String>>,
+  pub expose_headers: Option<Vec<String>>,
+  pub credentials: Option<bool>,
+  pub max_age: Option<u32>,
+}
+
+impl TryFrom<CorsOptions> for protocol::Cors {
+  type Error = crate::Error;
+
+  fn try_from(value: CorsOptions) -> std::result::Result<Self, Self::Error> {
+    let mut cors = protocol::Cors::new();
+    for origin in value.origins.unwrap_or_default() {
+      cors = cors.allow_origin(origin);
+    }
+    for scheme in value.schemes.unwrap_or_default() {
+      cors = cors.allow_scheme(scheme);
+    }
+    if let Some(methods) = value.methods {
+      cors = cors.allow_methods(methods.into_iter().map(http::Method::from));
+    }
+    for name in value.headers.unwrap_or_default() {
+      cors = if name == "*" {
+        cors.allow_any_header()
+      } else {
+        cors.allow_header(http::HeaderName::from_bytes(name.as_bytes())?)
+      };
+    }
+    for name in value.expose_headers.unwrap_or_default() {
+      cors = cors.expose_header(http::HeaderName::from_bytes(name.as_bytes())?);
+    }
+    if let Some(credentials) = value.credentials {
+      cors = cors.allow_credentials(credentials);
+    }
+    if let Some(max_age) = value.max_age {
+      cors = cors.max_age(max_age as u64);
+    }
+    Ok(cors)
+  }
+}
+
+/// Bundle protocol options.
+///
+/// @property {UriResolverOptions} [uriResolver] - How request URIs are resolved into bundle names
+/// @property {string} [headerRules] - Response header rules applied to every bundle, in the
+/// format of the `headers` bundle metadata
+/// @property {Record<string, string[]>} [overlays] - Map of bundle names to bundles overlaid on
+/// them, looked up in order before the bundle itself
+/// @property {number} [cacheSize] - Maximum size in bytes of decompressed entries cached in
+/// memory, caching is disabled when omitted
+/// @property {string[]} [warmup] - Entry paths decompressed into the cache when a bundle is
+/// loaded, along with the scripts and stylesheets referenced by HTML entries. Bundles can
+/// declare their own in the `warmup` metadata. Requires `cacheSize`
+/// @property {CorsOptions} [cors] - CORS policy, so pages of other schemes or hosts can fetch
+/// from bundles
+/// @property {number} [maxRangeSize] - Maximum size in bytes of the ranges sent in a response to
+/// a range request, defaults to 1000 KiB
+#[napi(object)]
+pub struct BundleProtocolOptions {
+  pub uri_resolver: Option<UriResolverOptions>,
+  pub header_rules: Option<String>,
+  pub overlays: Option<HashMap<String, Vec<String>>>,
+  pub cache_size: Option<u32>,
+  pub warmup: Option<Vec<String>>,
+  pub cors: Option<CorsOptions>,
+  pub max_range_size: Option<u32>,
+}
+
+/// Statistics of the entry cache of a bundle protocol.
+///
+/// @property {number} hits - Number of lookups served from the cache
+/// @property {number} misses - Number of lookups not found in the cache
+/// @property {number} entries - Number of cached entries
+/// @property {number} size - Total size of cached entries in bytes
+#[napi(object)]
+pub struct EntryCacheStats {
+  pub hits: u32,
+  pub misses: u32,
+  pub entries: u32,
+  pub size: u32,
+}
+
+impl From<protocol::EntryCacheStats> for EntryCacheStats {
+  fn from(value: protocol::EntryCacheStats) -> Self {
+    Self {
+      hits: value.hits as u32,
+      misses: value.misses as u32,
+      entries: value.entries as u32,
+      size: value.size as u32,
+    }
+  }
+}
 
 /// Protocol handler for serving files from bundle sources.
 ///
 /// Serves web resources from `.wvb` bundle files, supporting:
-/// - GET and HEAD HTTP methods
+/// - GET and HEAD HTTP methods, and OPTIONS for CORS preflight requests
 /// - HTTP Range requests for streaming
 /// - Content-Type and custom HTTP headers
 ///
@@ -40,6 +226,7 @@ impl BundleProtocol {
   /// Creates a new bundle protocol handler.
   ///
   /// @param {BundleSource} source - Bundle source to serve files from
+  /// @param {BundleProtocolOptions} [options] - Protocol options
   ///
   /// @example
   /// ```typescript
@@ -48,12 +235,105 @@ impl BundleProtocol {
   ///   remoteDir: "./remote"
   /// });
   /// const protocol = new BundleProtocol(source);
+  ///
+  /// // Serves "bundle://localhost/app/index.html" from bundle "app"
+  /// const pathProtocol = new BundleProtocol(source, {
+  ///   uriResolver: { kind: "path" }
+  /// });
+  ///
+  /// // Serves "/features/billing" from bundle "billing", and other paths from
+  /// // bundle "shell", looking up entries in "shell-patch" first
+  /// const mountProtocol = new BundleProtocol(source, {
+  ///   uriResolver: {
+  ///     kind: "mount",
+  ///     mounts: { "/": "shell", "/features/billing": "billing" }
+  ///   },
+  ///   overlays: { shell: ["shell-patch"] }
+  /// });
   /// ```
   #[napi(constructor)]
-  pub fn new(source: &BundleSource) -> BundleProtocol {
-    Self {
-      inner: Arc::new(protocol::BundleProtocol::new(source.inner.clone())),
+  pub fn new(
+    source: &BundleSource,
+    options: Option<BundleProtocolOptions>,
+  ) -> crate::Result<BundleProtocol> {
+    let mut builder = protocol::BundleProtocol::builder(source.inner.clone());
+    if let Some(options) = options {
+      if let Some(uri_resolver) = options.uri_resolver {
+        builder = uri_resolver.into_builder(builder)?;
+      }
+      if let Some(header_rules) = options.header_rules {
+        builder = builder.header_rules(protocol::HeaderRules::parse(&header_rules)?);
+      }
+      for (bundle, overlays) in options.overlays.unwrap_or_default() {
+        // overlays added later are looked up first
+        for overlay in overlays.into_iter().rev() {
+          builder = builder.overlay(&bundle, overlay);
+        }
+      }
+      if let Some(cache_size) = options.cache_size {
+        builder = builder.cache(protocol::EntryCache::new(cache_size as usize));
+      }
+      if let Some(warmup) = options.warmup {
+        let warmup = warmup
+          .into_iter()
+          .fold(protocol::Warmup::new(), |warmup, path| warmup.entry(path));
+        builder = builder.warmup(warmup);
+      }
+      if let Some(cors) = options.cors {
+        builder = builder.cors(cors.try_into()?);
+      }
+      if let Some(max_range_size) = options.max_range_size {
+        builder = builder.max_range_size(max_range_size as u64);
+      }
     }
+    Ok(Self {
+      inner: Arc::new(builder.build()),
+    })
+  }
+
+  /// Returns statistics of the entry cache, or `null` when caching is disabled.
+  ///
+  /// @returns {EntryCacheStats | null} Cache statistics
+  ///
+  /// @example
+  /// ```typescript
+  /// const protocol = new BundleProtocol(source, { cacheSize: 32 * 1024 * 1024 });
+  /// const stats = protocol.cacheStats();
+  /// console.log(`hits: ${stats?.hits}, misses: ${stats?.misses}`);
+  /// ```
+  #[napi]
+  pub fn cache_stats(&self) -> Option<EntryCacheStats> {
+    self.inner.cache().map(|x| x.stats().into())
+  }
+
+  /// Warms up the entries of a bundle into the cache, returning the number of entries added.
+  ///
+  /// Bundles are warmed up in the background when loaded, this waits for it instead.
+  ///
+  /// @param {string} bundleName - Name of the bundle to warm up
+  /// @returns {Promise<number>} Number of entries added to the cache
+  ///
+  /// @example
+  /// ```typescript
+  /// const protocol = new BundleProtocol(source, {
+  ///   cacheSize: 32 * 1024 * 1024,
+  ///   warmup: ["/index.html"]
+  /// });
+  /// await protocol.warmup("app");
+  /// ```
+  #[napi]
+  pub fn warmup(&self, env: Env, bundle_name: String) -> crate::Result<AsyncBlock<u32>> {
+    let inner = self.inner.clone();
+    let warmed = AsyncBlockBuilder::new(async move {
+      inner
+        .warmup(&bundle_name)
+        .await
+        .map(|x| x as u32)
+        .map_err(crate::Error::Core)
+        .map_err(|e| e.into())
+    })
+    .build(&env)?;
+    Ok(warmed)
   }
 
   /// Handles an HTTP request and returns a response.
@@ -105,12 +385,53 @@ impl BundleProtocol {
     .build(&env)?;
     Ok(resp)
   }
+
+  /// Handles an HTTP request and returns a response with a streamed body.
+  ///
+  /// Entries are decompressed while the body is read, and range requests stop
+  /// decompressing at the end of the range.
+  ///
+  /// @param {HttpMethod} method - HTTP method (GET or HEAD)
+  /// @param {string} uri - Request URI (e.g., "bundle://app/index.html")
+  /// @param {Record<string, string>} [headers] - Optional request headers
+  /// @returns {Promise<HttpResponseStream>} HTTP response
+  ///
+  /// @example
+  /// ```typescript
+  /// const response = await protocol.handleStream("GET", "bundle://app/video.mp4");
+  /// let chunk;
+  /// while ((chunk = await response.read()) != null) {
+  ///   stream.write(chunk);
+  /// }
+  /// ```
+  #[napi]
+  pub fn handle_stream(
+    &self,
+    env: Env,
+    method: HttpMethod,
+    uri: String,
+    headers: Option<HashMap<String, String>>,
+  ) -> crate::Result<AsyncBlock<HttpResponseStream>> {
+    let req = request(method, uri, headers)?;
+    let inner = self.inner.clone();
+    let resp = AsyncBlockBuilder::new(async move {
+      inner
+        .handle_stream(req)
+        .await
+        .map(HttpResponseStream::from)
+        .map_err(crate::Error::Core)
+        .map_err(|e| e.into())
+    })
+    .build(&env)?;
+    Ok(resp)
+  }
 }
 
 /// Protocol handler that proxies requests to localhost servers.
 ///
 /// Forwards requests to local development servers for hot-reloading workflows.
-/// Features response caching and 304 Not Modified support.
+/// Request headers are forwarded and responses are cached and revalidated with
+/// conditional requests.
 ///
 /// @example
 /// ```typescript
@@ -132,6 +453,7 @@ impl LocalProtocol {
   /// Creates a new local protocol handler.
   ///
   /// @param {Record<string, string>} hosts - Map of custom hosts to localhost URLs
+  /// @param {HttpOptions} [http] - HTTP client configuration, shared by all requests
   ///
   /// @example
   /// ```typescript
@@ -141,10 +463,20 @@ impl LocalProtocol {
   /// });
   /// ```
   #[napi(constructor)]
-  pub fn new(hosts: HashMap<String, String>) -> LocalProtocol {
-    Self {
-      inner: Arc::new(protocol::LocalProtocol::new(hosts)),
-    }
+  pub fn new(
+    hosts: HashMap<String, String>,
+    http: Option<HttpOptions>,
+  ) -> crate::Result<LocalProtocol> {
+    let inner = match http {
+      Some(http) => protocol::LocalProtocol::new_with_http(
+        hosts,
+        HttpConfig::try_from(http).map_err(|e| Error::new(Status::InvalidArg, e.to_string()))?,
+      )?,
+      None => protocol::LocalProtocol::new(hosts),
+    };
+    Ok(Self {
+      inner: Arc::new(inner),
+    })
   }
 
   /// Handles an HTTP request by proxying to localhost.
@@ -196,4 +528,43 @@ impl LocalProtocol {
     .build(&env)?;
     Ok(resp)
   }
+
+  /// Handles an HTTP request and returns a response with a streamed body.
+  ///
+  /// Responses are cached for revalidation, so the body is read as a single chunk.
+  ///
+  /// @param {HttpMethod} method - HTTP method
+  /// @param {string} uri - Request URI (e.g., "app://myapp/api/data")
+  /// @param {Record<string, string>} [headers] - Optional request headers
+  /// @returns {Promise<HttpResponseStream>} HTTP response
+  ///
+  /// @example
+  /// ```typescript
+  /// const response = await protocol.handleStream("GET", "app://myapp/video.mp4");
+  /// let chunk;
+  /// while ((chunk = await response.read()) != null) {
+  ///   stream.write(chunk);
+  /// }
+  /// ```
+  #[napi]
+  pub fn handle_stream(
+    &self,
+    env: Env,
+    method: HttpMethod,
+    uri: String,
+    headers: Option<HashMap<String, String>>,
+  ) -> crate::Result<AsyncBlock<HttpResponseStream>> {
+    let req = request(method, uri, headers)?;
+    let inner = self.inner.clone();
+    let resp = AsyncBlockBuilder::new(async move {
+      inner
+        .handle_stream(req)
+        .await
+        .map(HttpResponseStream::from)
+        .map_err(crate::Error::Core)
+        .map_err(|e| e.into())
+    })
+    .build(&env)?;
+    Ok(resp)
+  }
 }
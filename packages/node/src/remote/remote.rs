@@ -71,6 +71,10 @@ impl From<remote::ListRemoteBundleInfo> for ListRemoteBundleInfo {
 /// @property {string} [integrity] - SHA3 integrity hash
 /// @property {string} [signature] - Digital signature
 /// @property {string} [lastModified] - Last-Modified timestamp
+/// @property {number} [rolloutPercentage] - Percentage (0-100) of installs the bundle is rolled out to
+/// @property {string} [minAppVersion] - Minimum host app version required
+/// @property {string} [compatibleAppVersion] - Semver range of compatible host app versions
+/// @property {string[]} requiredCapabilities - Native capabilities required from the host app
 #[napi(object)]
 pub struct RemoteBundleInfo {
   pub name: String,
@@ -79,6 +83,10 @@ pub struct RemoteBundleInfo {
   pub integrity: Option<String>,
   pub signature: Option<String>,
   pub last_modified: Option<String>,
+  pub rollout_percentage: Option<u8>,
+  pub min_app_version: Option<String>,
+  pub compatible_app_version: Option<String>,
+  pub required_capabilities: Vec<String>,
 }
 
 impl From<remote::RemoteBundleInfo> for RemoteBundleInfo {
@@ -90,6 +98,10 @@ impl From<remote::RemoteBundleInfo> for RemoteBundleInfo {
       integrity: value.integrity,
       signature: value.signature,
       last_modified: value.last_modified,
+      rollout_percentage: value.rollout_percentage,
+      min_app_version: value.min_app_version,
+      compatible_app_version: value.compatible_app_version,
+      required_capabilities: value.required_capabilities,
     }
   }
 }
@@ -103,6 +115,10 @@ impl From<RemoteBundleInfo> for remote::RemoteBundleInfo {
       integrity: value.integrity,
       signature: value.signature,
       last_modified: value.last_modified,
+      rollout_percentage: value.rollout_percentage,
+      min_app_version: value.min_app_version,
+      compatible_app_version: value.compatible_app_version,
+      required_capabilities: value.required_capabilities,
     }
   }
 }
@@ -56,12 +56,20 @@ impl From<source::BundleSourceVersion> for BundleSourceVersion {
 /// @property {string} [integrity] - SHA3 integrity hash for verification
 /// @property {string} [signature] - Digital signature for authentication
 /// @property {string} [lastModified] - HTTP Last-Modified timestamp
+/// @property {number} [rolloutPercentage] - Percentage (0-100) of installs the version is rolled out to
+/// @property {string} [minAppVersion] - Minimum host app version required
+/// @property {string} [compatibleAppVersion] - Semver range of compatible host app versions
+/// @property {string[]} [requiredCapabilities] - Native capabilities required from the host app
 #[napi(object)]
 pub struct BundleManifestMetadata {
   pub etag: Option<String>,
   pub integrity: Option<String>,
   pub signature: Option<String>,
   pub last_modified: Option<String>,
+  pub rollout_percentage: Option<u8>,
+  pub min_app_version: Option<String>,
+  pub compatible_app_version: Option<String>,
+  pub required_capabilities: Option<Vec<String>>,
 }
 
 impl From<source::BundleManifestMetadata> for BundleManifestMetadata {
@@ -71,6 +79,10 @@ impl From<source::BundleManifestMetadata> for BundleManifestMetadata {
       integrity: value.integrity,
       signature: value.signature,
       last_modified: value.last_modified,
+      rollout_percentage: value.rollout_percentage,
+      min_app_version: value.min_app_version,
+      compatible_app_version: value.compatible_app_version,
+      required_capabilities: Some(value.required_capabilities),
     }
   }
 }
@@ -82,6 +94,10 @@ impl From<BundleManifestMetadata> for source::BundleManifestMetadata {
       integrity: value.integrity,
       signature: value.signature,
       last_modified: value.last_modified,
+      rollout_percentage: value.rollout_percentage,
+      min_app_version: value.min_app_version,
+      compatible_app_version: value.compatible_app_version,
+      required_capabilities: value.required_capabilities.unwrap_or_default(),
     }
   }
 }
@@ -154,6 +170,8 @@ impl From<source::ListBundleItem> for ListBundleItem {
 /// @property {string} remoteDir - Directory containing remote bundles
 /// @property {string} [builtinManifestFilepath] - Custom manifest path for builtin
 /// @property {string} [remoteManifestFilepath] - Custom manifest path for remote
+/// @property {string} [hostAppVersion] - Host app version, checked against bundle compatibility
+/// @property {string[]} [hostCapabilities] - Native capabilities provided by the host app
 ///
 /// @example
 /// ```typescript
@@ -169,6 +187,8 @@ pub struct BundleSourceConfig {
   pub remote_dir: String,
   pub builtin_manifest_filepath: Option<String>,
   pub remote_manifest_filepath: Option<String>,
+  pub host_app_version: Option<String>,
+  pub host_capabilities: Option<Vec<String>>,
 }
 
 /// Bundle source for managing multiple bundle versions.
@@ -215,7 +235,7 @@ impl BundleSource {
   /// });
   /// ```
   #[napi(constructor)]
-  pub fn new(config: BundleSourceConfig) -> BundleSource {
+  pub fn new(config: BundleSourceConfig) -> crate::Result<BundleSource> {
     let mut builder = source::BundleSource::builder()
       .builtin_dir(config.builtin_dir)
       .remote_dir(config.remote_dir);
@@ -225,10 +245,22 @@ impl BundleSource {
     if let Some(remote_manifest) = config.remote_manifest_filepath {
       builder = builder.remote_manifest_filepath(remote_manifest);
     }
+    if config.host_app_version.is_some() || config.host_capabilities.is_some() {
+      let mut host = source::HostInfo::new();
+      if let Some(version) = config.host_app_version {
+        let version = wvb::semver::Version::parse(&version)
+          .map_err(|e| napi::Error::new(napi::Status::InvalidArg, e.to_string()))?;
+        host = host.version(version);
+      }
+      if let Some(capabilities) = config.host_capabilities {
+        host = host.capabilities(capabilities);
+      }
+      builder = builder.host(host);
+    }
     let source = builder.build();
-    BundleSource {
+    Ok(BundleSource {
       inner: Arc::new(source),
-    }
+    })
   }
 
   /// Lists all available bundles from both sources.
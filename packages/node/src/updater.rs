@@ -19,6 +19,11 @@ use wvb::updater;
 /// @property {string} [integrity] - Integrity hash (e.g., "sha384-...")
 /// @property {string} [signature] - Digital signature
 /// @property {string} [lastModified] - Last modified timestamp
+/// @property {number} [rolloutPercentage] - Percentage (0-100) of installs the update is rolled out to
+/// @property {string} [minAppVersion] - Minimum host app version required
+/// @property {string} [compatibleAppVersion] - Semver range of compatible host app versions
+/// @property {string[]} requiredCapabilities - Native capabilities required from the host app
+/// @property {boolean} isCompatible - Whether the update is compatible with the host app
 ///
 /// @example
 /// ```typescript
@@ -38,6 +43,11 @@ pub struct BundleUpdateInfo {
   pub integrity: Option<String>,
   pub signature: Option<String>,
   pub last_modified: Option<String>,
+  pub rollout_percentage: Option<u8>,
+  pub min_app_version: Option<String>,
+  pub compatible_app_version: Option<String>,
+  pub required_capabilities: Vec<String>,
+  pub is_compatible: bool,
 }
 
 impl From<updater::BundleUpdateInfo> for BundleUpdateInfo {
@@ -51,6 +61,11 @@ impl From<updater::BundleUpdateInfo> for BundleUpdateInfo {
       integrity: value.integrity,
       signature: value.signature,
       last_modified: value.last_modified,
+      rollout_percentage: value.rollout_percentage,
+      min_app_version: value.min_app_version,
+      compatible_app_version: value.compatible_app_version,
+      required_capabilities: value.required_capabilities,
+      is_compatible: value.is_compatible,
     }
   }
 }
@@ -66,6 +81,11 @@ impl From<BundleUpdateInfo> for updater::BundleUpdateInfo {
       integrity: value.integrity,
       signature: value.signature,
       last_modified: value.last_modified,
+      rollout_percentage: value.rollout_percentage,
+      min_app_version: value.min_app_version,
+      compatible_app_version: value.compatible_app_version,
+      required_capabilities: value.required_capabilities,
+      is_compatible: value.is_compatible,
     }
   }
 }
@@ -75,6 +95,8 @@ pub(crate) type UpdateIntegrityChecker = JsCallback<FnArgs<(Buffer, String)>, Pr
 /// Configuration options for the updater.
 ///
 /// @property {string} [channel] - Update channel (e.g., "stable", "beta")
+/// @property {string} [appVersion] - Host app version, compared against `minAppVersion` of updates (defaults to the source host app version)
+/// @property {string} [installId] - Stable id used for staged rollouts (generated and persisted when omitted)
 /// @property {IntegrityPolicy} [integrityPolicy] - Policy for integrity verification
 /// @property {Function} [integrityChecker] - Custom integrity verification function
 /// @property {SignatureVerifierOptions | Function} [signatureVerifier] - Signature verification config or custom function
@@ -111,6 +133,8 @@ pub(crate) type UpdateIntegrityChecker = JsCallback<FnArgs<(Buffer, String)>, Pr
 #[napi(object, object_to_js = false)]
 pub struct UpdaterOptions {
   pub channel: Option<String>,
+  pub app_version: Option<String>,
+  pub install_id: Option<String>,
   pub integrity_policy: Option<IntegrityPolicy>,
   #[napi(ts_type = "(data: Uint8Array, integrity: string) => Promise<boolean>")]
   pub integrity_checker: Option<UpdateIntegrityChecker>,
@@ -126,6 +150,12 @@ impl From<UpdaterOptions> for updater::UpdaterConfig {
     if let Some(channel) = value.channel {
       config = config.channel(channel);
     }
+    if let Some(app_version) = value.app_version {
+      config = config.app_version(app_version);
+    }
+    if let Some(install_id) = value.install_id {
+      config = config.install_id(install_id);
+    }
     if let Some(policy) = value.integrity_policy {
       config = config.integrity_policy(policy.into());
     }
@@ -270,6 +300,9 @@ impl Updater {
   /// Downloads the specified bundle version (or the latest if not specified),
   /// verifies integrity and signature if configured, and installs it to the remote directory.
   ///
+  /// Without a version, the latest version must be compatible with the host app and eligible
+  /// for this install (rollout percentage and min app version), otherwise this throws.
+  ///
   /// @param {string} bundleName - Name of the bundle to download
   /// @param {string} [version] - Specific version to download (defaults to latest)
   /// @returns {Promise<RemoteBundleInfo>} Information about the downloaded bundle
@@ -3,12 +3,14 @@ use napi_derive::napi;
 #[napi(string_enum = "lowercase")]
 pub enum Version {
   V1,
+  V2,
 }
 
 impl From<Version> for wvb::Version {
   fn from(value: Version) -> Self {
     match value {
       Version::V1 => wvb::Version::V1,
+      Version::V2 => wvb::Version::V2,
     }
   }
 }
@@ -17,6 +19,7 @@ impl From<wvb::Version> for Version {
   fn from(value: wvb::Version) -> Self {
     match value {
       wvb::Version::V1 => Version::V1,
+      wvb::Version::V2 => Version::V2,
     }
   }
 }
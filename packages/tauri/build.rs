@@ -1,3 +1,13 @@
+const COMMANDS: &[&str] = &[
+  "list_bundles",
+  "load_version",
+  "update_version",
+  "apply_update",
+  "list_remotes",
+  "get_update",
+  "download_update",
+];
+
 fn main() {
-  tauri_plugin::Builder::new(&[]).build();
+  tauri_plugin::Builder::new(COMMANDS).build();
 }
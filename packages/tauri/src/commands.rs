@@ -0,0 +1,83 @@
+use crate::WebviewBundleExtra;
+use tauri::{AppHandle, Runtime, command};
+use wvb::remote::{ListRemoteBundleInfo, RemoteBundleInfo};
+use wvb::source::{BundleSourceVersion, ListBundleItem};
+use wvb::updater::BundleUpdateInfo;
+
+#[command]
+pub(crate) async fn list_bundles<R: Runtime>(
+  app: AppHandle<R>,
+) -> crate::Result<Vec<ListBundleItem>> {
+  let items = app.webview_bundle().source().list_bundles().await?;
+  Ok(items)
+}
+
+#[command]
+pub(crate) async fn load_version<R: Runtime>(
+  app: AppHandle<R>,
+  bundle_name: String,
+) -> crate::Result<Option<BundleSourceVersion>> {
+  let version = app
+    .webview_bundle()
+    .source()
+    .load_version(&bundle_name)
+    .await?;
+  Ok(version)
+}
+
+#[command]
+pub(crate) async fn update_version<R: Runtime>(
+  app: AppHandle<R>,
+  bundle_name: String,
+  version: String,
+) -> crate::Result<()> {
+  app
+    .webview_bundle()
+    .update_version(&bundle_name, &version)
+    .await?;
+  Ok(())
+}
+
+#[command]
+pub(crate) async fn apply_update<R: Runtime>(
+  app: AppHandle<R>,
+  bundle_name: String,
+  version: String,
+) -> crate::Result<()> {
+  app
+    .webview_bundle()
+    .apply_update(&bundle_name, &version)
+    .await?;
+  Ok(())
+}
+
+#[command]
+pub(crate) async fn list_remotes<R: Runtime>(
+  app: AppHandle<R>,
+) -> crate::Result<Vec<ListRemoteBundleInfo>> {
+  let updater = app.webview_bundle().require_updater()?;
+  let remotes = updater.list_remotes().await?;
+  Ok(remotes)
+}
+
+#[command]
+pub(crate) async fn get_update<R: Runtime>(
+  app: AppHandle<R>,
+  bundle_name: String,
+) -> crate::Result<BundleUpdateInfo> {
+  let info = app.webview_bundle().get_update(&bundle_name).await?;
+  Ok(info)
+}
+
+#[command]
+pub(crate) async fn download_update<R: Runtime>(
+  app: AppHandle<R>,
+  bundle_name: String,
+  version: Option<String>,
+) -> crate::Result<RemoteBundleInfo> {
+  let info = app
+    .webview_bundle()
+    .download_update(&bundle_name, version)
+    .await?;
+  Ok(info)
+}
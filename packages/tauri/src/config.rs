@@ -1,9 +1,17 @@
+use crate::{events, remote_dir};
 use std::collections::HashMap;
 use std::path::PathBuf;
+use std::sync::Arc;
 use tauri::path::BaseDirectory;
 use tauri::{AppHandle, Manager, Runtime};
-use wvb::remote;
-
+use wvb::source::BundleSource;
+use wvb::{protocol, remote, signature, updater};
+
+pub use wvb::integrity::IntegrityPolicy;
+pub use wvb::protocol::{
+  Cors, DefaultUriResolver, EntryCache, Fallback, HeaderRules, HybridMode, MappingUriResolver,
+  MountUriResolver, PathUriResolver, SingleBundleUriResolver, UriResolver, Warmup,
+};
 pub use wvb::remote::HttpConfig as Http;
 
 type DynamicDirFn<R> = fn(app: &AppHandle<R>) -> Result<PathBuf, Box<dyn std::error::Error>>;
@@ -30,6 +38,7 @@ impl<R: Runtime> Dir<R> {
 pub struct Source<R: Runtime> {
   pub(crate) builtin_dir: Option<Dir<R>>,
   pub(crate) remote_dir: Option<Dir<R>>,
+  pub(crate) legacy_remote_dir: Option<Dir<R>>,
 }
 
 impl<R: Runtime> Source<R> {
@@ -37,6 +46,7 @@ impl<R: Runtime> Source<R> {
     Self {
       builtin_dir: None,
       remote_dir: None,
+      legacy_remote_dir: None,
     }
   }
 
@@ -60,6 +70,35 @@ impl<R: Runtime> Source<R> {
     self
   }
 
+  /// Set the directory remote bundles were previously written to.
+  ///
+  /// When the remote directory has no manifest yet, the bundles of this directory are copied
+  /// into it once. Use this when upgrading from a version which wrote remote bundles into
+  /// another location, such as a custom remote directory:
+  ///
+  /// ```no_run
+  /// use tauri::Manager;
+  /// use tauri::path::BaseDirectory;
+  /// use wvb_tauri::Source;
+  ///
+  /// let source = Source::<tauri::Wry>::new().legacy_remote_dir_fn(|app| {
+  ///   Ok(app.path().resolve("downloads/bundles", BaseDirectory::AppLocalData)?)
+  /// });
+  /// ```
+  ///
+  /// Versions also listed in the builtin manifest are not copied, and nothing is copied when
+  /// the legacy directory is the builtin directory (such as the old default, `bundles` in the
+  /// resource directory), as those bundles ship with the app anyway.
+  pub fn legacy_remote_dir<T: Into<String>>(mut self, dir: T) -> Self {
+    self.legacy_remote_dir = Some(Dir::Static(dir.into()));
+    self
+  }
+
+  pub fn legacy_remote_dir_fn(mut self, dir: DynamicDirFn<R>) -> Self {
+    self.legacy_remote_dir = Some(Dir::Dynamic(dir));
+    self
+  }
+
   pub(crate) fn resolve_builtin_dir(&self, app: &AppHandle<R>) -> crate::Result<PathBuf> {
     let dir = match self.builtin_dir {
       Some(ref builtin_dir) => {
@@ -76,6 +115,7 @@ impl<R: Runtime> Source<R> {
     Ok(dir)
   }
 
+  /// Resolves the remote directory, which defaults to `bundles` in the app data directory.
   pub(crate) fn resolve_remote_dir(&self, app: &AppHandle<R>) -> crate::Result<PathBuf> {
     let dir = match self.remote_dir {
       Some(ref remote_dir) => {
@@ -85,23 +125,64 @@ impl<R: Runtime> Source<R> {
         dir
       }
       None => {
-        let dir = app.path().resolve("bundles", BaseDirectory::Resource)?;
+        let dir = app.path().resolve("bundles", BaseDirectory::AppData)?;
         dir
       }
     };
     Ok(dir)
   }
+
+  pub(crate) fn resolve_legacy_remote_dir(
+    &self,
+    app: &AppHandle<R>,
+  ) -> crate::Result<Option<PathBuf>> {
+    let dir = match self.legacy_remote_dir {
+      Some(ref legacy_remote_dir) => {
+        let dir = legacy_remote_dir
+          .resolve(app)
+          .map_err(|e| crate::Error::FailToResolveDirectory(e.to_string()))?;
+        Some(dir)
+      }
+      None => None,
+    };
+    Ok(dir)
+  }
+
+  /// Resolves the remote directory and makes it ready to write downloaded bundles into.
+  pub(crate) fn prepare_remote_dir(&self, app: &AppHandle<R>) -> crate::Result<PathBuf> {
+    let dir = self.resolve_remote_dir(app)?;
+    if let Some(legacy_dir) = self.resolve_legacy_remote_dir(app)? {
+      let builtin_dir = self.resolve_builtin_dir(app)?;
+      remote_dir::migrate(&legacy_dir, &builtin_dir, &dir)?;
+    }
+    remote_dir::ensure_writable(&dir)?;
+    Ok(dir)
+  }
 }
 
-#[derive(Clone, Default)]
-pub struct Remote {
+type OnDownload<R> = dyn Fn(&AppHandle<R>, u64, u64, String) + Send + Sync;
+
+pub struct Remote<R: Runtime> {
   builder: remote::RemoteBuilder,
+  on_download: Option<Arc<OnDownload<R>>>,
+}
+
+impl<R: Runtime> Clone for Remote<R> {
+  fn clone(&self) -> Self {
+    Self {
+      builder: self.builder.clone(),
+      on_download: self.on_download.clone(),
+    }
+  }
 }
 
-impl Remote {
+impl<R: Runtime> Remote<R> {
   pub fn new(endpoint: impl Into<String>) -> Self {
     let builder = remote::Remote::builder().endpoint(endpoint);
-    Self { builder }
+    Self {
+      builder,
+      on_download: None,
+    }
   }
 
   pub fn http(mut self, http: Http) -> Self {
@@ -109,30 +190,313 @@ impl Remote {
     self
   }
 
+  /// Set download progress callback.
+  ///
+  /// Progress is also emitted to webviews as [`crate::events::DOWNLOAD_PROGRESS`], whether
+  /// this callback is set or not.
   pub fn on_download<F>(mut self, on_download: F) -> Self
   where
-    F: Fn(u64, u64, String) + Send + Sync + 'static,
+    F: Fn(&AppHandle<R>, u64, u64, String) + Send + Sync + 'static,
   {
-    self.builder = self.builder.on_download(on_download);
+    self.on_download = Some(Arc::new(on_download));
     self
   }
 
-  pub(crate) fn build(self) -> crate::Result<remote::Remote> {
-    let remote = self.builder.build()?;
+  pub(crate) fn build(self, app: &AppHandle<R>) -> crate::Result<remote::Remote> {
+    let app = app.clone();
+    let on_download = self.on_download;
+    let remote = self
+      .builder
+      .on_download(move |downloaded_bytes, total_bytes, endpoint| {
+        if let Some(ref on_download) = on_download {
+          on_download(&app, downloaded_bytes, total_bytes, endpoint.clone());
+        }
+        events::emit(
+          &app,
+          events::DOWNLOAD_PROGRESS,
+          events::DownloadProgress {
+            downloaded_bytes,
+            total_bytes,
+            endpoint,
+          },
+        );
+      })
+      .build()?;
     Ok(remote)
   }
 }
 
+/// Digital signature algorithm used to verify downloaded bundles.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SignatureAlgorithm {
+  EcdsaSecp256r1,
+  EcdsaSecp384r1,
+  Ed25519,
+  RsaPkcs1V1_5,
+  RsaPss,
+}
+
+/// Public key used to verify signatures of downloaded bundles.
+///
+/// Keys are usually embedded into the app binary with `include_str!` or `include_bytes!`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifyingKey {
+  /// SubjectPublicKeyInfo PEM.
+  SpkiPem(String),
+  /// SubjectPublicKeyInfo DER.
+  SpkiDer(Vec<u8>),
+  /// PKCS#1 PEM (RSA only).
+  Pkcs1Pem(String),
+  /// PKCS#1 DER (RSA only).
+  Pkcs1Der(Vec<u8>),
+  /// SEC1 encoded point (ECDSA only).
+  Sec1(Vec<u8>),
+  /// Raw 32 bytes key (Ed25519 only).
+  Raw([u8; 32]),
+}
+
+#[derive(Clone)]
+struct PublicKey {
+  algorithm: SignatureAlgorithm,
+  key: VerifyingKey,
+}
+
+impl PublicKey {
+  fn build(&self) -> crate::Result<signature::SignatureVerifier> {
+    use SignatureAlgorithm as A;
+    use VerifyingKey as K;
+    let verifier = match (&self.algorithm, &self.key) {
+      (A::EcdsaSecp256r1, K::SpkiPem(pem)) => signature::SignatureVerifier::EcdsaSecp256r1(
+        Arc::new(signature::EcdsaSecp256r1Verifier::from_public_key_pem(pem)?),
+      ),
+      (A::EcdsaSecp256r1, K::SpkiDer(der)) => signature::SignatureVerifier::EcdsaSecp256r1(
+        Arc::new(signature::EcdsaSecp256r1Verifier::from_public_key_der(der)?),
+      ),
+      (A::EcdsaSecp256r1, K::Sec1(bytes)) => signature::SignatureVerifier::EcdsaSecp256r1(
+        Arc::new(signature::EcdsaSecp256r1Verifier::from_sec1_bytes(bytes)?),
+      ),
+      (A::EcdsaSecp384r1, K::SpkiPem(pem)) => signature::SignatureVerifier::EcdsaSecp384r1(
+        Arc::new(signature::EcdsaSecp384r1Verifier::from_public_key_pem(pem)?),
+      ),
+      (A::EcdsaSecp384r1, K::SpkiDer(der)) => signature::SignatureVerifier::EcdsaSecp384r1(
+        Arc::new(signature::EcdsaSecp384r1Verifier::from_public_key_der(der)?),
+      ),
+      (A::EcdsaSecp384r1, K::Sec1(bytes)) => signature::SignatureVerifier::EcdsaSecp384r1(
+        Arc::new(signature::EcdsaSecp384r1Verifier::from_sec1_bytes(bytes)?),
+      ),
+      (A::Ed25519, K::SpkiPem(pem)) => signature::SignatureVerifier::Ed25519(Arc::new(
+        signature::Ed25519Verifier::from_public_key_pem(pem)?,
+      )),
+      (A::Ed25519, K::SpkiDer(der)) => signature::SignatureVerifier::Ed25519(Arc::new(
+        signature::Ed25519Verifier::from_public_key_der(der)?,
+      )),
+      (A::Ed25519, K::Raw(bytes)) => signature::SignatureVerifier::Ed25519(Arc::new(
+        signature::Ed25519Verifier::from_public_key_bytes(bytes)?,
+      )),
+      (A::RsaPkcs1V1_5, K::SpkiPem(pem)) => signature::SignatureVerifier::RsaPkcs1V15(Arc::new(
+        signature::RsaPkcs1V15Verifier::from_public_key_pem(pem)?,
+      )),
+      (A::RsaPkcs1V1_5, K::SpkiDer(der)) => signature::SignatureVerifier::RsaPkcs1V15(Arc::new(
+        signature::RsaPkcs1V15Verifier::from_public_key_der(der)?,
+      )),
+      (A::RsaPkcs1V1_5, K::Pkcs1Pem(pem)) => signature::SignatureVerifier::RsaPkcs1V15(Arc::new(
+        signature::RsaPkcs1V15Verifier::from_pkcs1_pem(pem)?,
+      )),
+      (A::RsaPkcs1V1_5, K::Pkcs1Der(der)) => signature::SignatureVerifier::RsaPkcs1V15(Arc::new(
+        signature::RsaPkcs1V15Verifier::from_pkcs1_der(der)?,
+      )),
+      (A::RsaPss, K::SpkiPem(pem)) => signature::SignatureVerifier::RsaPss(Arc::new(
+        signature::RsaPssVerifier::from_public_key_pem(pem)?,
+      )),
+      (A::RsaPss, K::SpkiDer(der)) => signature::SignatureVerifier::RsaPss(Arc::new(
+        signature::RsaPssVerifier::from_public_key_der(der)?,
+      )),
+      (A::RsaPss, K::Pkcs1Pem(pem)) => signature::SignatureVerifier::RsaPss(Arc::new(
+        signature::RsaPssVerifier::from_pkcs1_pem(pem)?,
+      )),
+      (A::RsaPss, K::Pkcs1Der(der)) => signature::SignatureVerifier::RsaPss(Arc::new(
+        signature::RsaPssVerifier::from_pkcs1_der(der)?,
+      )),
+      (algorithm, _) => {
+        return Err(crate::Error::UnsupportedVerifyingKey {
+          algorithm: *algorithm,
+        });
+      }
+    };
+    Ok(verifier)
+  }
+}
+
+#[derive(Clone, Default)]
+pub struct Updater {
+  channel: Option<String>,
+  integrity_policy: Option<IntegrityPolicy>,
+  public_key: Option<PublicKey>,
+}
+
+impl Updater {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Set the release channel to fetch updates from.
+  pub fn channel(mut self, channel: impl Into<String>) -> Self {
+    self.channel = Some(channel.into());
+    self
+  }
+
+  /// Set how integrity of downloaded bundles is checked.
+  pub fn integrity_policy(mut self, policy: IntegrityPolicy) -> Self {
+    self.integrity_policy = Some(policy);
+    self
+  }
+
+  /// Verify signatures of downloaded bundles with the given public key.
+  ///
+  /// ```no_run
+  /// use wvb_tauri::{SignatureAlgorithm, Updater, VerifyingKey};
+  ///
+  /// // usually `include_str!("../keys/public.pem")`
+  /// let pem = "-----BEGIN PUBLIC KEY-----\n...\n-----END PUBLIC KEY-----";
+  /// let updater = Updater::new().public_key(
+  ///   SignatureAlgorithm::Ed25519,
+  ///   VerifyingKey::SpkiPem(pem.to_string()),
+  /// );
+  /// ```
+  pub fn public_key(mut self, algorithm: SignatureAlgorithm, key: VerifyingKey) -> Self {
+    self.public_key = Some(PublicKey { algorithm, key });
+    self
+  }
+
+  pub(crate) fn build(&self) -> crate::Result<updater::UpdaterConfig> {
+    let mut config = updater::UpdaterConfig::new();
+    if let Some(ref channel) = self.channel {
+      config = config.channel(channel);
+    }
+    if let Some(policy) = self.integrity_policy {
+      config = config.integrity_policy(policy);
+    }
+    if let Some(ref public_key) = self.public_key {
+      config = config.signature_verifier(public_key.build()?);
+    }
+    Ok(config)
+  }
+}
+
 #[derive(Clone)]
 pub struct BundleProtocolConfig {
   scheme: String,
+  pub(crate) bundle: BundleOptions,
 }
 
 impl BundleProtocolConfig {
   pub fn new<S: Into<String>>(scheme: S) -> Self {
     Self {
       scheme: scheme.into(),
+      bundle: BundleOptions::default(),
+    }
+  }
+
+  /// Set how request URIs are resolved into bundle names and file paths.
+  ///
+  /// Defaults to [`DefaultUriResolver`], which takes the bundle name from the host.
+  pub fn uri_resolver(mut self, resolver: impl UriResolver + 'static) -> Self {
+    self.bundle.uri_resolver = Some(Arc::new(resolver));
+    self
+  }
+
+  /// Set the routing fallback for bundles which don't declare their own in metadata.
+  pub fn fallback(mut self, fallback: Fallback) -> Self {
+    self.bundle.fallback = Some(fallback);
+    self
+  }
+
+  /// Set response header rules applied to every bundle, before the rules of the bundle.
+  pub fn header_rules(mut self, rules: HeaderRules) -> Self {
+    self.bundle.header_rules = Some(rules);
+    self
+  }
+
+  /// Overlay `overlay` on `bundle`, so entries are looked up in `overlay` first.
+  ///
+  /// Overlays added later are looked up first, and overlays which are not installed are skipped.
+  pub fn overlay(mut self, bundle: impl Into<String>, overlay: impl Into<String>) -> Self {
+    self.bundle.overlays.push((bundle.into(), overlay.into()));
+    self
+  }
+
+  /// Cache decompressed entries in memory, invalidated when a bundle version is switched.
+  pub fn cache(mut self, cache: impl Into<Arc<EntryCache>>) -> Self {
+    self.bundle.cache = Some(cache.into());
+    self
+  }
+
+  /// Warm up entries into the cache when a bundle is loaded, for bundles which don't declare
+  /// their own warmup in metadata. Requires [`Self::cache`].
+  pub fn warmup(mut self, warmup: Warmup) -> Self {
+    self.bundle.warmup = Some(warmup);
+    self
+  }
+
+  /// Set the CORS policy, so pages of other schemes or hosts can fetch from bundles.
+  pub fn cors(mut self, cors: Cors) -> Self {
+    self.bundle.cors = Some(cors);
+    self
+  }
+
+  /// Set the maximum size in bytes of the ranges sent in a response to a range request,
+  /// which defaults to 1000 KiB.
+  pub fn max_range_size(mut self, size: u64) -> Self {
+    self.bundle.max_range_size = Some(size);
+    self
+  }
+
+  pub(crate) fn build(&self, source: Arc<BundleSource>) -> protocol::BundleProtocol {
+    self.bundle.build(source)
+  }
+}
+
+/// Options of bundle protocols, shared by [`BundleProtocolConfig`] and [`HybridProtocolConfig`].
+#[derive(Clone, Default)]
+pub(crate) struct BundleOptions {
+  pub(crate) uri_resolver: Option<Arc<dyn UriResolver>>,
+  pub(crate) fallback: Option<Fallback>,
+  pub(crate) header_rules: Option<HeaderRules>,
+  pub(crate) overlays: Vec<(String, String)>,
+  pub(crate) cache: Option<Arc<EntryCache>>,
+  pub(crate) warmup: Option<Warmup>,
+  pub(crate) cors: Option<Cors>,
+  pub(crate) max_range_size: Option<u64>,
+}
+
+impl BundleOptions {
+  fn build(&self, source: Arc<BundleSource>) -> protocol::BundleProtocol {
+    let mut builder = protocol::BundleProtocol::builder(source);
+    if let Some(uri_resolver) = &self.uri_resolver {
+      builder = builder.uri_resolver(uri_resolver.clone());
+    }
+    if let Some(fallback) = &self.fallback {
+      builder = builder.fallback(fallback.clone());
     }
+    if let Some(header_rules) = &self.header_rules {
+      builder = builder.header_rules(header_rules.clone());
+    }
+    for (bundle, overlay) in &self.overlays {
+      builder = builder.overlay(bundle, overlay);
+    }
+    if let Some(cache) = &self.cache {
+      builder = builder.cache(cache.clone());
+    }
+    if let Some(warmup) = &self.warmup {
+      builder = builder.warmup(warmup.clone());
+    }
+    if let Some(cors) = &self.cors {
+      builder = builder.cors(cors.clone());
+    }
+    if let Some(size) = self.max_range_size {
+      builder = builder.max_range_size(size);
+    }
+    builder.build()
   }
 }
 
@@ -140,6 +504,8 @@ impl BundleProtocolConfig {
 pub struct LocalProtocolConfig {
   scheme: String,
   pub(crate) hosts: HashMap<String, String>,
+  pub(crate) http: Option<Http>,
+  pub(crate) hmr: bool,
 }
 
 impl LocalProtocolConfig {
@@ -147,6 +513,8 @@ impl LocalProtocolConfig {
     Self {
       scheme: scheme.into(),
       hosts: HashMap::new(),
+      http: None,
+      hmr: false,
     }
   }
 
@@ -154,6 +522,8 @@ impl LocalProtocolConfig {
     Self {
       scheme,
       hosts: hosts.into(),
+      http: None,
+      hmr: false,
     }
   }
 
@@ -166,12 +536,162 @@ impl LocalProtocolConfig {
     self.hosts = hosts.into();
     self
   }
+
+  /// Set HTTP client configuration used to connect to the local dev server.
+  pub fn http(mut self, http: Http) -> Self {
+    self.http = Some(http);
+    self
+  }
+
+  /// Redirect WebSocket connections of HMR clients to the local dev server.
+  pub fn hmr(mut self, enabled: bool) -> Self {
+    self.hmr = enabled;
+    self
+  }
+}
+
+impl LocalProtocolConfig {
+  pub(crate) fn build(&self) -> crate::Result<protocol::LocalProtocol> {
+    build_local_protocol(&self.hosts, self.http.as_ref(), self.hmr)
+  }
+}
+
+fn build_local_protocol(
+  hosts: &HashMap<String, String>,
+  http: Option<&Http>,
+  hmr: bool,
+) -> crate::Result<protocol::LocalProtocol> {
+  let protocol = match http {
+    Some(http) => protocol::LocalProtocol::new_with_http(hosts.clone(), http.clone())?,
+    None => protocol::LocalProtocol::new(hosts.clone()),
+  };
+  Ok(protocol.with_hmr(hmr))
+}
+
+/// Protocol proxying to a local dev server when reachable and serving from bundles otherwise.
+///
+/// Defaults to [`HybridMode::Auto`] in debug builds and [`HybridMode::Bundle`] in release
+/// builds. The mode can be switched at runtime with `WebviewBundle::set_protocol_mode`.
+#[derive(Clone)]
+pub struct HybridProtocolConfig {
+  scheme: String,
+  pub(crate) hosts: HashMap<String, String>,
+  pub(crate) http: Option<Http>,
+  pub(crate) hmr: bool,
+  pub(crate) mode: HybridMode,
+  pub(crate) bundle: BundleOptions,
+}
+
+impl HybridProtocolConfig {
+  pub fn new<S: Into<String>>(scheme: S) -> Self {
+    Self {
+      scheme: scheme.into(),
+      hosts: HashMap::new(),
+      http: None,
+      hmr: false,
+      mode: if cfg!(debug_assertions) {
+        HybridMode::Auto
+      } else {
+        HybridMode::Bundle
+      },
+      bundle: BundleOptions::default(),
+    }
+  }
+
+  pub fn host<T: Into<String>, U: Into<String>>(mut self, host: T, url: U) -> Self {
+    self.hosts.insert(host.into(), url.into());
+    self
+  }
+
+  pub fn hosts<T: Into<HashMap<String, String>>>(mut self, hosts: T) -> Self {
+    self.hosts = hosts.into();
+    self
+  }
+
+  /// Set HTTP client configuration used to connect to the local dev server.
+  pub fn http(mut self, http: Http) -> Self {
+    self.http = Some(http);
+    self
+  }
+
+  /// Redirect WebSocket connections of HMR clients to the local dev server.
+  pub fn hmr(mut self, enabled: bool) -> Self {
+    self.hmr = enabled;
+    self
+  }
+
+  pub fn mode(mut self, mode: HybridMode) -> Self {
+    self.mode = mode;
+    self
+  }
+
+  /// Set how request URIs are resolved into bundle names and file paths when serving from
+  /// bundles.
+  pub fn uri_resolver(mut self, resolver: impl UriResolver + 'static) -> Self {
+    self.bundle.uri_resolver = Some(Arc::new(resolver));
+    self
+  }
+
+  /// Set the routing fallback used when serving from bundles.
+  pub fn fallback(mut self, fallback: Fallback) -> Self {
+    self.bundle.fallback = Some(fallback);
+    self
+  }
+
+  /// Set response header rules used when serving from bundles.
+  pub fn header_rules(mut self, rules: HeaderRules) -> Self {
+    self.bundle.header_rules = Some(rules);
+    self
+  }
+
+  /// Overlay `overlay` on `bundle` when serving from bundles, so entries are looked up in
+  /// `overlay` first.
+  pub fn overlay(mut self, bundle: impl Into<String>, overlay: impl Into<String>) -> Self {
+    self.bundle.overlays.push((bundle.into(), overlay.into()));
+    self
+  }
+
+  /// Cache decompressed entries in memory when serving from bundles.
+  pub fn cache(mut self, cache: impl Into<Arc<EntryCache>>) -> Self {
+    self.bundle.cache = Some(cache.into());
+    self
+  }
+
+  /// Warm up entries into the cache when a bundle is loaded. Requires [`Self::cache`].
+  pub fn warmup(mut self, warmup: Warmup) -> Self {
+    self.bundle.warmup = Some(warmup);
+    self
+  }
+
+  /// Set the CORS policy used when serving from bundles.
+  pub fn cors(mut self, cors: Cors) -> Self {
+    self.bundle.cors = Some(cors);
+    self
+  }
+
+  /// Set the maximum size in bytes of the ranges sent in a response to a range request when
+  /// serving from bundles.
+  pub fn max_range_size(mut self, size: u64) -> Self {
+    self.bundle.max_range_size = Some(size);
+    self
+  }
+}
+
+impl HybridProtocolConfig {
+  pub(crate) fn build_bundle(&self, source: Arc<BundleSource>) -> protocol::BundleProtocol {
+    self.bundle.build(source)
+  }
+
+  pub(crate) fn build_local(&self) -> crate::Result<protocol::LocalProtocol> {
+    build_local_protocol(&self.hosts, self.http.as_ref(), self.hmr)
+  }
 }
 
 #[derive(Clone)]
 pub enum Protocol {
   Bundle(BundleProtocolConfig),
   Local(LocalProtocolConfig),
+  Hybrid(HybridProtocolConfig),
 }
 
 impl Protocol {
@@ -183,10 +703,24 @@ impl Protocol {
     LocalProtocolConfig::new(scheme)
   }
 
+  pub fn hybrid<S: Into<String>>(scheme: S) -> HybridProtocolConfig {
+    HybridProtocolConfig::new(scheme)
+  }
+
   pub fn scheme(&self) -> &str {
     match self {
       Protocol::Bundle(x) => &x.scheme,
       Protocol::Local(x) => &x.scheme,
+      Protocol::Hybrid(x) => &x.scheme,
+    }
+  }
+
+  /// Custom URI resolver of the protocol serving from bundles, if any.
+  pub(crate) fn uri_resolver(&self) -> Option<&Arc<dyn UriResolver>> {
+    match self {
+      Protocol::Bundle(x) => x.bundle.uri_resolver.as_ref(),
+      Protocol::Local(_) => None,
+      Protocol::Hybrid(x) => x.bundle.uri_resolver.as_ref(),
     }
   }
 }
@@ -203,11 +737,18 @@ impl From<LocalProtocolConfig> for Protocol {
   }
 }
 
+impl From<HybridProtocolConfig> for Protocol {
+  fn from(value: HybridProtocolConfig) -> Self {
+    Protocol::Hybrid(value)
+  }
+}
+
 #[derive(Clone, Default)]
 pub struct Config<R: Runtime> {
   pub(crate) source: Source<R>,
   pub(crate) protocols: Vec<Protocol>,
-  pub(crate) remote: Option<Remote>,
+  pub(crate) remote: Option<Remote<R>>,
+  pub(crate) updater: Option<Updater>,
 }
 
 impl<R: Runtime> Config<R> {
@@ -216,6 +757,7 @@ impl<R: Runtime> Config<R> {
       source: Source::new(),
       protocols: vec![],
       remote: Default::default(),
+      updater: Default::default(),
     }
   }
 
@@ -229,17 +771,27 @@ impl<R: Runtime> Config<R> {
     self
   }
 
-  pub fn remote(mut self, remote: Remote) -> Self {
+  pub fn remote(mut self, remote: Remote<R>) -> Self {
     self.remote = Some(remote);
     self
   }
 
-  pub(crate) fn build_remote(&self) -> crate::Result<Option<remote::Remote>> {
+  /// Configure the updater. Only used when a remote is configured.
+  pub fn updater(mut self, updater: Updater) -> Self {
+    self.updater = Some(updater);
+    self
+  }
+
+  pub(crate) fn build_remote(&self, app: &AppHandle<R>) -> crate::Result<Option<remote::Remote>> {
     if let Some(ref remote_config) = self.remote {
-      let remote = remote_config.clone().build()?;
+      let remote = remote_config.clone().build(app)?;
       Ok(Some(remote))
     } else {
       Ok(None)
     }
   }
+
+  pub(crate) fn build_updater_config(&self) -> crate::Result<Option<updater::UpdaterConfig>> {
+    self.updater.as_ref().map(|x| x.build()).transpose()
+  }
 }
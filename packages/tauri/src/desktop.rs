@@ -1,11 +1,11 @@
-use crate::{Config, Protocol};
+use crate::{Config, HybridMode, Protocol, events};
 use std::collections::HashMap;
 use std::sync::Arc;
-use tauri::{AppHandle, Runtime};
+use tauri::{AppHandle, Manager, Runtime, Url};
 use wvb::protocol;
-use wvb::remote::Remote;
+use wvb::remote::{Remote, RemoteBundleInfo};
 use wvb::source::BundleSource;
-use wvb::updater::Updater;
+use wvb::updater::{BundleUpdateInfo, Updater};
 
 pub fn init<R: Runtime>(
   app: &AppHandle<R>,
@@ -16,12 +16,13 @@ pub fn init<R: Runtime>(
 }
 
 pub struct WebviewBundle<R: Runtime> {
-  _app: AppHandle<R>,
-  _config: Arc<Config<R>>,
+  app: AppHandle<R>,
+  config: Arc<Config<R>>,
   source: Arc<BundleSource>,
   remote: Option<Arc<Remote>>,
   updater: Option<Arc<Updater>>,
-  protocols: HashMap<String, Arc<dyn protocol::Protocol>>,
+  protocols: HashMap<String, Arc<dyn protocol::StreamProtocol>>,
+  hybrid_protocols: HashMap<String, Arc<protocol::HybridProtocol>>,
 }
 
 impl<R: Runtime> WebviewBundle<R> {
@@ -29,33 +30,53 @@ impl<R: Runtime> WebviewBundle<R> {
     let source = Arc::new(
       BundleSource::builder()
         .builtin_dir(config.source.resolve_builtin_dir(&app)?.as_path())
-        .remote_dir(config.source.resolve_remote_dir(&app)?.as_path())
+        .remote_dir(config.source.prepare_remote_dir(&app)?.as_path())
         .build(),
     );
     let mut protocols = HashMap::with_capacity(config.protocols.len());
+    let mut hybrid_protocols = HashMap::new();
     for protocol_config in &config.protocols {
       let scheme = protocol_config.scheme().to_string();
-      let protocol: Arc<dyn protocol::Protocol> = match protocol_config {
-        Protocol::Bundle(_) => Arc::new(protocol::BundleProtocol::new(source.clone())),
-        Protocol::Local(config) => Arc::new(protocol::LocalProtocol::new(config.hosts.clone())),
+      let protocol: Arc<dyn protocol::StreamProtocol> = match protocol_config {
+        Protocol::Bundle(config) => Arc::new(config.build(source.clone())),
+        Protocol::Local(config) => Arc::new(config.build()?),
+        Protocol::Hybrid(config) => {
+          let protocol = Arc::new(
+            protocol::HybridProtocol::new(
+              config.build_bundle(source.clone()),
+              config.build_local()?,
+            )
+            .with_mode(config.mode),
+          );
+          hybrid_protocols.insert(scheme.clone(), protocol.clone());
+          protocol
+        }
       };
       if protocols.contains_key(&scheme) {
         return Err(crate::Error::ProtocolSchemeDuplicated { scheme });
       }
       protocols.insert(scheme, protocol);
     }
-    let remote = config.build_remote()?.map(Arc::new);
-    let updater = remote
-      .clone()
-      .map(|x| Updater::new(source.clone(), x, None))
-      .map(Arc::new);
+    let remote = config.build_remote(&app)?.map(Arc::new);
+    let updater = match remote.clone() {
+      Some(remote) => {
+        let updater_config = config.build_updater_config()?;
+        Some(Arc::new(Updater::new(
+          source.clone(),
+          remote,
+          updater_config,
+        )))
+      }
+      None => None,
+    };
     Ok(Self {
-      _app: app,
-      _config: config,
+      app,
+      config,
       source,
       remote,
       updater,
       protocols,
+      hybrid_protocols,
     })
   }
 
@@ -71,7 +92,151 @@ impl<R: Runtime> WebviewBundle<R> {
     self.updater.as_ref()
   }
 
-  pub(crate) fn get_protocol(&self, scheme: &str) -> Option<&Arc<dyn protocol::Protocol>> {
+  pub(crate) fn require_updater(&self) -> crate::Result<&Arc<Updater>> {
+    self
+      .updater
+      .as_ref()
+      .ok_or(crate::Error::RemoteNotConfigured)
+  }
+
+  /// Checks the remote for an update of the bundle.
+  ///
+  /// Emits [`events::UPDATE_AVAILABLE`] when an update is available.
+  pub async fn get_update(&self, bundle_name: &str) -> crate::Result<BundleUpdateInfo> {
+    let info = self.require_updater()?.get_update(bundle_name).await?;
+    if info.is_available {
+      events::emit(&self.app, events::UPDATE_AVAILABLE, info.clone());
+    }
+    Ok(info)
+  }
+
+  /// Downloads the bundle from the remote and writes it to the remote directory.
+  ///
+  /// Emits [`events::DOWNLOAD_PROGRESS`] while downloading and [`events::INSTALLED`] once the
+  /// bundle is written.
+  pub async fn download_update(
+    &self,
+    bundle_name: &str,
+    version: Option<String>,
+  ) -> crate::Result<RemoteBundleInfo> {
+    let info = self
+      .require_updater()?
+      .download_update(bundle_name, version)
+      .await?;
+    events::emit(
+      &self.app,
+      events::INSTALLED,
+      events::Installed {
+        bundle_name: info.name.clone(),
+        version: info.version.clone(),
+      },
+    );
+    Ok(info)
+  }
+
+  /// Switches the current version of the bundle.
+  ///
+  /// Emits [`events::ROLLBACK`] when switching to an older version.
+  pub async fn update_version(&self, bundle_name: &str, version: &str) -> crate::Result<()> {
+    let prev = self.source.load_version(bundle_name).await?;
+    self.source.update_version(bundle_name, version).await?;
+    if let Some(prev) = prev
+      && events::is_rollback(&prev.version, version)
+    {
+      events::emit(
+        &self.app,
+        events::ROLLBACK,
+        events::Rollback {
+          bundle_name: bundle_name.to_string(),
+          from_version: prev.version,
+          to_version: version.to_string(),
+        },
+      );
+    }
+    Ok(())
+  }
+
+  /// Switches the current version of the bundle and reloads webviews showing it.
+  ///
+  /// Every webview whose URL points to the bundle through a bundle protocol is reloaded.
+  /// A webview that fails to report its URL or to reload is logged and skipped, so the
+  /// remaining webviews are still reloaded.
+  pub async fn apply_update(&self, bundle_name: &str, version: &str) -> crate::Result<()> {
+    self.update_version(bundle_name, version).await?;
+    for (label, webview) in self.app.webviews() {
+      let url = match webview.url() {
+        Ok(url) => url,
+        Err(e) => {
+          tracing::warn!(webview = %label, error = %e, "fail to get webview url");
+          continue;
+        }
+      };
+      if !self.is_bundle_url(&url, bundle_name) {
+        continue;
+      }
+      if let Err(e) = webview.reload() {
+        tracing::warn!(webview = %label, error = %e, "fail to reload webview");
+      }
+    }
+    Ok(())
+  }
+
+  /// Whether the URL is served from the bundle by one of the bundle protocols.
+  ///
+  /// Matches both `scheme://bundle_name/..` and `http(s)://scheme.bundle_name/..`, the form
+  /// custom schemes take on Windows and Android. Protocols with a custom URI resolver match
+  /// the bundle it resolves instead.
+  fn is_bundle_url(&self, url: &Url, bundle_name: &str) -> bool {
+    let host = match url.host_str() {
+      Some(x) => x,
+      None => return false,
+    };
+    self
+      .config
+      .protocols
+      .iter()
+      .filter(|x| matches!(x, Protocol::Bundle(_) | Protocol::Hybrid(_)))
+      .any(|protocol| {
+        let scheme = protocol.scheme();
+        let host = if url.scheme() == scheme {
+          Some(host)
+        } else if url.scheme() == "http" || url.scheme() == "https" {
+          host.strip_prefix(scheme).and_then(|x| x.strip_prefix('.'))
+        } else {
+          None
+        };
+        let Some(host) = host else {
+          return false;
+        };
+        match protocol.uri_resolver() {
+          Some(resolver) => {
+            let uri = url.as_str().parse::<tauri::http::Uri>().ok();
+            uri.and_then(|x| resolver.resolve_bundle(&x)).as_deref() == Some(bundle_name)
+          }
+          None => host.split('.').next() == Some(bundle_name),
+        }
+      })
+  }
+
+  /// Switches the mode of the hybrid protocol registered for the scheme.
+  pub fn set_protocol_mode(&self, scheme: &str, mode: HybridMode) -> crate::Result<()> {
+    let protocol =
+      self
+        .hybrid_protocols
+        .get(scheme)
+        .ok_or_else(|| crate::Error::HybridProtocolNotFound {
+          scheme: scheme.to_string(),
+        })?;
+    protocol.set_mode(mode);
+    Ok(())
+  }
+
+  /// Returns the mode of the hybrid protocol registered for the scheme.
+  pub fn protocol_mode(&self, scheme: &str) -> Option<HybridMode> {
+    self.hybrid_protocols.get(scheme).map(|x| x.mode())
+  }
+
+  pub(crate) fn get_protocol(&self, scheme: &str) -> Option<&Arc<dyn protocol::StreamProtocol>> {
     self.protocols.get(scheme)
   }
 }
@@ -1,4 +1,5 @@
 use serde::{Serialize, ser::Serializer};
+use std::path::PathBuf;
 
 pub type Result<T> = std::result::Result<T, Error>;
 
@@ -12,6 +13,18 @@ pub enum Error {
   Tauri(#[from] tauri::Error),
   #[error("duplicated protocol scheme: {scheme}")]
   ProtocolSchemeDuplicated { scheme: String },
+  #[error("hybrid protocol not found: {scheme}")]
+  HybridProtocolNotFound { scheme: String },
+  #[error("remote is not configured")]
+  RemoteNotConfigured,
+  #[error("remote directory is not writable: {path}: {reason}")]
+  RemoteDirNotWritable { path: PathBuf, reason: String },
+  #[error("fail to migrate legacy remote directory: {0}")]
+  FailToMigrateRemoteDir(String),
+  #[error("unsupported verifying key format for {algorithm:?}")]
+  UnsupportedVerifyingKey {
+    algorithm: crate::SignatureAlgorithm,
+  },
 }
 
 impl Serialize for Error {
@@ -0,0 +1,65 @@
+//! Events emitted to webviews during the update lifecycle.
+//!
+//! ```js
+//! import { listen } from '@tauri-apps/api/event';
+//!
+//! await listen('wvb://download-progress', ({ payload }) => {
+//!   console.log(payload.downloadedBytes, payload.totalBytes);
+//! });
+//! ```
+
+use serde::Serialize;
+use tauri::{AppHandle, Emitter, Runtime};
+
+/// Emitted when an update check finds an available update.
+///
+/// The payload is a [`wvb::updater::BundleUpdateInfo`].
+pub const UPDATE_AVAILABLE: &str = "wvb://update-available";
+/// Emitted while downloading a bundle from the remote. The payload is a [`DownloadProgress`].
+pub const DOWNLOAD_PROGRESS: &str = "wvb://download-progress";
+/// Emitted when a downloaded bundle is written to the remote directory.
+///
+/// The payload is an [`Installed`].
+pub const INSTALLED: &str = "wvb://installed";
+/// Emitted when the current version of a bundle is switched to an older version.
+///
+/// The payload is a [`Rollback`].
+pub const ROLLBACK: &str = "wvb://rollback";
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DownloadProgress {
+  pub downloaded_bytes: u64,
+  pub total_bytes: u64,
+  pub endpoint: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Installed {
+  pub bundle_name: String,
+  pub version: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rollback {
+  pub bundle_name: String,
+  pub from_version: String,
+  pub to_version: String,
+}
+
+pub(crate) fn emit<R: Runtime, S: Serialize + Clone>(app: &AppHandle<R>, event: &str, payload: S) {
+  // events are best effort, failing to emit must not fail the update itself
+  let _ = app.emit(event, payload);
+}
+
+/// Whether switching from `from` to `to` moves to an older version.
+///
+/// Versions which are not valid semver are never treated as a rollback.
+pub(crate) fn is_rollback(from: &str, to: &str) -> bool {
+  match (semver::Version::parse(from), semver::Version::parse(to)) {
+    (Ok(from), Ok(to)) => to < from,
+    _ => false,
+  }
+}
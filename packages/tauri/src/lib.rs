@@ -4,13 +4,20 @@ use tauri::{
   plugin::{Builder, TauriPlugin},
 };
 
-pub use config::{Config, Http, Protocol, Remote, Source};
+pub use config::{
+  Config, Cors, DefaultUriResolver, EntryCache, Fallback, HeaderRules, Http, HybridMode,
+  IntegrityPolicy, MappingUriResolver, MountUriResolver, PathUriResolver, Protocol, Remote,
+  SignatureAlgorithm, SingleBundleUriResolver, Source, Updater, UriResolver, VerifyingKey, Warmup,
+};
 
 #[cfg(desktop)]
 mod desktop;
 
+mod commands;
 mod config;
 mod error;
+pub mod events;
+mod remote_dir;
 
 pub use error::{Error, Result};
 
@@ -35,12 +42,22 @@ impl<R: Runtime, T: Manager<R>> WebviewBundleExtra<R> for T {
 pub fn init<R: Runtime>(config: Config<R>) -> TauriPlugin<R> {
   let config = Arc::new(config);
   let c = config.clone();
-  let mut builder = Builder::<R>::new("webview-bundle").setup(move |app, _api| {
-    #[cfg(desktop)]
-    let webview_bundle = desktop::init(app, c)?;
-    app.manage(webview_bundle);
-    Ok(())
-  });
+  let mut builder = Builder::<R>::new("webview-bundle")
+    .invoke_handler(tauri::generate_handler![
+      commands::list_bundles,
+      commands::load_version,
+      commands::update_version,
+      commands::apply_update,
+      commands::list_remotes,
+      commands::get_update,
+      commands::download_update,
+    ])
+    .setup(move |app, _api| {
+      #[cfg(desktop)]
+      let webview_bundle = desktop::init(app, c)?;
+      app.manage(webview_bundle);
+      Ok(())
+    });
   for protocol_config in &config.protocols {
     let scheme = protocol_config.scheme().to_string();
     builder = builder.register_asynchronous_uri_scheme_protocol(
@@ -53,7 +70,18 @@ pub fn init<R: Runtime>(config: Config<R>) -> TauriPlugin<R> {
           .unwrap_or_else(|| panic!("protocol not found: {scheme}"))
           .clone();
         tauri::async_runtime::spawn(async move {
-          match protocol.handle(req).await {
+          // wry only accepts complete bodies, but streaming still stops decoding range
+          // requests at the end of the requested range
+          let resp = match protocol.handle_stream(req).await {
+            Ok(resp) => {
+              let (parts, body) = resp.into_parts();
+              wvb::protocol::collect_stream(body)
+                .await
+                .map(|body| http::Response::from_parts(parts, body))
+            }
+            Err(e) => Err(e),
+          };
+          match resp {
             Ok(resp) => res.respond(resp),
             Err(e) => {
               let resp = http::Response::builder()
@@ -0,0 +1,199 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use wvb::source::BundleManifestData;
+use wvb::updater::INSTALL_ID_FILENAME;
+use wvb::{EXTENSION, MANIFEST_FILENAME};
+
+const WRITE_CHECK_FILENAME: &str = ".wvb-write-check";
+
+/// Copies the bundles of the legacy remote directory into the remote directory.
+///
+/// Runs only when the remote directory has no manifest yet and the legacy directory has one,
+/// so a migrated install is never migrated again.
+///
+/// Versions which are also in the builtin manifest are not copied, and the legacy directory
+/// is skipped entirely when it is the builtin directory: copies of builtin bundles would take
+/// priority over builtin bundles shipped by later app updates.
+pub(crate) fn migrate(legacy_dir: &Path, builtin_dir: &Path, dir: &Path) -> crate::Result<()> {
+  if is_same_dir(legacy_dir, dir)
+    || is_same_dir(legacy_dir, builtin_dir)
+    || dir.join(MANIFEST_FILENAME).exists()
+    || !legacy_dir.join(MANIFEST_FILENAME).exists()
+  {
+    return Ok(());
+  }
+  copy_bundles(legacy_dir, builtin_dir, dir)
+    .map_err(|e| crate::Error::FailToMigrateRemoteDir(e.to_string()))?;
+  Ok(())
+}
+
+fn is_same_dir(a: &Path, b: &Path) -> bool {
+  a == b || matches!((a.canonicalize(), b.canonicalize()), (Ok(a), Ok(b)) if a == b)
+}
+
+fn read_manifest(dir: &Path) -> io::Result<BundleManifestData> {
+  match fs::read(dir.join(MANIFEST_FILENAME)) {
+    Ok(raw) => serde_json::from_slice(&raw).map_err(io::Error::other),
+    Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(BundleManifestData::default()),
+    Err(e) => Err(e),
+  }
+}
+
+fn copy_bundles(legacy_dir: &Path, builtin_dir: &Path, dir: &Path) -> io::Result<()> {
+  let builtin = read_manifest(builtin_dir)?;
+  let mut manifest = read_manifest(legacy_dir)?;
+  manifest.entries.retain(|name, entry| {
+    let is_builtin = |version: &str| {
+      builtin
+        .entries
+        .get(name)
+        .is_some_and(|x| x.versions.contains_key(version))
+    };
+    // the builtin version is served anyway, and keeps being updated with the app
+    if is_builtin(&entry.current_version) {
+      return false;
+    }
+    entry.versions.retain(|version, _| !is_builtin(version));
+    let versions = &entry.versions;
+    entry
+      .channels
+      .retain(|_, version| versions.contains_key(version));
+    true
+  });
+  fs::create_dir_all(dir)?;
+  for (name, entry) in &manifest.entries {
+    fs::create_dir_all(dir.join(name))?;
+    for version in entry.versions.keys() {
+      let filename = format!("{name}_{version}.{EXTENSION}");
+      fs::copy(
+        legacy_dir.join(name).join(&filename),
+        dir.join(name).join(&filename),
+      )?;
+    }
+  }
+  // keeps the rollout buckets of the install
+  match fs::copy(
+    legacy_dir.join(INSTALL_ID_FILENAME),
+    dir.join(INSTALL_ID_FILENAME),
+  ) {
+    Ok(_) => {}
+    Err(e) if e.kind() == io::ErrorKind::NotFound => {}
+    Err(e) => return Err(e),
+  }
+  let raw = serde_json::to_vec(&manifest).map_err(io::Error::other)?;
+  fs::write(dir.join(MANIFEST_FILENAME), raw)?;
+  Ok(())
+}
+
+/// Creates the remote directory and checks that files can be written into it.
+pub(crate) fn ensure_writable(dir: &Path) -> crate::Result<()> {
+  let not_writable = |e: io::Error| crate::Error::RemoteDirNotWritable {
+    path: dir.to_path_buf(),
+    reason: e.to_string(),
+  };
+  fs::create_dir_all(dir).map_err(not_writable)?;
+  let filepath = dir.join(WRITE_CHECK_FILENAME);
+  fs::write(&filepath, b"").map_err(not_writable)?;
+  let _ = fs::remove_file(&filepath);
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use std::path::PathBuf;
+
+  fn temp_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir()
+      .join("wvb-tauri-tests")
+      .join(format!("{name}-{}", std::process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    fs::create_dir_all(&dir).unwrap();
+    dir
+  }
+
+  fn write_dir(dir: &Path, manifest: &str, versions: &[(&str, &str)]) {
+    fs::create_dir_all(dir).unwrap();
+    fs::write(dir.join(MANIFEST_FILENAME), manifest).unwrap();
+    for (name, version) in versions {
+      fs::create_dir_all(dir.join(name)).unwrap();
+      fs::write(
+        dir.join(name).join(format!("{name}_{version}.wvb")),
+        version,
+      )
+      .unwrap();
+    }
+  }
+
+  const BUILTIN_MANIFEST: &str = r#"{
+    "manifestVersion": 1,
+    "entries": {
+      "app": { "versions": { "1.0.0": {} }, "currentVersion": "1.0.0" }
+    }
+  }"#;
+
+  #[test]
+  fn legacy_dir_is_builtin_dir() {
+    let temp = temp_dir("legacy-is-builtin");
+    let builtin_dir = temp.join("builtin");
+    let dir = temp.join("remote");
+    write_dir(&builtin_dir, BUILTIN_MANIFEST, &[("app", "1.0.0")]);
+    migrate(&builtin_dir, &builtin_dir, &dir).unwrap();
+    assert!(!dir.join(MANIFEST_FILENAME).exists());
+    assert!(!dir.join("app").exists());
+  }
+
+  #[test]
+  fn skip_builtin_versions() {
+    let temp = temp_dir("skip-builtin-versions");
+    let builtin_dir = temp.join("builtin");
+    let legacy_dir = temp.join("legacy");
+    let dir = temp.join("remote");
+    write_dir(&builtin_dir, BUILTIN_MANIFEST, &[("app", "1.0.0")]);
+    write_dir(
+      &legacy_dir,
+      r#"{
+        "manifestVersion": 1,
+        "entries": {
+          "app": { "versions": { "1.0.0": {}, "1.1.0": {} }, "currentVersion": "1.1.0" },
+          "docs": { "versions": { "1.0.0": {} }, "currentVersion": "1.0.0" }
+        }
+      }"#,
+      &[("app", "1.0.0"), ("app", "1.1.0"), ("docs", "1.0.0")],
+    );
+    fs::write(legacy_dir.join(INSTALL_ID_FILENAME), "install").unwrap();
+    migrate(&legacy_dir, &builtin_dir, &dir).unwrap();
+
+    let manifest = read_manifest(&dir).unwrap();
+    let app = &manifest.entries["app"];
+    assert_eq!(app.current_version, "1.1.0");
+    assert_eq!(app.versions.keys().collect::<Vec<_>>(), vec!["1.1.0"]);
+    assert!(manifest.entries.contains_key("docs"));
+    assert!(!dir.join("app/app_1.0.0.wvb").exists());
+    assert!(dir.join("app/app_1.1.0.wvb").exists());
+    assert!(dir.join("docs/docs_1.0.0.wvb").exists());
+    assert_eq!(
+      fs::read_to_string(dir.join(INSTALL_ID_FILENAME)).unwrap(),
+      "install"
+    );
+
+    // never migrated again
+    fs::remove_file(dir.join("docs/docs_1.0.0.wvb")).unwrap();
+    migrate(&legacy_dir, &builtin_dir, &dir).unwrap();
+    assert!(!dir.join("docs/docs_1.0.0.wvb").exists());
+  }
+
+  #[test]
+  fn skip_entries_current_in_builtin() {
+    let temp = temp_dir("skip-current-in-builtin");
+    let builtin_dir = temp.join("builtin");
+    let legacy_dir = temp.join("legacy");
+    let dir = temp.join("remote");
+    write_dir(&builtin_dir, BUILTIN_MANIFEST, &[("app", "1.0.0")]);
+    write_dir(&legacy_dir, BUILTIN_MANIFEST, &[("app", "1.0.0")]);
+    migrate(&legacy_dir, &builtin_dir, &dir).unwrap();
+    assert!(read_manifest(&dir).unwrap().entries.is_empty());
+    assert!(!dir.join("app").exists());
+  }
+}